@@ -0,0 +1,26 @@
+//! Constants clients need to reproduce the program's math off-chain, re-exported from where the
+//! program defines them.
+//!
+//! ```
+//! use cbmm::constants::*;
+//!
+//! // Burn sizes are configured in x100 basis points and tracked by the limiter in x10k
+//! assert_eq!(X100_100_PERCENT_BP * SCALING_FACTOR, X10K_100_PERCENT_BP);
+//! let burn_bp_x100 = 20_000; // 2%
+//! assert_eq!(burn_bp_x100 * SCALING_FACTOR, 2_000_000);
+//!
+//! assert_eq!(DEFAULT_BASE_MINT_DECIMALS, 6);
+//! assert!(MIN_TOPUP_FEE_BP <= MAX_TOTAL_FEES_BP);
+//! ```
+use crate::state::PlatformConfig;
+
+pub use crate::helpers::{SCALING_FACTOR, X100_100_PERCENT_BP, X10K_100_PERCENT_BP};
+pub use crate::state::{DEFAULT_BASE_MINT_DECIMALS, DEFAULT_BASE_MINT_RESERVE, MIN_VIRTUAL_RESERVE};
+
+/// See `PlatformConfig::MAX_TOTAL_FEES_BP`
+pub const MAX_TOTAL_FEES_BP: u16 = PlatformConfig::MAX_TOTAL_FEES_BP;
+/// See `PlatformConfig::MIN_TOPUP_FEE_BP`. A platform may configure a higher floor, see
+/// `PlatformConfig::topup_fee_floor_bp`.
+pub const MIN_TOPUP_FEE_BP: u16 = PlatformConfig::MIN_TOPUP_FEE_BP;
+/// See `PlatformConfig::MAX_PLATFORM_FEE_BP`
+pub const MAX_PLATFORM_FEE_BP: u16 = PlatformConfig::MAX_PLATFORM_FEE_BP;
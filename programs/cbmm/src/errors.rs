@@ -56,4 +56,86 @@ pub enum CbmmError {
     InvalidBurnAuthority,
     #[msg("Burn tiers were updated too recently")]
     BurnTiersUpdatedTooRecently,
+    #[msg("Pool has circulating supply or unclaimed fees")]
+    PoolNotClosable,
+    #[msg("Invalid slippage parameters")]
+    InvalidSlippage,
+    #[msg("Invariant violated")]
+    InvariantViolated,
+    #[msg("Sell locked after recent buy")]
+    SellLocked,
+    #[msg("Invalid minimum base reserve")]
+    InvalidMinBaseReserve,
+    #[msg("Invalid burn reset interval")]
+    InvalidBurnResetInterval,
+    #[msg("Order trigger price not reached")]
+    OrderNotTriggered,
+    #[msg("Pool has already graduated")]
+    AlreadyGraduated,
+    #[msg("Pool has not graduated")]
+    NotGraduated,
+    #[msg("Liquidity migration is not configured")]
+    MigrationNotConfigured,
+    #[msg("Invalid AMM program")]
+    InvalidAmmProgram,
+    #[msg("Pool liquidity has already been migrated")]
+    AlreadyMigrated,
+    #[msg("Invalid virtual token account")]
+    InvalidVirtualTokenAccount,
+    #[msg("Virtual token account already exists")]
+    VirtualTokenAccountAlreadyExists,
+    #[msg("Signer is neither the owner nor the delegate")]
+    InvalidDelegate,
+    #[msg("Insufficient delegated amount")]
+    InsufficientDelegatedAmount,
+    #[msg("Account is not a versioned account")]
+    InvalidVersionedAccount,
+    #[msg("Invalid user burn allowance")]
+    InvalidBurnAllowance,
+    #[msg("Virtual token account is frozen")]
+    AccountFrozen,
+    #[msg("Buyer is not on the pool allowlist")]
+    NotAllowlisted,
+    #[msg("Platform is paused")]
+    PlatformPaused,
+    #[msg("Config changes are timelocked, use propose_config_change")]
+    ConfigChangeTimelocked,
+    #[msg("Config change is not effective yet")]
+    ConfigChangeNotEffective,
+    #[msg("Invalid config change delay")]
+    InvalidConfigChangeDelay,
+    #[msg("Token account does not belong to the platform fee recipient")]
+    InvalidFeeRecipient,
+    #[msg("Burn schedule is not due yet")]
+    BurnScheduleNotDue,
+    #[msg("Invalid burn schedule interval")]
+    InvalidBurnScheduleInterval,
+    #[msg("Burn tiers exceed the platform daily burn envelope")]
+    BurnEnvelopeExceeded,
+    #[msg("Burn allowance is still valid for the current burn tiers")]
+    BurnAllowanceNotStale,
+    #[msg("Sweep accounts must be [platform_config, pool, pool_ata] triples")]
+    InvalidSweepAccounts,
+    #[msg("Buy would take the virtual token account above the pool position cap")]
+    PositionCapExceeded,
+    #[msg("Pool token account holds less than the sell output")]
+    InsufficientPoolLiquidity,
+    #[msg("Pool creator has not locked the platform's minimum initial liquidity")]
+    InitialLockMissing,
+    #[msg("Platform still has open pools")]
+    PlatformHasOpenPools,
+    #[msg("Allowed quote mints must be unique, at most 5 and exclude the platform quote mint")]
+    InvalidAllowedQuoteMints,
+    #[msg("Pool creator can only burn on their pool through a PoolOwner tier")]
+    CreatorMustUsePoolOwnerTier,
+    #[msg("Pool requires the creator to co-sign burns")]
+    CreatorCosignRequired,
+    #[msg("Quote batch must hold between 1 and 10 amounts")]
+    InvalidQuoteBatch,
+    #[msg("Fee holiday runs past the platform's maximum")]
+    FeeHolidayTooLong,
+    #[msg("Pool entry is missing or belongs to another pool")]
+    InvalidPoolEntry,
+    #[msg("Event interval exceeds the platform's maximum")]
+    EventIntervalTooLong,
 }
@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+
+/// Anchor discriminator of `deposit_liquidity`: sha256("global:deposit_liquidity")[..8]
+pub const DEPOSIT_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [245, 99, 59, 25, 151, 71, 233, 249];
+
+/// Deposit interfaces of external AMMs that graduated pools can migrate their reserves into
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace, PartialEq)]
+pub enum AmmInterface {
+    /// Anchor program exposing `deposit_liquidity(quote_amount: u64, base_amount: u64)`
+    #[default]
+    AnchorDepositLiquidity,
+}
+
+impl AmmInterface {
+    pub fn deposit_instruction(
+        &self,
+        program_id: Pubkey,
+        accounts: Vec<AccountMeta>,
+        quote_amount: u64,
+        base_amount: u64,
+    ) -> Instruction {
+        let data = match self {
+            AmmInterface::AnchorDepositLiquidity => {
+                let mut data = DEPOSIT_LIQUIDITY_DISCRIMINATOR.to_vec();
+                data.extend_from_slice(&quote_amount.to_le_bytes());
+                data.extend_from_slice(&base_amount.to_le_bytes());
+                data
+            }
+        };
+        Instruction {
+            program_id,
+            accounts,
+            data,
+        }
+    }
+}
+
+/// External AMM that graduated pools migrate into
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace, PartialEq)]
+pub struct MigrationAmm {
+    pub program_id: Pubkey,
+    pub interface: AmmInterface,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anchor_deposit_liquidity_instruction_data() {
+        let program_id = Pubkey::new_unique();
+        let ix = AmmInterface::AnchorDepositLiquidity.deposit_instruction(
+            program_id,
+            vec![],
+            1_000,
+            2_000,
+        );
+        assert_eq!(ix.program_id, program_id);
+        assert_eq!(ix.data.len(), 24);
+        assert_eq!(ix.data[..8], DEPOSIT_LIQUIDITY_DISCRIMINATOR);
+        assert_eq!(ix.data[8..16], 1_000u64.to_le_bytes());
+        assert_eq!(ix.data[16..], 2_000u64.to_le_bytes());
+    }
+}
@@ -5,7 +5,7 @@ pub const X10K_100_PERCENT_BP: u64 = 100_000_000;
 pub const X100_100_PERCENT_BP: u64 = 1_000_000;
 pub const SCALING_FACTOR: u64 = X10K_100_PERCENT_BP / X100_100_PERCENT_BP;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Fees {
     pub creator_fees_amount: u64,
     pub buyback_fees_amount: u64,
@@ -27,19 +27,22 @@ pub fn calculate_fees(
     if platform_fee_bp > 10000 || creator_fee_bp > 10000 || buyback_fee_bp > 10000 {
         return Err(CbmmError::InvalidFeeBasisPoints.into());
     }
-    if u64::MAX / (platform_fee_bp as u64) < quote_amount
-        || u64::MAX / (creator_fee_bp as u64) < quote_amount
-        || u64::MAX / (buyback_fee_bp as u64) < quote_amount
-    {
+    // A zero fee can't overflow
+    let exceeds = |fee_bp: u16| {
+        u64::MAX
+            .checked_div(fee_bp as u64)
+            .is_some_and(|max| max < quote_amount)
+    };
+    if exceeds(platform_fee_bp) || exceeds(creator_fee_bp) || exceeds(buyback_fee_bp) {
         return Err(CbmmError::AmountTooBig.into());
     }
-    // Use ceiling division for fees to avoid rounding down: ceil(x / d) = (x + d - 1) / d
+    // Use ceiling division for fees to avoid rounding down
     let creator_fees_amount =
-        ((quote_amount as u128 * creator_fee_bp as u128 + 9999) / 10000) as u64;
+        (quote_amount as u128 * creator_fee_bp as u128).div_ceil(10000) as u64;
     let buyback_fees_amount =
-        ((quote_amount as u128 * buyback_fee_bp as u128 + 9999) / 10000) as u64;
+        (quote_amount as u128 * buyback_fee_bp as u128).div_ceil(10000) as u64;
     let platform_fees_amount =
-        ((quote_amount as u128 * platform_fee_bp as u128 + 9999) / 10000) as u64;
+        (quote_amount as u128 * platform_fee_bp as u128).div_ceil(10000) as u64;
     Ok(Fees {
         creator_fees_amount,
         buyback_fees_amount,
@@ -53,25 +56,148 @@ pub fn calculate_buy_output_amount(
     quote_reserve: u64,
     base_reserve: u64,
     quote_virtual_reserve: u64,
-) -> u64 {
+) -> Result<u64> {
+    let numerator = base_reserve as u128 * quote_amount as u128;
+    let denominator = quote_reserve as u128 + quote_virtual_reserve as u128 + quote_amount as u128;
+    u64::try_from(numerator / denominator).map_err(|_| CbmmError::MathOverflow.into())
+}
+
+/// `calculate_buy_output_amount` rounded up, handing the buyer the sub-unit remainder. Capped
+/// below the B reserve, so a buy never empties the pool.
+pub fn calculate_buy_output_amount_rounded_up(
+    quote_amount: u64,
+    quote_reserve: u64,
+    base_reserve: u64,
+    quote_virtual_reserve: u64,
+) -> Result<u64> {
     let numerator = base_reserve as u128 * quote_amount as u128;
     let denominator = quote_reserve as u128 + quote_virtual_reserve as u128 + quote_amount as u128;
-    (numerator / denominator) as u64
+    let output = numerator.div_ceil(denominator).min(base_reserve.saturating_sub(1) as u128);
+    u64::try_from(output).map_err(|_| CbmmError::MathOverflow.into())
+}
+
+/// Smallest amount of Mint A a buy has to swap, after fees, to receive at least one B.
+/// `None` when the pool has no more than one B left to sell.
+pub fn calculate_min_buy_input_amount(
+    quote_reserve: u64,
+    base_reserve: u64,
+    quote_virtual_reserve: u64,
+) -> Option<u64> {
+    if base_reserve <= 1 {
+        return None;
+    }
+    // B * x / (A + V + x) >= 1  <=>  x >= (A + V) / (B - 1)
+    let min_amount = (quote_reserve as u128 + quote_virtual_reserve as u128)
+        .div_ceil(base_reserve as u128 - 1)
+        .max(1);
+    u64::try_from(min_amount).ok()
+}
+
+/// Calculates the amount of Mint B received at the current spot price, i.e. with no price impact.
+pub fn calculate_ideal_buy_output_amount(
+    quote_amount: u64,
+    quote_reserve: u64,
+    base_reserve: u64,
+    quote_virtual_reserve: u64,
+) -> Result<u64> {
+    let numerator = base_reserve as u128 * quote_amount as u128;
+    let denominator = quote_reserve as u128 + quote_virtual_reserve as u128;
+    u64::try_from(numerator / denominator).map_err(|_| CbmmError::MathOverflow.into())
+}
+
+/// Spot price of Mint B denominated in Mint A, (A + V) / B, as a Q64.64 fixed point number.
+pub fn calculate_spot_price_x64(
+    quote_reserve: u64,
+    quote_virtual_reserve: u64,
+    base_reserve: u64,
+) -> Result<u128> {
+    (quote_reserve as u128 + quote_virtual_reserve as u128)
+        .checked_mul(1 << 64)
+        .and_then(|numerator| numerator.checked_div(base_reserve as u128))
+        .ok_or(CbmmError::MathOverflow.into())
+}
+
+/// Marginal price paid per B on a buy once `total_fee_bp` is taken from the A sent, as Q64.64.
+/// Rounded up, so it never quotes better than execution.
+pub fn calculate_effective_buy_price_x64(spot_price_x64: u128, total_fee_bp: u32) -> Result<u128> {
+    require!(total_fee_bp < 10000, CbmmError::InvalidFeeBasisPoints);
+    spot_price_x64
+        .checked_mul(10000)
+        .map(|numerator| numerator.div_ceil(10000 - total_fee_bp as u128))
+        .ok_or(CbmmError::MathOverflow.into())
+}
+
+/// Marginal price received per B on a sell once `total_fee_bp` is taken from the A paid out, as
+/// Q64.64. Rounded down, so it never quotes better than execution.
+pub fn calculate_effective_sell_price_x64(spot_price_x64: u128, total_fee_bp: u32) -> Result<u128> {
+    require!(total_fee_bp <= 10000, CbmmError::InvalidFeeBasisPoints);
+    spot_price_x64
+        .checked_mul(10000 - total_fee_bp as u128)
+        .map(|numerator| numerator / 10000)
+        .ok_or(CbmmError::MathOverflow.into())
+}
+
+/// Converts a Q64.64 price between raw units into the price of one whole B in whole A by
+/// scaling with `10^base_decimals / 10^quote_decimals`. The curve itself only ever sees raw
+/// units, so this is needed just where a price is shown or compared across mints.
+pub fn normalize_price_decimals_x64(
+    price_x64: u128,
+    base_decimals: u8,
+    quote_decimals: u8,
+) -> Result<u128> {
+    if base_decimals >= quote_decimals {
+        10u128
+            .checked_pow((base_decimals - quote_decimals) as u32)
+            .and_then(|scale| price_x64.checked_mul(scale))
+            .ok_or(CbmmError::MathOverflow.into())
+    } else {
+        Ok(10u128
+            .checked_pow((quote_decimals - base_decimals) as u32)
+            .map_or(0, |scale| price_x64 / scale))
+    }
+}
+
+/// Minimum acceptable output when allowing `slippage_bps` below the ideal output.
+pub fn calculate_min_output_with_slippage(ideal_output: u64, slippage_bps: u16) -> Result<u64> {
+    require!(slippage_bps <= 10000, CbmmError::InvalidSlippage);
+    Ok((ideal_output as u128 * (10000 - slippage_bps) as u128 / 10000) as u64)
 }
 
-// todo overflow and underflow checks
 /// Calculates the amount of Mint A received when selling Mint B.
 pub fn calculate_sell_output_amount(
     base_amount: u64,
     base_reserve: u64,
     quote_reserve: u64,
     quote_virtual_reserve: u64,
-) -> u64 {
-    let numerator = base_amount as u128 * (quote_reserve as u128 + quote_virtual_reserve as u128);
+) -> Result<u64> {
+    let numerator = (base_amount as u128)
+        .checked_mul(quote_reserve as u128 + quote_virtual_reserve as u128)
+        .ok_or(CbmmError::MathOverflow)?;
     let denominator = base_reserve as u128 + base_amount as u128;
-    (numerator / denominator) as u64
+    u64::try_from(numerator / denominator).map_err(|_| CbmmError::MathOverflow.into())
 }
 
+/// `calculate_sell_output_amount` rounded up, handing the seller the sub-unit remainder. Capped
+/// below the real and virtual A combined, as the floored output always is.
+pub fn calculate_sell_output_amount_rounded_up(
+    base_amount: u64,
+    base_reserve: u64,
+    quote_reserve: u64,
+    quote_virtual_reserve: u64,
+) -> Result<u64> {
+    let quote_total = quote_reserve as u128 + quote_virtual_reserve as u128;
+    let numerator = (base_amount as u128)
+        .checked_mul(quote_total)
+        .ok_or(CbmmError::MathOverflow)?;
+    let denominator = base_reserve as u128 + base_amount as u128;
+    let output = numerator
+        .div_ceil(denominator)
+        .min(quote_total.saturating_sub(1));
+    u64::try_from(output).map_err(|_| CbmmError::MathOverflow.into())
+}
+
+/// B removed from `base_reserve` by a burn of `base_amount_bp_x100`, which is in basis points
+/// times 100: `X100_100_PERCENT_BP` (1_000_000) is the whole reserve, 10_000 is 1%.
 pub fn calculate_burn_amount(base_amount_bp_x100: u64, base_reserve: u64) -> u64 {
     (base_reserve as u128 * base_amount_bp_x100 as u128 / X100_100_PERCENT_BP as u128) as u64
 }
@@ -94,7 +220,7 @@ pub fn calculate_optimal_virtual_quote_reserve(
     let numerator = quote_starting_virtual_reserve as u128 * base_total_supply as u128;
     let denominator = base_starting_total_supply as u128;
     // Rounding up to be sure that we stay solvent
-    ((numerator + denominator - 1) / denominator) as u64
+    numerator.div_ceil(denominator) as u64
 }
 
 pub fn calculate_optimal_real_quote_reserve(
@@ -106,7 +232,7 @@ pub fn calculate_optimal_real_quote_reserve(
         quote_optimal_virtual_reserve as u128 * (base_total_supply as u128 - base_reserve as u128);
     let denominator = base_reserve as u128;
     // Rounding up to be sure that the worst-case exit price is always at least the original price
-    ((numerator + denominator - 1) / denominator) as u64
+    numerator.div_ceil(denominator) as u64
 }
 
 pub fn calculate_new_virtual_reserve_after_topup(
@@ -123,6 +249,41 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
+    #[test]
+    fn test_calculate_min_buy_input_amount() {
+        // (0 + 1e12) / (2M - 1) rounded up
+        let min_amount = calculate_min_buy_input_amount(0, 2_000_000, 1_000_000_000_000).unwrap();
+        assert_eq!(min_amount, 500_001);
+        assert_eq!(
+            calculate_buy_output_amount(min_amount, 0, 2_000_000, 1_000_000_000_000).unwrap(),
+            1
+        );
+        assert_eq!(
+            calculate_buy_output_amount(min_amount - 1, 0, 2_000_000, 1_000_000_000_000)
+                .unwrap(),
+            0
+        );
+        assert_eq!(calculate_min_buy_input_amount(0, 1, 1_000_000), None);
+    }
+
+    #[test]
+    fn test_sell_output_overflowing_u64_errors() {
+        // A + V is close to 2^65, so selling nearly all of B owes more than u64::MAX
+        let result = calculate_sell_output_amount(1 << 62, 1, u64::MAX, u64::MAX);
+        assert_eq!(result.unwrap_err(), CbmmError::MathOverflow.into());
+        let result = calculate_sell_output_amount_rounded_up(1 << 62, 1, u64::MAX, u64::MAX);
+        assert_eq!(result.unwrap_err(), CbmmError::MathOverflow.into());
+        // The intermediate product itself no longer fits in u128
+        let result = calculate_sell_output_amount(u64::MAX, 1, u64::MAX, u64::MAX);
+        assert_eq!(result.unwrap_err(), CbmmError::MathOverflow.into());
+
+        // Just within u64 still quotes
+        assert_eq!(
+            calculate_sell_output_amount(1, 1, u64::MAX, 1).unwrap(),
+            1u64 << 63
+        );
+    }
+
     #[test]
     fn test_calculate_fees() {
         let fees = calculate_fees(1_000_000_000, 1000, 2000, 3000).unwrap();
@@ -141,6 +302,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_calculate_spot_price_x64() {
+        // (1000 + 1000) / 4000 = 0.5
+        assert_eq!(calculate_spot_price_x64(1000, 1000, 4000).unwrap(), 1 << 63);
+        assert_eq!(calculate_spot_price_x64(0, 3, 1).unwrap(), 3 << 64);
+        assert!(calculate_spot_price_x64(1, 1, 0).is_err());
+        assert!(calculate_spot_price_x64(u64::MAX, u64::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_normalize_price_decimals_x64() {
+        // 0.5 raw A per raw B with 9 decimal B and 6 decimal A is 500 A per B
+        assert_eq!(
+            normalize_price_decimals_x64(1 << 63, 9, 6).unwrap(),
+            500 << 64
+        );
+        assert_eq!(
+            normalize_price_decimals_x64(500 << 64, 6, 9).unwrap(),
+            1 << 63
+        );
+        assert_eq!(normalize_price_decimals_x64(1 << 63, 6, 6).unwrap(), 1 << 63);
+        assert!(normalize_price_decimals_x64(u128::MAX, 9, 6).is_err());
+    }
+
+    #[test]
+    fn test_swap_outputs_scale_with_base_decimals() {
+        // The same pool with B at 9 instead of 6 decimals gives 1000x the raw B and the same A
+        let quote_amount = 4500;
+        let base_output_6 =
+            calculate_buy_output_amount(quote_amount, 0, 2_000_000, 1_000_000).unwrap();
+        let base_output_9 =
+            calculate_buy_output_amount(quote_amount, 0, 2_000_000_000, 1_000_000).unwrap();
+        assert_eq!(base_output_6, 8959);
+        assert_eq!(base_output_9, 8_959_681);
+        assert_eq!(base_output_9 / 1000, base_output_6);
+
+        // Selling it all back returns the same A, less rounding
+        let quote_output_6 = calculate_sell_output_amount(
+            base_output_6,
+            2_000_000 - base_output_6,
+            quote_amount,
+            1_000_000,
+        )
+        .unwrap();
+        let quote_output_9 = calculate_sell_output_amount(
+            base_output_9,
+            2_000_000_000 - base_output_9,
+            quote_amount,
+            1_000_000,
+        )
+        .unwrap();
+        assert_eq!(quote_output_6, 4499);
+        assert_eq!(quote_output_9, 4499);
+    }
+
+    #[test]
+    fn test_calculate_effective_prices_x64() {
+        let spot_price_x64 = 1 << 64;
+        // 10% fees: 1 / 0.9 and 1 * 0.9
+        assert_eq!(
+            calculate_effective_buy_price_x64(spot_price_x64, 1000).unwrap(),
+            (10u128 << 64).div_ceil(9)
+        );
+        assert_eq!(
+            calculate_effective_sell_price_x64(spot_price_x64, 1000).unwrap(),
+            (9u128 << 64) / 10
+        );
+        assert_eq!(
+            calculate_effective_buy_price_x64(spot_price_x64, 0).unwrap(),
+            spot_price_x64
+        );
+        assert_eq!(
+            calculate_effective_sell_price_x64(spot_price_x64, 10000).unwrap(),
+            0
+        );
+        assert!(calculate_effective_buy_price_x64(spot_price_x64, 10000).is_err());
+        assert!(calculate_effective_sell_price_x64(spot_price_x64, 10001).is_err());
+        assert!(calculate_effective_buy_price_x64(u128::MAX, 1).is_err());
+    }
+
+    #[test]
+    fn test_ideal_buy_output_overflowing_u64_errors() {
+        let result = calculate_ideal_buy_output_amount(u64::MAX, 0, u64::MAX, 1);
+        assert_eq!(result.unwrap_err(), CbmmError::MathOverflow.into());
+
+        // Just within u64 still quotes
+        assert_eq!(
+            calculate_ideal_buy_output_amount(1, 0, u64::MAX, 1).unwrap(),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_calculate_min_output_with_slippage() {
+        let ideal = calculate_ideal_buy_output_amount(4500, 0, 2_000_000, 1_000_000).unwrap();
+        assert_eq!(ideal, 9000);
+        let actual = calculate_buy_output_amount(4500, 0, 2_000_000, 1_000_000).unwrap();
+        assert!(actual < ideal);
+
+        assert_eq!(calculate_min_output_with_slippage(ideal, 0).unwrap(), 9000);
+        assert_eq!(calculate_min_output_with_slippage(ideal, 50).unwrap(), 8955);
+        assert_eq!(calculate_min_output_with_slippage(ideal, 10000).unwrap(), 0);
+        assert_eq!(
+            calculate_min_output_with_slippage(ideal, 10001).unwrap_err(),
+            CbmmError::InvalidSlippage.into()
+        );
+    }
+
     #[test]
     fn test_calculate_amount_too_big() {
         let result = calculate_fees(u64::MAX, 10000, 10000, 10000);
@@ -148,6 +417,14 @@ mod tests {
         assert_eq!(result.unwrap_err(), CbmmError::AmountTooBig.into());
     }
 
+    #[test]
+    fn test_calculate_fees_zero_fee_bp() {
+        let fees = calculate_fees(1_000_000_000, 0, 600, 0).unwrap();
+        assert_eq!(fees.creator_fees_amount, 0);
+        assert_eq!(fees.buyback_fees_amount, 60_000_000);
+        assert_eq!(fees.platform_fees_amount, 0);
+    }
+
     #[test]
     fn test_calculate_fees_creator_fee_bp_overflow() {
         let result = calculate_fees(1_000_000_000, 10000, 10001, 10000);
@@ -168,4 +445,133 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), CbmmError::InvalidFeeBasisPoints.into());
     }
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        // Bounds cover dust up to ~1e15 quote / 1e18 base, beyond any realistic pool
+        proptest! {
+            #[test]
+            fn prop_buy_output_within_reserve_and_invariant_non_decreasing(
+                quote_reserve in 0u64..1_000_000_000_000_000,
+                quote_virtual_reserve in 1u64..1_000_000_000_000_000,
+                base_reserve in 1u64..1_000_000_000_000_000_000,
+                quote_amount in 1u64..1_000_000_000_000_000,
+            ) {
+                let output = calculate_buy_output_amount(
+                    quote_amount,
+                    quote_reserve,
+                    base_reserve,
+                    quote_virtual_reserve,
+                )
+                .unwrap();
+                prop_assert!(output < base_reserve);
+
+                let k_before = (quote_reserve as u128 + quote_virtual_reserve as u128) * base_reserve as u128;
+                let k_after = (quote_reserve as u128 + quote_virtual_reserve as u128 + quote_amount as u128)
+                    * (base_reserve - output) as u128;
+                prop_assert!(k_after >= k_before);
+            }
+
+            #[test]
+            fn prop_sell_output_within_reserve_and_invariant_non_decreasing(
+                quote_reserve in 0u64..1_000_000_000_000_000,
+                quote_virtual_reserve in 1u64..1_000_000_000_000_000,
+                base_reserve in 1u64..1_000_000_000_000_000_000,
+                base_amount in 1u64..1_000_000_000_000_000_000,
+            ) {
+                let output = calculate_sell_output_amount(
+                    base_amount,
+                    base_reserve,
+                    quote_reserve,
+                    quote_virtual_reserve,
+                )
+                .unwrap();
+                let quote_total = quote_reserve as u128 + quote_virtual_reserve as u128;
+                prop_assert!((output as u128) < quote_total);
+
+                let k_before = quote_total * base_reserve as u128;
+                let k_after = (quote_total - output as u128) * (base_reserve as u128 + base_amount as u128);
+                prop_assert!(k_after >= k_before);
+            }
+
+            #[test]
+            fn prop_rounded_up_buy_output_loses_less_than_one_unit(
+                quote_reserve in 0u64..1_000_000_000_000_000,
+                quote_virtual_reserve in 1u64..1_000_000_000_000_000,
+                base_reserve in 1u64..1_000_000_000_000_000_000,
+                quote_amount in 1u64..1_000_000_000_000_000,
+            ) {
+                let floor = calculate_buy_output_amount(
+                    quote_amount,
+                    quote_reserve,
+                    base_reserve,
+                    quote_virtual_reserve,
+                )
+                .unwrap();
+                let output = calculate_buy_output_amount_rounded_up(
+                    quote_amount,
+                    quote_reserve,
+                    base_reserve,
+                    quote_virtual_reserve,
+                )
+                .unwrap();
+                prop_assert!(output < base_reserve);
+                prop_assert!(output >= floor && output - floor <= 1);
+
+                // The invariant can only drop by the remainder handed to the buyer
+                let quote_after = quote_reserve as u128 + quote_virtual_reserve as u128 + quote_amount as u128;
+                let k_before = (quote_reserve as u128 + quote_virtual_reserve as u128) * base_reserve as u128;
+                let k_after = quote_after * (base_reserve - output) as u128;
+                prop_assert!(k_after + quote_after >= k_before);
+            }
+
+            #[test]
+            fn prop_rounded_up_sell_output_loses_less_than_one_unit(
+                quote_reserve in 0u64..1_000_000_000_000_000,
+                quote_virtual_reserve in 1u64..1_000_000_000_000_000,
+                base_reserve in 1u64..1_000_000_000_000_000_000,
+                base_amount in 1u64..1_000_000_000_000_000_000,
+            ) {
+                let floor = calculate_sell_output_amount(
+                    base_amount,
+                    base_reserve,
+                    quote_reserve,
+                    quote_virtual_reserve,
+                )
+                .unwrap();
+                let output = calculate_sell_output_amount_rounded_up(
+                    base_amount,
+                    base_reserve,
+                    quote_reserve,
+                    quote_virtual_reserve,
+                )
+                .unwrap();
+                let quote_total = quote_reserve as u128 + quote_virtual_reserve as u128;
+                prop_assert!((output as u128) < quote_total);
+                prop_assert!(output >= floor && output - floor <= 1);
+
+                let base_after = base_reserve as u128 + base_amount as u128;
+                let k_before = quote_total * base_reserve as u128;
+                let k_after = (quote_total - output as u128) * base_after;
+                prop_assert!(k_after + base_after >= k_before);
+            }
+
+            #[test]
+            fn prop_fees_never_exceed_bp_share(
+                quote_amount in 0u64..1_000_000_000_000_000,
+                creator_fee_bp in 0u16..=1000,
+                buyback_fee_bp in 0u16..=1000,
+                platform_fee_bp in 0u16..=1000,
+            ) {
+                let fees = calculate_fees(quote_amount, creator_fee_bp, buyback_fee_bp, platform_fee_bp).unwrap();
+                let total_bp = (creator_fee_bp + buyback_fee_bp + platform_fee_bp) as u128;
+                // Each fee rounds up by less than one unit
+                prop_assert!(
+                    fees.total_fees_amount() as u128 <= quote_amount as u128 * total_bp / 10000 + 3
+                );
+            }
+        }
+    }
 }
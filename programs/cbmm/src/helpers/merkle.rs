@@ -0,0 +1,57 @@
+use solana_sha256_hasher::hashv;
+
+// Leaves and inner nodes are domain-separated so a node can never be presented as a leaf
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn merkle_leaf(value: &[u8]) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, value]).to_bytes()
+}
+
+/// Parent of two nodes. Children are sorted, so proofs need no left/right flags.
+pub fn merkle_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, lo, hi]).to_bytes()
+}
+
+pub fn verify_merkle_proof(root: &[u8; 32], value: &[u8], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(merkle_leaf(value), |node, sibling| {
+        merkle_node(&node, sibling)
+    });
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let values: Vec<[u8; 32]> = (1..=3u8).map(|i| [i; 32]).collect();
+        let leaves: Vec<[u8; 32]> = values.iter().map(|value| merkle_leaf(value)).collect();
+        // Odd leaf is carried up unchanged
+        let left = merkle_node(&leaves[0], &leaves[1]);
+        let root = merkle_node(&left, &leaves[2]);
+
+        assert!(verify_merkle_proof(
+            &root,
+            &values[0],
+            &[leaves[1], leaves[2]]
+        ));
+        assert!(verify_merkle_proof(
+            &root,
+            &values[1],
+            &[leaves[0], leaves[2]]
+        ));
+        assert!(verify_merkle_proof(&root, &values[2], &[left]));
+
+        assert!(!verify_merkle_proof(
+            &root,
+            &[4; 32],
+            &[leaves[1], leaves[2]]
+        ));
+        assert!(!verify_merkle_proof(&root, &values[0], &[leaves[2]]));
+        // An inner node is not accepted as a leaf
+        assert!(!verify_merkle_proof(&root, &left, &[leaves[2]]));
+    }
+}
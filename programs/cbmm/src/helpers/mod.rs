@@ -1,5 +1,9 @@
+mod amm;
 mod math;
+mod merkle;
 mod rate_limit;
 
+pub use amm::*;
 pub use math::*;
+pub use merkle::*;
 pub use rate_limit::*;
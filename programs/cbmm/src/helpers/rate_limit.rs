@@ -51,6 +51,17 @@ impl BurnRateLimiter {
         }
     }
 
+    /// Stress a limiter starts from: 3/4 of total fees, so a pool is not exploitable right away
+    pub fn initial_stress_bp_x10k(total_fee_bp: u16) -> u64 {
+        total_fee_bp as u64 * 100 * 3 / 4
+    }
+
+    /// Restarts the stress from the initial value for `total_fee_bp`. Queued burns are kept.
+    pub fn resync(&mut self, now: i64, total_fee_bp: u16) {
+        self.accumulated_stress_bp_x10k = Self::initial_stress_bp_x10k(total_fee_bp);
+        self.last_update_ts = now;
+    }
+
     /// Geometric add: result = 1 - (1 - a) * (1 - b), in x10k basis points.
     /// Used for combining queue or stress in a share-based way.
     fn compound_add(current_x10k: u64, new_x10k: u64) -> Result<u64> {
@@ -217,6 +228,7 @@ mod tests {
         RateLimitResult::ExecuteFull(27_756);
         "full_flush_after_long_cooldown"
     )]
+    #[allow(clippy::too_many_arguments)]
     fn test_try_burn_and_flush_scenarios(
         // State before
         pre_accumulated_stress_bp_x10k: u64,
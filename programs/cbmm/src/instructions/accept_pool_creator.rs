@@ -0,0 +1,137 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptPoolCreator<'info> {
+    #[account(
+        constraint = pool.pending_creator == Some(new_creator.key()) @ CbmmError::InvalidPoolCreator
+    )]
+    pub new_creator: Signer<'info>,
+
+    // The PDA stays derived from `seed_creator`, so the pool keeps its address
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Second step of the creator handover. From here on the new creator holds the
+/// `PoolOwner` burn tier and claims creator fees, including any unclaimed balance.
+pub fn accept_pool_creator(ctx: Context<AcceptPoolCreator>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.creator = ctx.accounts.new_creator.key();
+    pool.pending_creator = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let creator = Keypair::new();
+        let new_creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner.airdrop(&new_creator.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&creator, 9);
+
+        let platform_config = runner.create_platform_config_mock(
+            &creator,
+            quote_mint,
+            5,
+            5,
+            2,
+            1,
+            200,
+            600,
+            200,
+            None,
+        );
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            1000,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&creator, quote_mint, &pool.pool);
+        runner.mint_tokens(&creator, pool.pool, quote_mint, 1000);
+
+        (runner, creator, new_creator, pool.pool, quote_mint)
+    }
+
+    #[test]
+    fn test_transfer_pool_creator() {
+        let (mut runner, creator, new_creator, pool, quote_mint) = setup_test();
+
+        runner
+            .propose_pool_creator(&creator, pool, new_creator.pubkey())
+            .unwrap();
+        runner.accept_pool_creator(&new_creator, pool).unwrap();
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.creator.to_bytes(), new_creator.pubkey().to_bytes());
+        assert_eq!(pool_data.seed_creator.to_bytes(), creator.pubkey().to_bytes());
+        assert!(pool_data.pending_creator.is_none());
+
+        // Creator-gated instructions follow the new creator, the old one is locked out
+        let old_creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        assert!(runner
+            .claim_creator_fees(&creator, old_creator_ata, quote_mint, pool)
+            .is_err());
+        let new_creator_ata =
+            runner.create_associated_token_account(&new_creator, quote_mint, &new_creator.pubkey());
+        runner
+            .claim_creator_fees(&new_creator, new_creator_ata, quote_mint, pool)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_accept_pool_creator_wrong_acceptor() {
+        let (mut runner, creator, new_creator, pool, _) = setup_test();
+        let intruder = Keypair::new();
+        runner.airdrop(&intruder.pubkey(), 10_000_000_000);
+
+        // Nothing proposed yet
+        assert!(runner.accept_pool_creator(&new_creator, pool).is_err());
+
+        runner
+            .propose_pool_creator(&creator, pool, new_creator.pubkey())
+            .unwrap();
+        assert!(runner.accept_pool_creator(&intruder, pool).is_err());
+
+        // Only the current creator can propose
+        assert!(runner
+            .propose_pool_creator(&intruder, pool, intruder.pubkey())
+            .is_err());
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.creator.to_bytes(), creator.pubkey().to_bytes());
+    }
+}
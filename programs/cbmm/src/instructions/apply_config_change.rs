@@ -0,0 +1,139 @@
+use crate::errors::CbmmError;
+use crate::instructions::apply_platform_config_update;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ApplyConfigChange<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [PENDING_CONFIG_CHANGE_SEED, platform_config.key().as_ref()],
+        bump = pending_config_change.bump,
+    )]
+    pub pending_config_change: Account<'info, PendingConfigChange>,
+}
+
+/// Writes the staged config change once it is due. A new `burn_tiers` list takes effect, and
+/// invalidates existing allowances, only at this point.
+pub fn apply_config_change(ctx: Context<ApplyConfigChange>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let pending_config_change = &mut ctx.accounts.pending_config_change;
+    require_gte!(
+        now,
+        pending_config_change.effective_at,
+        CbmmError::ConfigChangeNotEffective
+    );
+
+    apply_platform_config_update(
+        &mut ctx.accounts.platform_config,
+        std::mem::take(&mut pending_config_change.args),
+        now,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{BurnRole, BurnTier, PlatformConfig, UpdatePlatformConfigArgs};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+    const DELAY: i64 = 86400;
+
+    fn get_platform_config(runner: &TestRunner, platform_config: &Pubkey) -> PlatformConfig {
+        let account = runner.svm.get_account(platform_config).unwrap();
+        PlatformConfig::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let mut platform_config_data = get_platform_config(&runner, &platform_config);
+        platform_config_data.config_change_delay_seconds = DELAY;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+        runner.set_system_clock(NOW);
+        (runner, admin, platform_config)
+    }
+
+    fn burn_tiers_change() -> UpdatePlatformConfigArgs {
+        UpdatePlatformConfigArgs {
+            platform_fee_bp: Some(300),
+            burn_tiers: Some(vec![BurnTier {
+                burn_bp_x100: 500,
+                role: BurnRole::Anyone,
+                max_daily_burns: 3,
+                expires_at: None,
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apply_config_change_before_effective_at() {
+        let (mut runner, admin, platform_config) = setup_test();
+        runner
+            .propose_config_change(&admin, platform_config, burn_tiers_change())
+            .unwrap();
+
+        runner.set_system_clock(NOW + DELAY - 1);
+        assert!(runner.apply_config_change(&admin, platform_config).is_err());
+        assert_eq!(
+            get_platform_config(&runner, &platform_config).platform_fee_bp,
+            200
+        );
+    }
+
+    #[test]
+    fn test_apply_config_change_on_time() {
+        let (mut runner, admin, platform_config) = setup_test();
+        runner
+            .propose_config_change(&admin, platform_config, burn_tiers_change())
+            .unwrap();
+
+        runner.set_system_clock(NOW + DELAY);
+        runner.apply_config_change(&admin, platform_config).unwrap();
+
+        let platform_config_data = get_platform_config(&runner, &platform_config);
+        assert_eq!(platform_config_data.platform_fee_bp, 300);
+        assert_eq!(platform_config_data.burn_tiers.len(), 1);
+        assert_eq!(platform_config_data.burn_tiers_updated_at, NOW + DELAY);
+        // The pending change is consumed
+        assert!(runner.get_pending_config_change(platform_config).is_none());
+    }
+
+    #[test]
+    fn test_update_platform_config_rejected_when_timelocked() {
+        let (mut runner, admin, platform_config) = setup_test();
+        assert!(runner
+            .update_platform_config(&admin, platform_config, burn_tiers_change())
+            .is_err());
+    }
+
+    #[test]
+    fn test_propose_config_change_wrong_admin() {
+        let (mut runner, _, platform_config) = setup_test();
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 1_000_000_000);
+        assert!(runner
+            .propose_config_change(&stranger, platform_config, burn_tiers_change())
+            .is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ApproveVirtualArgs {
+    /// Account allowed to sell on the owner's behalf, None revokes the current delegate
+    pub delegate: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct ApproveVirtual<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            virtual_token_account.pool.as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+}
+
+/// Replaces the delegate and its allowance, like SPL `approve`.
+pub fn approve_virtual(ctx: Context<ApproveVirtual>, args: ApproveVirtualArgs) -> Result<()> {
+    let virtual_token_account = &mut ctx.accounts.virtual_token_account;
+    virtual_token_account.delegate = args.delegate;
+    virtual_token_account.delegated_amount = if args.delegate.is_some() {
+        args.amount
+    } else {
+        0
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::VirtualTokenAccount;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (
+        TestRunner,
+        Keypair,
+        Keypair,
+        TestPool,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+    ) {
+        let mut runner = TestRunner::new();
+        let owner = Keypair::new();
+        let delegate = Keypair::new();
+        runner.airdrop(&owner.pubkey(), 10_000_000_000);
+        runner.airdrop(&delegate.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&owner, 9);
+        let owner_ata = runner.create_associated_token_account(&owner, quote_mint, &owner.pubkey());
+        let platform_config =
+            runner.create_platform_config_mock(&owner, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &owner,
+            platform_config,
+            quote_mint,
+            2000,
+            2000,
+            500,
+            1000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&owner, quote_mint, &pool.pool);
+        runner.mint_tokens(&owner, pool.pool, quote_mint, 2000);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(owner.pubkey(), pool.pool, 500);
+        (
+            runner,
+            owner,
+            delegate,
+            pool,
+            quote_mint,
+            owner_ata,
+            virtual_token_account,
+        )
+    }
+
+    fn get_virtual_token_account(runner: &TestRunner, address: &Pubkey) -> VirtualTokenAccount {
+        let account = runner.svm.get_account(address).unwrap();
+        VirtualTokenAccount::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_approve_virtual_delegated_sell() {
+        let (mut runner, owner, delegate, pool, quote_mint, owner_ata, virtual_token_account) =
+            setup_test();
+        runner
+            .approve_virtual(&owner, pool.pool, Some(delegate.pubkey()), 150)
+            .unwrap();
+        let data = get_virtual_token_account(&runner, &virtual_token_account);
+        assert_eq!(
+            data.delegate.map(|delegate| delegate.to_bytes()),
+            Some(delegate.pubkey().to_bytes())
+        );
+        assert_eq!(data.delegated_amount, 150);
+
+        // Within the allowance, proceeds land in the owner's ATA
+        runner
+            .sell_virtual_token(
+                &delegate,
+                owner_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0,
+            )
+            .unwrap();
+        let data = get_virtual_token_account(&runner, &virtual_token_account);
+        assert_eq!(data.balance, 400);
+        assert_eq!(data.delegated_amount, 50);
+        assert!(data.quote_received > 0);
+
+        // Beyond the remaining allowance
+        assert!(runner
+            .sell_virtual_token(
+                &delegate,
+                owner_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                51,
+                0,
+            )
+            .is_err());
+
+        // The owner is not limited by the allowance
+        runner
+            .sell_virtual_token(
+                &owner,
+                owner_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(
+            get_virtual_token_account(&runner, &virtual_token_account).delegated_amount,
+            50
+        );
+    }
+
+    #[test]
+    fn test_approve_virtual_revoke() {
+        let (mut runner, owner, delegate, pool, quote_mint, owner_ata, virtual_token_account) =
+            setup_test();
+        runner
+            .approve_virtual(&owner, pool.pool, Some(delegate.pubkey()), 150)
+            .unwrap();
+        runner
+            .approve_virtual(&owner, pool.pool, None, 150)
+            .unwrap();
+        let data = get_virtual_token_account(&runner, &virtual_token_account);
+        assert!(data.delegate.is_none());
+        assert_eq!(data.delegated_amount, 0);
+
+        assert!(runner
+            .sell_virtual_token(
+                &delegate,
+                owner_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0,
+            )
+            .is_err());
+    }
+}
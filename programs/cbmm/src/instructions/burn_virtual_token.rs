@@ -1,4 +1,6 @@
 use crate::errors::CbmmError;
+use crate::helpers::RateLimitResult;
+use crate::instructions::{emit_queue_flushed_event, emit_topup_event};
 use crate::state::*;
 use anchor_lang::prelude::*;
 
@@ -13,11 +15,30 @@ pub struct BurnEvent {
 
     pub new_virtual_reserve: u64,
     pub new_buyback_fees_balance: u64,
+    pub quote_outstanding_topup: u64,
 
     pub burner: Pubkey,
     pub pool: Pubkey,
 }
 
+/// Returned by `burn_virtual_token` so automation can tell whether to burn again
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BurnReceipt {
+    /// Burns executed from the allowance in the current period, including this one unless it
+    /// was queued
+    pub burns_today: u16,
+    /// Burns queued from the allowance in the current period, including this one if it was
+    /// queued. Together with `burns_today` they count towards `max_daily_burns`.
+    pub queued_today: u16,
+    /// Limit of the allowance's tier (0 = unlimited)
+    pub max_daily_burns: u16,
+    /// B actually burned from the reserve
+    pub executed_amount: u64,
+    /// The rate limiter queued the burn instead of executing it now
+    pub queued: bool,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BurnVirtualToken<'info> {
     #[account(mut)]
@@ -28,8 +49,9 @@ pub struct BurnVirtualToken<'info> {
         seeds = [
             CBMM_POOL_SEED,
             pool.pool_index.to_le_bytes().as_ref(),
-            pool.creator.as_ref(),
+            pool.seed_creator.as_ref(),
             platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
         ],
         bump = pool.bump,        
     )]
@@ -51,12 +73,52 @@ pub struct BurnVirtualToken<'info> {
     /// Optional burn authority. Required and must match `platform_config.burn_authority`
     /// if that field is set; otherwise this account is ignored.
     pub burn_authority: Option<Signer<'info>>,
+
+    /// Optional pool creator co-sign. Required if `pool.require_creator_cosign_on_burn` is set
+    /// and the signer is not the creator; otherwise this account is ignored.
+    pub pool_creator: Option<Signer<'info>>,
 }
 
-pub fn burn_virtual_token(ctx: Context<BurnVirtualToken>) -> Result<()> {
-    let user_burn_allowance = &mut ctx.accounts.user_burn_allowance;
-    let user_daily_burn_index = user_burn_allowance.pop()?;
-    let platform_config = &ctx.accounts.platform_config;
+pub fn burn_virtual_token(ctx: Context<BurnVirtualToken>) -> Result<BurnReceipt> {
+    // If a global burn authority is configured, require it to sign every burn.
+    ctx.accounts.platform_config.check_burn_authority(
+        ctx.accounts
+            .burn_authority
+            .as_ref()
+            .map(|authority| authority.key()),
+    )?;
+
+    let (event, receipt) = execute_burn(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.user_burn_allowance,
+        &ctx.accounts.platform_config,
+        ctx.accounts.signer.key(),
+        ctx.accounts
+            .pool_creator
+            .as_ref()
+            .map(|creator| creator.key()),
+    )?;
+    emit_cpi!(event);
+    Ok(receipt)
+}
+
+/// Applies one burn from `burner`'s allowance to the pool, followed by a topup.
+/// Checking the burn authority is left to the caller, `creator_cosigner` is the pool creator's
+/// key if they signed.
+pub(crate) fn execute_burn(
+    pool: &mut Account<CbmmPool>,
+    user_burn_allowance: &mut Account<UserBurnAllowance>,
+    platform_config: &PlatformConfig,
+    burner: Pubkey,
+    creator_cosigner: Option<Pubkey>,
+) -> Result<(BurnEvent, BurnReceipt)> {
+    require!(!platform_config.paused, CbmmError::PlatformPaused);
+    pool.check_creator_cosign(burner, creator_cosigner)?;
+    let now = Clock::get()?.unix_timestamp;
+    let reset_interval_seconds = platform_config.burn_reset_interval_seconds;
+    // Only counted once the rate limiter has run, see below. Queued burns count towards the
+    // limit as well, so queueing cannot get around `max_daily_burns`.
+    let user_daily_burn_index = user_burn_allowance.peek_at(reset_interval_seconds, now)?;
     let burn_tier_index = user_burn_allowance.burn_tier_index;
     require_gt!(
         platform_config.burn_tiers.len() as u8,
@@ -64,47 +126,71 @@ pub fn burn_virtual_token(ctx: Context<BurnVirtualToken>) -> Result<()> {
         CbmmError::InvalidBurnTierIndex
     );
     let burn_tier = &platform_config.burn_tiers[burn_tier_index as usize];
+    require!(!burn_tier.is_expired(now), CbmmError::InvalidBurnTierIndex);
 
-    // If a global burn authority is configured, require it to sign every burn.
-    platform_config.check_burn_authority(
-        ctx.accounts
-            .burn_authority
-            .as_ref()
-            .map(|authority| authority.key()),
-    )?;
+    if let BurnRole::Anyone = burn_tier.role {
+        require!(pool.allow_user_burns, CbmmError::InvalidBurnTierIndex);
+    }
 
+    // On their own pool a creator burns only through the PoolOwner tier, so a user allowance
+    // cannot add a second burn budget on top of it
     if let BurnRole::PoolOwner = burn_tier.role {
-        require_keys_eq!(ctx.accounts.pool.creator, ctx.accounts.signer.key(), CbmmError::InvalidPoolCreator);
+        require_keys_eq!(pool.creator, burner, CbmmError::InvalidPoolCreator);
+    } else {
+        require_keys_neq!(pool.creator, burner, CbmmError::CreatorMustUsePoolOwnerTier);
     }
 
-    require_gte!(
-        burn_tier.max_daily_burns,
-        user_daily_burn_index,
-        CbmmError::BurnLimitReached
-    );
+    // 0 = unlimited, burns are still counted
+    if burn_tier.max_daily_burns > 0 {
+        require_gte!(
+            burn_tier.max_daily_burns,
+            user_daily_burn_index,
+            CbmmError::BurnLimitReached
+        );
+    }
 
     let requested_amount = burn_tier.burn_bp_x100;
 
     let config = &platform_config.burn_rate_config;
-    let burn_result = ctx.accounts.pool.burn(config, requested_amount)?;
-    let topup_accrued = ctx.accounts.pool.topup()?;
-
-    emit!(BurnEvent {
+    let burn_result = pool.burn_at(config, requested_amount, now)?;
+    emit_queue_flushed_event(pool, &burn_result);
+    let queued = matches!(burn_result.rate_limit_result, RateLimitResult::Queued);
+    if queued {
+        user_burn_allowance.queue_at(reset_interval_seconds, now)?;
+    } else {
+        user_burn_allowance.pop_at(reset_interval_seconds, now)?;
+    }
+    let topup_accrued = pool.topup()?;
+    emit_topup_event(pool, topup_accrued);
+
+    let receipt = BurnReceipt {
+        burns_today: user_burn_allowance.burns_today,
+        queued_today: user_burn_allowance.queued_today,
+        max_daily_burns: burn_tier.max_daily_burns,
+        executed_amount: burn_result.burn_amount,
+        queued,
+    };
+    let event = BurnEvent {
         burn_amount: burn_result.burn_amount,
-        topup_accrued: topup_accrued,
-        new_b_reserve: ctx.accounts.pool.base_reserve,
-        new_a_reserve: ctx.accounts.pool.quote_reserve,
-        new_virtual_reserve: ctx.accounts.pool.quote_virtual_reserve,
-        new_buyback_fees_balance: ctx.accounts.pool.buyback_fees_balance,
-        burner: ctx.accounts.signer.key(),
-        pool: ctx.accounts.pool.key(),
-    });
-    Ok(())
+        topup_accrued,
+        new_b_reserve: pool.base_reserve,
+        new_a_reserve: pool.quote_reserve,
+        new_virtual_reserve: pool.quote_virtual_reserve,
+        new_buyback_fees_balance: pool.buyback_fees_balance,
+        quote_outstanding_topup: pool.quote_outstanding_topup,
+        burner,
+        pool: pool.key(),
+    };
+    Ok((event, receipt))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::state::CbmmPool;
+    use crate::errors::CbmmError;
+    use crate::helpers::{RateLimitResult, SCALING_FACTOR};
+    use crate::instructions::{BurnEvent, BurnReceipt, QueueFlushed, TopupEvent};
+    use crate::simulate::preview_burn;
+    use crate::state::{CbmmPool, PlatformConfig};
     use crate::test_utils::{TestPool, TestRunner};
     use anchor_lang::prelude::*;
     use solana_sdk::signature::{Keypair, Signer};
@@ -257,6 +343,37 @@ mod tests {
         assert_eq!(pool_data.quote_virtual_reserve, 499500);
     }
 
+    #[test]
+    fn test_burn_virtual_token_creator_rejected_on_user_tier() {
+        let (mut runner, pool_owner, _, pool) = setup_test(None);
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+
+        // The Anyone tier allowance stays usable on other pools of the platform
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(
+                &pool_owner,
+                pool_owner.pubkey(),
+                platform_config,
+                false,
+            )
+            .unwrap();
+        runner.set_system_clock(1682899200);
+        let err = runner
+            .burn_virtual_token(&pool_owner, pool.pool, user_burn_allowance, None)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::CreatorMustUsePoolOwnerTier)
+        )));
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 1_000_000);
+    }
+
     #[test]
     fn test_burn_virtual_token_twice() {
         let (mut runner, _pool_owner, user, pool) = setup_test(None);
@@ -373,6 +490,86 @@ mod tests {
         assert!(burn_result.is_err());
     }
 
+    #[test]
+    fn test_burn_virtual_token_unlimited_daily_burns() {
+        let (mut runner, _pool_owner, user, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config_sdk).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.burn_tiers[0].max_daily_burns = 0;
+        runner.put_account_on_chain(&platform_config_sdk, platform_config_data);
+
+        // Already past the 5 burns the tier allowed before
+        let one_hour_ago = 1682899200 - 3600;
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            user.pubkey(),
+            user.pubkey(),
+            platform_config_sdk,
+            5,
+            one_hour_ago,
+            false,
+            one_hour_ago,
+        );
+
+        for i in 0..3 {
+            runner.set_system_clock(1682899200 + i);
+            runner.svm.expire_blockhash();
+            runner
+                .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+                .unwrap();
+        }
+
+        let user_burn_allowance_data = runner
+            .get_user_burn_allowance(&user_burn_allowance)
+            .unwrap();
+        assert_eq!(user_burn_allowance_data.burns_today, 8);
+        assert_eq!(user_burn_allowance_data.last_burn_timestamp, 1682899202);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_tier_expiry() {
+        let (mut runner, _pool_owner, user, pool) = setup_test(None);
+        let expires_at = 1682899200 + 30 * 86400;
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config_sdk).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.burn_tiers[0].expires_at = Some(expires_at);
+        runner.put_account_on_chain(&platform_config_sdk, platform_config_data);
+
+        runner.set_system_clock(1682899200);
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&user, user.pubkey(), platform_config_sdk, false)
+            .unwrap();
+
+        // Last second of the tier
+        runner.set_system_clock(expires_at - 1);
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        runner.set_system_clock(expires_at);
+        runner.svm.expire_blockhash();
+        let err = runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::InvalidBurnTierIndex)
+        )));
+    }
+
     #[test]
     fn test_burn_virtual_token_past_limit_after_reset() {
         let (mut runner, _pool_owner, user, pool) = setup_test(None );
@@ -472,4 +669,511 @@ mod tests {
             runner.burn_virtual_token(&user, pool.pool, user_burn_allowance, None);
         assert!(burn_result.is_err());
     }
+
+    #[test]
+    fn test_burn_virtual_token_allow_user_burns_toggle() {
+        let (mut runner, pool_owner, user, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+
+        runner.set_system_clock(1682899200);
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&user, user.pubkey(), platform_config_sdk, false)
+            .unwrap();
+        let owner_burn_allowance = runner
+            .initialize_user_burn_allowance(&pool_owner, pool_owner.pubkey(), platform_config_sdk, true)
+            .unwrap();
+
+        // Owner-only mode blocks the Anyone tier but keeps the owner tier working
+        runner
+            .set_allow_user_burns(&pool_owner, pool.pool, false)
+            .unwrap();
+        assert!(runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .is_err());
+        assert!(runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .is_ok());
+
+        // Only the creator can toggle
+        assert!(runner.set_allow_user_burns(&user, pool.pool, true).is_err());
+
+        runner
+            .set_allow_user_burns(&pool_owner, pool.pool, true)
+            .unwrap();
+        runner.svm.expire_blockhash();
+        assert!(runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_burn_virtual_token_emits_event_via_cpi() {
+        let (mut runner, _pool_owner, user, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+
+        runner.set_system_clock(1682899200);
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&user, user.pubkey(), platform_config_sdk, false)
+            .unwrap();
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        // The event travels as self-CPI instruction data rather than a log line
+        assert!(!runner
+            .last_logs
+            .iter()
+            .any(|log| log.starts_with("Program data: ")));
+        assert_eq!(runner.last_event_cpi_data.len(), 1);
+
+        let events = runner.last_events::<BurnEvent>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].burn_amount, 1000);
+        assert_eq!(events[0].new_b_reserve, 999000);
+        assert_eq!(events[0].burner.to_bytes(), user.pubkey().to_bytes());
+        assert_eq!(events[0].pool.to_bytes(), pool.pool.to_bytes());
+    }
+
+    #[test]
+    fn test_preview_burn_matches_burn() {
+        let (mut runner, pool_owner, _, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_before: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_before.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config_sdk).unwrap();
+        let platform_config =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+
+        runner.set_system_clock(1682899200);
+        let owner_burn_allowance = runner
+            .initialize_user_burn_allowance(
+                &pool_owner,
+                pool_owner.pubkey(),
+                platform_config_sdk,
+                true,
+            )
+            .unwrap();
+        let tier_index = runner
+            .get_user_burn_allowance(&owner_burn_allowance)
+            .unwrap()
+            .burn_tier_index;
+        let preview =
+            preview_burn(&pool_before, &platform_config, tier_index, 1682899200).unwrap();
+
+        runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .unwrap();
+
+        let event = runner.last_events::<BurnEvent>().pop().unwrap();
+        assert!(matches!(
+            preview.rate_limit_result,
+            RateLimitResult::ExecuteFull(_)
+        ));
+        assert_eq!(preview.burn_amount, event.burn_amount);
+        assert_eq!(preview.topup_accrued, event.topup_accrued);
+        assert_eq!(preview.pool.base_reserve, event.new_b_reserve);
+        assert_eq!(preview.pool.quote_virtual_reserve, event.new_virtual_reserve);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_after: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(
+            preview.pool.burn_limiter.accumulated_stress_bp_x10k,
+            pool_after.burn_limiter.accumulated_stress_bp_x10k
+        );
+    }
+
+    #[test]
+    fn test_burn_virtual_token_returns_receipt() {
+        let (mut runner, _pool_owner, user, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+
+        // One burn already recorded an hour ago
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            user.pubkey(),
+            user.pubkey(),
+            platform_config_sdk,
+            1,
+            1682899200 - 3600,
+            false,
+            1682899200 - 3600,
+        );
+        runner.set_system_clock(1682899200);
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        let receipt = runner.last_return_data::<BurnReceipt>();
+        assert_eq!(
+            receipt,
+            BurnReceipt {
+                burns_today: 2,
+                queued_today: 0,
+                max_daily_burns: 5,
+                executed_amount: 1000,
+                queued: false,
+            }
+        );
+        // Three more burns remain today
+        assert_eq!(receipt.max_daily_burns - receipt.burns_today, 3);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_queued_keeps_daily_allowance() {
+        let (mut runner, _pool_owner, user, pool) = setup_test(None);
+
+        // The limiter sits at its soft limit, so the burn can only be queued
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config_sdk).unwrap();
+        let platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        pool_data.burn_limiter.accumulated_stress_bp_x10k =
+            platform_config_data.burn_rate_config.burn_limit_bp_x100 * SCALING_FACTOR;
+        pool_data.burn_limiter.last_update_ts = 1682899200;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            user.pubkey(),
+            user.pubkey(),
+            platform_config_sdk,
+            1,
+            1682899200 - 3600,
+            false,
+            1682899200 - 3600,
+        );
+        runner.set_system_clock(1682899200);
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        let receipt = runner.last_return_data::<BurnReceipt>();
+        assert_eq!(
+            receipt,
+            BurnReceipt {
+                burns_today: 1,
+                queued_today: 1,
+                max_daily_burns: 5,
+                executed_amount: 0,
+                queued: true,
+            }
+        );
+        // Nothing executed, so the burn is not counted in `burns_today`
+        let allowance = runner.get_user_burn_allowance(&user_burn_allowance).unwrap();
+        assert_eq!(allowance.burns_today, 1);
+        assert_eq!(allowance.queued_today, 1);
+        assert_eq!(allowance.last_burn_timestamp, 1682899200);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 1_000_000);
+        assert!(pool_data.burn_limiter.pending_queue_shares_bp_x10k > 0);
+
+        // Queued burns still count towards the tier's daily limit
+        for _ in 0..3 {
+            runner.svm.expire_blockhash();
+            runner
+                .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+                .unwrap();
+            assert!(runner.last_return_data::<BurnReceipt>().queued);
+        }
+        runner.svm.expire_blockhash();
+        let err = runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::BurnLimitReached)
+        )));
+    }
+
+    #[test]
+    fn test_burn_virtual_token_emits_queue_flushed() {
+        let (mut runner, _pool_owner, user, pool) = setup_test(None);
+
+        // The limiter sits at its soft limit, so the first burn can only be queued
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config_sdk).unwrap();
+        let platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        pool_data.burn_limiter.accumulated_stress_bp_x10k =
+            platform_config_data.burn_rate_config.burn_limit_bp_x100 * SCALING_FACTOR;
+        pool_data.burn_limiter.last_update_ts = 1682899200;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner.set_system_clock(1682899200);
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&user, user.pubkey(), platform_config_sdk, false)
+            .unwrap();
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+        assert!(runner.last_events::<QueueFlushed>().is_empty());
+
+        // A second later the decay frees some room, and the queued shares go out first
+        runner.set_system_clock(1682899201);
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let events = runner.last_events::<QueueFlushed>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].executed_amount, 1_000_000 - pool_data.base_reserve);
+        assert!(events[0].executed_amount > 0);
+        assert_eq!(
+            events[0].remaining_shares,
+            pool_data.burn_limiter.pending_queue_shares_bp_x10k
+        );
+        assert!(events[0].remaining_shares > 0);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_clamped_to_min_base_reserve() {
+        let (mut runner, pool_owner, _, pool) = setup_test(None);
+
+        // Leave room for only 5000 of the 20000 a creator burn would take
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.min_base_reserve = 995_000;
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner.set_system_clock(1682899200);
+        let owner_burn_allowance = runner
+            .initialize_user_burn_allowance(
+                &pool_owner,
+                pool_owner.pubkey(),
+                platform_config_sdk,
+                true,
+            )
+            .unwrap();
+        runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .unwrap();
+
+        let events = runner.last_events::<BurnEvent>();
+        assert_eq!(events[0].burn_amount, 5000);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 995_000);
+        assert_eq!(pool_data.base_total_supply, 995_000);
+
+        // Once at the floor further burns are no-ops
+        runner.svm.expire_blockhash();
+        runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .unwrap();
+        let events = runner.last_events::<BurnEvent>();
+        assert_eq!(events[0].burn_amount, 0);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_tracks_outstanding_topup() {
+        let (mut runner, pool_owner, _, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_before: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_before.platform_config.to_bytes());
+
+        runner.set_system_clock(1682899200);
+        let owner_burn_allowance = runner
+            .initialize_user_burn_allowance(
+                &pool_owner,
+                pool_owner.pubkey(),
+                platform_config_sdk,
+                true,
+            )
+            .unwrap();
+        runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_after: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        // No buyback fees to top up with, so the whole L = ΔV - ΔA stays outstanding
+        let virtual_delta = pool_before.quote_virtual_reserve - pool_after.quote_virtual_reserve;
+        let real_delta = pool_before.quote_reserve - pool_after.quote_reserve;
+        assert_eq!(virtual_delta, 10_000);
+        assert_eq!(pool_after.quote_outstanding_topup, virtual_delta - real_delta);
+
+        let events = runner.last_events::<BurnEvent>();
+        assert_eq!(
+            events[0].quote_outstanding_topup,
+            pool_after.quote_outstanding_topup
+        );
+    }
+
+    #[test]
+    fn test_burn_virtual_token_emits_topup_event_when_shortfall_repaid() {
+        let (mut runner, pool_owner, _, pool) = setup_test(None);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_sdk =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+
+        runner.set_system_clock(1682899200);
+        let owner_burn_allowance = runner
+            .initialize_user_burn_allowance(
+                &pool_owner,
+                pool_owner.pubkey(),
+                platform_config_sdk,
+                true,
+            )
+            .unwrap();
+        runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .unwrap();
+        // Nothing to repay the shortfall with yet
+        assert!(runner.last_events::<TopupEvent>().is_empty());
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.buyback_fees_balance = 50_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner.svm.expire_blockhash();
+        runner
+            .burn_virtual_token(&pool_owner, pool.pool, owner_burn_allowance, None)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let burn_event = runner.last_events::<BurnEvent>().pop().unwrap();
+        let topup_events = runner.last_events::<TopupEvent>();
+        assert_eq!(topup_events.len(), 1);
+        let topup_event = &topup_events[0];
+        assert!(topup_event.real_topup_amount > 0);
+        assert_eq!(topup_event.real_topup_amount, burn_event.topup_accrued);
+        assert_eq!(topup_event.pool.to_bytes(), pool.pool.to_bytes());
+        assert_eq!(topup_event.new_quote_reserve, pool_data.quote_reserve);
+        assert_eq!(topup_event.new_virtual_reserve, pool_data.quote_virtual_reserve);
+        assert_eq!(
+            topup_event.remaining_buyback_fees,
+            50_000 - topup_event.real_topup_amount
+        );
+        assert_eq!(
+            topup_event.quote_outstanding_topup,
+            pool_data.quote_outstanding_topup
+        );
+        assert_eq!(
+            topup_event.quote_outstanding_topup,
+            burn_event.quote_outstanding_topup
+        );
+    }
+
+    fn setup_cosign_test(
+        require_creator_cosign_on_burn: bool,
+    ) -> (TestRunner, Keypair, Keypair, TestPool, solana_sdk::pubkey::Pubkey) {
+        let (mut runner, pool_owner, user, pool) = setup_test(None);
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.require_creator_cosign_on_burn = require_creator_cosign_on_burn;
+        let platform_config =
+            solana_sdk::pubkey::Pubkey::from(pool_data.platform_config.to_bytes());
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner.set_system_clock(1682899200);
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&user, user.pubkey(), platform_config, false)
+            .unwrap();
+        (runner, pool_owner, user, pool, user_burn_allowance)
+    }
+
+    #[test]
+    fn test_burn_virtual_token_with_creator_cosign() {
+        let (mut runner, pool_owner, user, pool, user_burn_allowance) = setup_cosign_test(true);
+
+        runner
+            .burn_virtual_token_with_cosign(
+                &user,
+                pool.pool,
+                user_burn_allowance,
+                None,
+                Some(&pool_owner),
+            )
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 999000);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_missing_creator_cosign() {
+        let (mut runner, _, user, pool, user_burn_allowance) = setup_cosign_test(true);
+
+        let err = runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::CreatorCosignRequired)
+        )));
+
+        // Someone else co-signing in the creator's place does not count
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 10_000_000_000);
+        assert!(runner
+            .burn_virtual_token_with_cosign(
+                &user,
+                pool.pool,
+                user_burn_allowance,
+                None,
+                Some(&stranger),
+            )
+            .is_err());
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 1_000_000);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_cosign_not_required() {
+        let (mut runner, _, user, pool, user_burn_allowance) = setup_cosign_test(false);
+
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 999000);
+    }
 }
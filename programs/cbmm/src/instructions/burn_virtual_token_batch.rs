@@ -0,0 +1,210 @@
+use crate::errors::CbmmError;
+use crate::instructions::execute_burn;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BurnVirtualTokenBatch<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Optional burn authority. Required and must match `platform_config.burn_authority`
+    /// if that field is set; otherwise this account is ignored.
+    pub burn_authority: Option<Signer<'info>>,
+}
+
+/// Burns on several pools of one platform. `remaining_accounts` holds writable
+/// `[pool, user_burn_allowance]` pairs; each pool's rate limiter applies independently.
+/// Pools requiring a creator co-sign only accept batch burns from their creator.
+pub fn burn_virtual_token_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BurnVirtualTokenBatch<'info>>,
+) -> Result<()> {
+    let platform_config = &ctx.accounts.platform_config;
+    platform_config.check_burn_authority(
+        ctx.accounts
+            .burn_authority
+            .as_ref()
+            .map(|authority| authority.key()),
+    )?;
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(2),
+        CbmmError::InvalidBurnAllowance
+    );
+
+    let platform_config_key = platform_config.key();
+    let signer = ctx.accounts.signer.key();
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let mut pool = Account::<CbmmPool>::try_from(&pair[0])?;
+        let pool_index_bytes = pool.pool_index.to_le_bytes();
+        let expected_pool = Pubkey::create_program_address(
+            &[
+                CBMM_POOL_SEED,
+                pool_index_bytes.as_ref(),
+                pool.seed_creator.as_ref(),
+                platform_config_key.as_ref(),
+                pool.quote_mint.as_ref(),
+                &[pool.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| CbmmError::InvalidPlatformConfig)?;
+        require_keys_eq!(pool.key(), expected_pool, CbmmError::InvalidPlatformConfig);
+
+        let mut user_burn_allowance = Account::<UserBurnAllowance>::try_from(&pair[1])?;
+        let expected_allowance = Pubkey::create_program_address(
+            &[
+                USER_BURN_ALLOWANCE_SEED,
+                signer.as_ref(),
+                platform_config_key.as_ref(),
+                &[user_burn_allowance.burn_tier_index],
+                platform_config.burn_tiers_updated_at.to_le_bytes().as_ref(),
+                &[user_burn_allowance.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| CbmmError::InvalidBurnAllowance)?;
+        require_keys_eq!(
+            user_burn_allowance.key(),
+            expected_allowance,
+            CbmmError::InvalidBurnAllowance
+        );
+
+        let (event, _) =
+            execute_burn(&mut pool, &mut user_burn_allowance, platform_config, signer, None)?;
+        // Persist before the next pair, which may reuse the same allowance
+        pool.exit(ctx.program_id)?;
+        user_burn_allowance.exit(ctx.program_id)?;
+        emit_cpi!(event);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::BurnEvent;
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const BASE_RESERVE: u64 = 1_000_000;
+
+    fn create_pool(runner: &mut TestRunner, platform_config: Pubkey, quote_mint: Pubkey) -> Pubkey {
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner
+            .create_pool_mock(
+                &creator,
+                platform_config,
+                quote_mint,
+                0,
+                500_000,
+                BASE_RESERVE,
+                BASE_RESERVE,
+                6,
+                200,
+                600,
+                200,
+                0,
+                0,
+                0,
+            )
+            .pool
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config = runner.create_platform_config_mock(
+            &admin, quote_mint, 5, 5, 1_000, 20_000, 200, 600, 200, None,
+        );
+
+        runner.set_system_clock(1682899200);
+        let burner = Keypair::new();
+        runner.airdrop(&burner.pubkey(), 10_000_000_000);
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&burner, burner.pubkey(), platform_config, false)
+            .unwrap();
+        (
+            runner,
+            burner,
+            platform_config,
+            quote_mint,
+            user_burn_allowance,
+        )
+    }
+
+    fn base_reserve(runner: &TestRunner, pool: &Pubkey) -> u64 {
+        let pool_account = runner.svm.get_account(pool).unwrap();
+        CbmmPool::try_deserialize(&mut pool_account.data.as_slice())
+            .unwrap()
+            .base_reserve
+    }
+
+    #[test]
+    fn test_burn_virtual_token_batch() {
+        let (mut runner, burner, platform_config, quote_mint, user_burn_allowance) = setup_test();
+        let pools: Vec<Pubkey> = (0..3)
+            .map(|_| create_pool(&mut runner, platform_config, quote_mint))
+            .collect();
+        let burns: Vec<(Pubkey, Pubkey)> = pools
+            .iter()
+            .map(|pool| (*pool, user_burn_allowance))
+            .collect();
+
+        runner
+            .burn_virtual_token_batch(&burner, platform_config, &burns, None)
+            .unwrap();
+
+        for pool in &pools {
+            assert!(base_reserve(&runner, pool) < BASE_RESERVE);
+        }
+        assert_eq!(runner.last_events::<BurnEvent>().len(), 3);
+        // The shared allowance is consumed once per pool
+        let allowance = runner
+            .get_user_burn_allowance(&user_burn_allowance)
+            .unwrap();
+        assert_eq!(allowance.burns_today, 3);
+    }
+
+    #[test]
+    fn test_burn_virtual_token_batch_rejects_other_platform() {
+        let (mut runner, burner, platform_config, quote_mint, user_burn_allowance) = setup_test();
+        let pool = create_pool(&mut runner, platform_config, quote_mint);
+
+        let other_admin = Keypair::new();
+        runner.airdrop(&other_admin.pubkey(), 10_000_000_000);
+        let other_platform_config = runner.create_platform_config_mock(
+            &other_admin,
+            quote_mint,
+            5,
+            5,
+            1_000,
+            20_000,
+            200,
+            600,
+            200,
+            None,
+        );
+        let other_pool = create_pool(&mut runner, other_platform_config, quote_mint);
+
+        assert!(runner
+            .burn_virtual_token_batch(
+                &burner,
+                platform_config,
+                &[
+                    (pool, user_burn_allowance),
+                    (other_pool, user_burn_allowance)
+                ],
+                None,
+            )
+            .is_err());
+        assert_eq!(base_reserve(&runner, &pool), BASE_RESERVE);
+    }
+}
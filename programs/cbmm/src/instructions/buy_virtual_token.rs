@@ -1,4 +1,6 @@
 use crate::errors::CbmmError;
+use crate::helpers::{calculate_ideal_buy_output_amount, calculate_min_output_with_slippage, Fees};
+use crate::instructions::emit_topup_event;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{
@@ -19,6 +21,50 @@ pub struct BuyEvent {
 
     pub buyer: Pubkey,
     pub pool: Pubkey,
+
+    pub quote_mint: Pubkey,
+    pub base_decimals: u8,
+
+    /// Pool volume totals after this trade, see `CbmmPool::cumulative_quote_volume`
+    pub cumulative_quote_volume: u128,
+    pub cumulative_base_volume: u128,
+
+    /// `memo` of the buy, if any
+    pub source: Option<[u8; 32]>,
+}
+
+impl BuyEvent {
+    pub fn throttled(&self) -> ThrottledTradeEvent {
+        ThrottledTradeEvent {
+            pool: self.pool,
+            trader: self.buyer,
+            is_buy: true,
+            quote_amount: self.quote_input,
+            base_amount: self.base_output,
+        }
+    }
+}
+
+/// Emitted instead of `BuyEvent` or `SellEvent` while the pool throttles trade events, see
+/// `CbmmPool::min_event_interval_seconds`. Indexers still see every movement of funds.
+#[event]
+pub struct ThrottledTradeEvent {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub is_buy: bool,
+    /// A paid in on buys, A paid out on sells
+    pub quote_amount: u64,
+    /// B bought or sold
+    pub base_amount: u64,
+}
+
+#[event]
+pub struct ReferralPaid {
+    pub amount: u64,
+
+    pub referrer: Pubkey,
+    pub buyer: Pubkey,
+    pub pool: Pubkey,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -28,25 +74,40 @@ pub struct BuyVirtualTokenArgs {
 
     /// The minimum amount of Mint B to receive. If below this, the transaction will fail.
     pub base_amount_min: u64,
+
+    /// Maximum allowed shortfall of the output versus the no-price-impact output, in basis points.
+    /// Alternative to `base_amount_min`; the two cannot be combined.
+    pub slippage_bps: Option<u16>,
+
+    /// Merkle proof of the buyer on allowlisted pools, empty otherwise
+    pub allowlist_proof: Vec<[u8; 32]>,
+
+    /// Caller-chosen tag echoed into `BuyEvent::source`, e.g. to attribute volume to a frontend
+    pub memo: Option<[u8; 32]>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BuyVirtualToken<'info> {
+    /// Pays the transaction fees, e.g. a relayer. May be the trader.
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub fee_payer: Signer<'info>,
+
+    /// Owns the virtual token account and funds the quote from `trader_ata`
+    pub trader: Signer<'info>,
     #[account(mut,
         associated_token::mint = quote_mint,
-        associated_token::authority = payer,
+        associated_token::authority = trader,
         associated_token::token_program = token_program
     )]
-    pub payer_ata: InterfaceAccount<'info, TokenAccount>,
+    pub trader_ata: InterfaceAccount<'info, TokenAccount>,
 
     // We only allow buying for yourself. This restriction can be lifted
     #[account(mut,
         seeds = [
             VIRTUAL_TOKEN_ACCOUNT_SEED,
             pool.key().as_ref(),
-            payer.key().as_ref(),
+            trader.key().as_ref(),
         ],
         bump = virtual_token_account.bump,
     )]
@@ -57,8 +118,9 @@ pub struct BuyVirtualToken<'info> {
         seeds = [
             CBMM_POOL_SEED,
             pool.pool_index.to_le_bytes().as_ref(),
-            pool.creator.as_ref(),
+            pool.seed_creator.as_ref(),
             platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
         ],
         bump = pool.bump,
     )]
@@ -71,8 +133,19 @@ pub struct BuyVirtualToken<'info> {
     )]
     pub pool_ata: InterfaceAccount<'info, TokenAccount>,
 
+    /// Mutable so the trade can add to `lifetime_platform_fees`
+    #[account(mut)]
     pub platform_config: Account<'info, PlatformConfig>,
 
+    /// Optional referrer, paid `platform_config.referral_fee_bp` out of the platform fee
+    #[account(mut,
+        token::mint = quote_mint,
+        token::token_program = token_program,
+        constraint = referrer_ata.owner != trader.key() @ CbmmError::InvalidOwner,
+        constraint = referrer_ata.key() != pool_ata.key() @ CbmmError::InvalidOwner,
+    )]
+    pub referrer_ata: Option<InterfaceAccount<'info, TokenAccount>>,
+
     #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
     pub quote_mint: InterfaceAccount<'info, Mint>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -80,29 +153,14 @@ pub struct BuyVirtualToken<'info> {
 }
 
 pub fn buy_virtual_token(ctx: Context<BuyVirtualToken>, args: BuyVirtualTokenArgs) -> Result<()> {
-    let pool = &mut ctx.accounts.pool;
-    let virtual_token_account = &mut ctx.accounts.virtual_token_account;
-
-    // Topup before trade for more impact on price curve
-    let amount_after_fees = pool.collect_fees(args.quote_amount)?;
-    let topup_amount = pool.topup()?;
-    let exchange_rate = pool.quote_to_base(amount_after_fees)?;
-    let output_amount = exchange_rate.base_amount;
-    virtual_token_account.add(output_amount)?;
-
-    require_gt!(output_amount, 0, CbmmError::AmountTooSmall);
-    require_gte!(
-        output_amount,
-        args.base_amount_min,
-        CbmmError::SlippageExceeded
-    );
-
-    // Transfer A tokens to pool ata, excluding platform fees
+    // Transfer A tokens to pool ata first. Transfer fees or hooks on the mint may deliver less
+    // than requested, so the trade is priced on what actually arrived.
+    let balance_before = ctx.accounts.pool_ata.amount;
     let cpi_accounts = TransferChecked {
         mint: ctx.accounts.quote_mint.to_account_info(),
-        from: ctx.accounts.payer_ata.to_account_info(),
+        from: ctx.accounts.trader_ata.to_account_info(),
         to: ctx.accounts.pool_ata.to_account_info(),
-        authority: ctx.accounts.payer.to_account_info(),
+        authority: ctx.accounts.trader.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_context = CpiContext::new(cpi_program, cpi_accounts);
@@ -111,27 +169,160 @@ pub fn buy_virtual_token(ctx: Context<BuyVirtualToken>, args: BuyVirtualTokenArg
         args.quote_amount,
         ctx.accounts.quote_mint.decimals,
     )?;
+    ctx.accounts.pool_ata.reload()?;
+    let quote_received = ctx
+        .accounts
+        .pool_ata
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(CbmmError::Underflow)?;
+
+    let (event, fees) = execute_buy(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.virtual_token_account,
+        &mut ctx.accounts.platform_config,
+        quote_received,
+        &args,
+        ctx.accounts.trader.key(),
+    )?;
+    if ctx.accounts.pool.take_trade_event_slot(Clock::get()?.unix_timestamp) {
+        emit_cpi!(event);
+    } else {
+        emit_cpi!(event.throttled());
+    }
 
-    emit!(BuyEvent {
-        quote_input: args.quote_amount,
+    if let Some(referrer_ata) = &ctx.accounts.referrer_ata {
+        let pool = &mut ctx.accounts.pool;
+        let referral_amount =
+            pool.take_referral_fee(quote_received, &fees, &mut ctx.accounts.platform_config)?;
+        if referral_amount > 0 {
+            let pool_account_info = pool.to_account_info();
+            pool.transfer_out(
+                referral_amount,
+                &pool_account_info,
+                &ctx.accounts.quote_mint,
+                &ctx.accounts.pool_ata,
+                referrer_ata,
+                &ctx.accounts.token_program,
+            )?;
+            emit_cpi!(ReferralPaid {
+                amount: referral_amount,
+                referrer: referrer_ata.owner,
+                buyer: ctx.accounts.trader.key(),
+                pool: pool.key(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Applies a buy of `quote_amount` to the pool and the buyer's virtual token account, returning
+/// the event along with the fees booked. Moving the quote tokens into the pool ATA is left to
+/// the caller.
+pub(crate) fn execute_buy(
+    pool: &mut Account<CbmmPool>,
+    virtual_token_account: &mut Account<VirtualTokenAccount>,
+    platform_config: &mut Account<PlatformConfig>,
+    quote_amount: u64,
+    args: &BuyVirtualTokenArgs,
+    buyer: Pubkey,
+) -> Result<(BuyEvent, Fees)> {
+    require!(!platform_config.paused, CbmmError::PlatformPaused);
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+    pool.check_initial_lock(platform_config.min_initial_lock)?;
+    require!(!virtual_token_account.frozen, CbmmError::AccountFrozen);
+    pool.check_allowlist(&buyer, &args.allowlist_proof)?;
+    require!(
+        args.slippage_bps.is_none() || args.base_amount_min == 0,
+        CbmmError::InvalidSlippage
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    // Topup before trade for more impact on price curve, unless the pool opted out
+    let (amount_after_fees, fees) = pool.collect_fees(quote_amount, platform_config, now)?;
+    let topup_first = pool.topup_order.topup_before_swap(true);
+    let mut topup_amount = 0;
+    if topup_first {
+        topup_amount = pool.topup()?;
+        emit_topup_event(pool, topup_amount);
+    }
+    let base_amount_min = match args.slippage_bps {
+        Some(slippage_bps) => {
+            let ideal_output = calculate_ideal_buy_output_amount(
+                amount_after_fees,
+                pool.quote_reserve,
+                pool.base_reserve,
+                pool.quote_virtual_reserve,
+            )?;
+            calculate_min_output_with_slippage(ideal_output, slippage_bps)?
+        }
+        None => args.base_amount_min,
+    };
+    let exchange_rate = pool.quote_to_base(amount_after_fees)?;
+    let output_amount = exchange_rate.base_amount;
+    if output_amount == 0 {
+        // Quote against the reserves the swap saw, the transaction reverts anyway
+        pool.quote_reserve -= amount_after_fees;
+        if let Some(min_quote_amount) = pool.min_buy_quote_amount() {
+            msg!(
+                "Buy output rounds to zero, minimum quote_amount is {}",
+                min_quote_amount
+            );
+        }
+        return err!(CbmmError::AmountTooSmall);
+    }
+    if !topup_first {
+        topup_amount = pool.topup()?;
+        emit_topup_event(pool, topup_amount);
+    }
+    virtual_token_account.add(output_amount)?;
+    pool.check_position_cap(virtual_token_account.balance)?;
+    virtual_token_account.last_buy_ts = now;
+    virtual_token_account.quote_spent = virtual_token_account
+        .quote_spent
+        .checked_add(quote_amount)
+        .ok_or(CbmmError::MathOverflow)?;
+
+    require_gte!(
+        output_amount,
+        base_amount_min,
+        CbmmError::SlippageExceeded
+    );
+    pool.record_volume(quote_amount, output_amount)?;
+
+    let event = BuyEvent {
+        quote_input: quote_amount,
         base_output: output_amount,
-        fees: args.quote_amount - exchange_rate.quote_amount,
+        fees: quote_amount - exchange_rate.quote_amount,
         topup_paid: topup_amount,
         new_base_reserve: pool.base_reserve,
         new_quote_reserve: pool.quote_reserve,
-        buyer: ctx.accounts.payer.key(),
-        pool: ctx.accounts.pool.key(),
-    });
-    Ok(())
+        buyer,
+        pool: pool.key(),
+        quote_mint: pool.quote_mint,
+        base_decimals: pool.base_mint_decimals,
+        cumulative_quote_volume: pool.cumulative_quote_volume,
+        cumulative_base_volume: pool.cumulative_base_volume,
+        source: args.memo,
+    };
+    Ok((event, fees))
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::state::CbmmPool;
+    use crate::errors::CbmmError;
+    use crate::helpers::{merkle_leaf, merkle_node};
+    use crate::instructions::{
+        BuyEvent, BuyVirtualTokenArgs, ReferralPaid, SellEvent, ThrottledTradeEvent,
+    };
+    use crate::simulate::{simulate_buy, simulate_sell};
+    use crate::state::{CbmmPool, PlatformConfig, VirtualTokenAccount};
     use crate::test_utils::{TestPool, TestRunner};
     use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
     use solana_sdk::pubkey::Pubkey;
     use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
 
     fn setup_test() -> (TestRunner, Keypair, Keypair, TestPool, Pubkey, Pubkey) {
         // Parameters
@@ -255,6 +446,652 @@ mod tests {
             pool_data.creator_fees_balance, creator_fees,
             "creator_fees_balance is not correct"
         );
+
+        // The emitted event reports the same trade
+        let events = runner.last_events::<BuyEvent>();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.quote_input, quote_amount);
+        assert_eq!(event.base_output, calculated_base_amount_min);
+        assert_eq!(event.fees, total_fees);
+        assert_eq!(event.topup_paid, 0);
+        assert_eq!(event.new_base_reserve, pool_data.base_reserve);
+        assert_eq!(event.new_quote_reserve, pool_data.quote_reserve);
+        assert_eq!(event.buyer.to_bytes(), payer.pubkey().to_bytes());
+        assert_eq!(event.pool.to_bytes(), pool.pool.to_bytes());
+        assert_eq!(event.source, None);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_tracks_lifetime_platform_fees() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        // Platform fee is 2%: 100 on the first buy, 200 on the second
+        for quote_amount in [5000, 10_000] {
+            runner
+                .buy_virtual_token(
+                    &payer,
+                    payer_ata,
+                    quote_mint,
+                    pool.pool,
+                    virtual_token_account,
+                    quote_amount,
+                    0,
+                )
+                .unwrap();
+        }
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_account = runner
+            .svm
+            .get_account(&Pubkey::from(pool_data.platform_config.to_bytes()))
+            .unwrap();
+        let platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        assert_eq!(platform_config_data.lifetime_platform_fees, 300);
+        assert_eq!(pool_data.platform_fees_balance, 300);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_position_cap() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        // Exactly what the first 5000 A buy yields
+        pool_data.max_vta_balance = 8959;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .unwrap();
+        assert!(runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                0,
+            )
+            .is_err());
+
+        // Selling is never capped and frees room for another buy
+        runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .unwrap();
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                0,
+            )
+            .unwrap();
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert!(vta_data.balance <= 8959);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_fee_balance_overflow() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.platform_fees_balance = u64::MAX - 50;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        // The 2% platform fee on 5000 is 100
+        assert!(runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .is_err());
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.platform_fees_balance, u64::MAX - 50);
+    }
+
+    #[test_case(false; "without_referrer")]
+    #[test_case(true; "with_referrer")]
+    fn test_buy_virtual_token_referral(with_referrer: bool) {
+        let (mut runner, payer, referrer, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let referrer_ata =
+            runner.create_associated_token_account(&referrer, quote_mint, &referrer.pubkey());
+
+        // Half of the 2% platform fee goes to the referrer
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config = Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.referral_fee_bp = 100;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let args = BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 0,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+        runner
+            .buy_virtual_token_with_referrer(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                args,
+                with_referrer.then_some(referrer_ata),
+            )
+            .unwrap();
+
+        let expected_referral = if with_referrer { 50 } else { 0 };
+        let referrer_ata_account = runner.svm.get_account(&referrer_ata).unwrap();
+        let referrer_balance =
+            anchor_spl::token::spl_token::state::Account::unpack(&referrer_ata_account.data)
+                .unwrap()
+                .amount;
+        assert_eq!(referrer_balance, expected_referral);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.platform_fees_balance, 100 - expected_referral);
+        // Creator and buyback shares are untouched
+        assert_eq!(pool_data.creator_fees_balance, 100);
+        assert_eq!(pool_data.buyback_fees_balance, 300);
+
+        let referrals = runner.last_events::<ReferralPaid>();
+        if with_referrer {
+            assert_eq!(referrals.len(), 1);
+            assert_eq!(referrals[0].amount, 50);
+            assert_eq!(referrals[0].referrer.to_bytes(), referrer.pubkey().to_bytes());
+        } else {
+            assert!(referrals.is_empty());
+        }
+    }
+
+    #[test_case(i64::MAX, 200, 0; "fee_holiday")]
+    #[test_case(0, 50, 25; "pool_platform_fee_below_referral_fee")]
+    fn test_buy_virtual_token_referral_capped_by_trade_platform_fee(
+        fee_holiday_until: i64,
+        platform_fee_bp: u16,
+        expected_referral: u64,
+    ) {
+        let (mut runner, payer, referrer, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let referrer_ata =
+            runner.create_associated_token_account(&referrer, quote_mint, &referrer.pubkey());
+
+        // Platform fees from earlier trades must not fund the referral
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.fee_holiday_until = fee_holiday_until;
+        pool_data.platform_fee_bp = platform_fee_bp;
+        pool_data.platform_fees_balance = 1_000;
+        let platform_config = Pubkey::from(pool_data.platform_config.to_bytes());
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.referral_fee_bp = 100;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let args = BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 0,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+        runner
+            .buy_virtual_token_with_referrer(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                args,
+                Some(referrer_ata),
+            )
+            .unwrap();
+
+        let referrer_ata_account = runner.svm.get_account(&referrer_ata).unwrap();
+        let referrer_balance =
+            anchor_spl::token::spl_token::state::Account::unpack(&referrer_ata_account.data)
+                .unwrap()
+                .amount;
+        assert_eq!(referrer_balance, expected_referral);
+        // The referral takes at most this trade's platform fee
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data: CbmmPool =
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.platform_fees_balance, 1_000);
+        assert_eq!(
+            runner.last_events::<ReferralPaid>().len(),
+            usize::from(expected_referral > 0)
+        );
+    }
+
+    #[test]
+    fn test_buy_virtual_token_pool_ata_as_referrer_rejected() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(quote_mint.to_bytes()),
+        );
+        let args = BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 0,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+        let err = runner
+            .buy_virtual_token_with_referrer(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                args,
+                Some(Pubkey::from(pool_ata.to_bytes())),
+            )
+            .unwrap_err();
+        assert!(err
+            .message
+            .contains(&format!("Custom({})", u32::from(CbmmError::InvalidOwner))));
+    }
+
+    #[test]
+    fn test_buy_virtual_token_self_referral_rejected() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let args = BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 0,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+        assert!(runner
+            .buy_virtual_token_with_referrer(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                args,
+                Some(payer_ata),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_buy_virtual_token_matches_simulate_buy() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_before = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let simulated = simulate_buy(&pool_before, 5000, 0).unwrap();
+
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                1,
+            )
+            .unwrap();
+
+        let event = runner.last_events::<BuyEvent>().pop().unwrap();
+        assert_eq!(event.base_output, simulated.base_output);
+        assert_eq!(event.fees, simulated.fees);
+        assert_eq!(event.topup_paid, simulated.topup_paid);
+        assert_eq!(event.new_quote_reserve, simulated.pool.quote_reserve);
+        assert_eq!(event.new_base_reserve, simulated.pool.base_reserve);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_event_reports_cumulative_volume() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        let mut base_bought = 0;
+        for quote_amount in [5000, 7000] {
+            runner.svm.expire_blockhash();
+            runner
+                .buy_virtual_token(
+                    &payer,
+                    payer_ata,
+                    quote_mint,
+                    pool.pool,
+                    virtual_token_account,
+                    quote_amount,
+                    1,
+                )
+                .unwrap();
+            let event = runner.last_events::<BuyEvent>().pop().unwrap();
+            base_bought += event.base_output as u128;
+            assert_eq!(event.cumulative_base_volume, base_bought);
+        }
+
+        let event = runner.last_events::<BuyEvent>().pop().unwrap();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        // Volume counts A before fees
+        assert_eq!(event.cumulative_quote_volume, 12_000);
+        assert_eq!(pool_data.cumulative_quote_volume, event.cumulative_quote_volume);
+        assert_eq!(pool_data.cumulative_base_volume, event.cumulative_base_volume);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_event_throttle() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.min_event_interval_seconds = 60;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        let now = 1682899200;
+        let mut balance = 0;
+        for (at, emitted) in [(now, true), (now + 59, false), (now + 60, true)] {
+            runner.set_system_clock(at);
+            runner.svm.expire_blockhash();
+            runner
+                .buy_virtual_token(
+                    &payer,
+                    payer_ata,
+                    quote_mint,
+                    pool.pool,
+                    virtual_token_account,
+                    5000,
+                    1,
+                )
+                .unwrap();
+            assert_eq!(runner.last_events::<BuyEvent>().len(), emitted as usize);
+            // Throttled buys still record the funds they moved
+            let throttled = runner.last_events::<ThrottledTradeEvent>();
+            assert_eq!(throttled.len(), !emitted as usize);
+            if let Some(event) = throttled.first() {
+                assert!(event.is_buy);
+                assert_eq!(event.quote_amount, 5000);
+                assert_eq!(event.trader.to_bytes(), payer.pubkey().to_bytes());
+            }
+
+            // The trade itself goes through either way
+            let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+            let vta =
+                VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+            assert!(vta.balance > balance);
+            balance = vta.balance;
+        }
+    }
+
+    #[test]
+    fn test_buy_virtual_token_memo_echoed_as_source() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let mut memo = [0u8; 32];
+        memo[..10].copy_from_slice(b"frontend-a");
+
+        for memo in [Some(memo), None] {
+            runner.svm.expire_blockhash();
+            let args = BuyVirtualTokenArgs {
+                quote_amount: 5000,
+                base_amount_min: 1,
+                slippage_bps: None,
+                allowlist_proof: vec![],
+                memo,
+            };
+            runner
+                .relay_buy_virtual_token(
+                    &payer,
+                    &payer,
+                    payer_ata,
+                    quote_mint,
+                    pool.pool,
+                    virtual_token_account,
+                    args,
+                    None,
+                )
+                .unwrap();
+            let event = runner.last_events::<BuyEvent>().pop().unwrap();
+            assert_eq!(event.source, memo);
+        }
+    }
+
+    #[test]
+    fn test_buy_virtual_token_fees_paid_by_relayer() {
+        let (mut runner, payer, relayer, pool, _, quote_mint) = setup_test();
+        // The trader holds quote tokens but no SOL at all
+        let trader = Keypair::new();
+        let trader_ata =
+            runner.create_associated_token_account(&payer, quote_mint, &trader.pubkey());
+        runner.mint_to(&payer, &quote_mint, trader_ata, 5000);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(trader.pubkey(), pool.pool, 0);
+        let relayer_lamports = runner.svm.get_balance(&relayer.pubkey()).unwrap();
+
+        let args = BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 1,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+        runner
+            .relay_buy_virtual_token(
+                &relayer,
+                &trader,
+                trader_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                args,
+                None,
+            )
+            .unwrap();
+
+        assert!(runner.svm.get_balance(&relayer.pubkey()).unwrap() < relayer_lamports);
+        assert_eq!(runner.svm.get_balance(&trader.pubkey()).unwrap_or(0), 0);
+        let trader_ata_account = runner.svm.get_account(&trader_ata).unwrap();
+        let trader_quote_balance =
+            anchor_spl::token::spl_token::state::Account::unpack(&trader_ata_account.data)
+                .unwrap()
+                .amount;
+        assert_eq!(trader_quote_balance, 0);
+        let event = runner.last_events::<BuyEvent>().pop().unwrap();
+        assert_eq!(event.buyer.to_bytes(), trader.pubkey().to_bytes());
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta = VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert_eq!(vta.balance, event.base_output);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_relayer_cannot_spend_trader_funds_alone() {
+        let (mut runner, payer, relayer, pool, _, quote_mint) = setup_test();
+        let trader = Keypair::new();
+        let trader_ata =
+            runner.create_associated_token_account(&payer, quote_mint, &trader.pubkey());
+        runner.mint_to(&payer, &quote_mint, trader_ata, 5000);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(trader.pubkey(), pool.pool, 0);
+
+        // A relayer posing as the trader cannot match the trader's ATA or virtual token account
+        let args = BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 1,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+        assert!(runner
+            .relay_buy_virtual_token(
+                &relayer,
+                &relayer,
+                trader_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                args,
+                None,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_buy_and_sell_with_nine_decimal_base_and_six_decimal_quote() {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 6);
+        let payer_ata = runner.create_associated_token_account(&payer, quote_mint, &payer.pubkey());
+        runner.mint_to(&payer, &quote_mint, payer_ata, 10_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        runner.create_associated_token_account(&payer, quote_mint, &platform_config);
+        // 1 A of virtual reserve against 2 B: 0.5 A per B whatever the raw scale
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000_000,
+            2_000_000_000,
+            9,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&payer, quote_mint, &pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        // Within the rounding of the raw price, scaled up by 10^3
+        let normalized_price_x64 = pool_data.normalized_spot_price_x64(6).unwrap();
+        assert!((1u128 << 63) - normalized_price_x64 < 1000);
+        let simulated_buy = simulate_buy(&pool_data, 5000, 0).unwrap();
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                1,
+            )
+            .unwrap();
+        let buy_event = runner.last_events::<BuyEvent>().pop().unwrap();
+        assert_eq!(buy_event.base_decimals, 9);
+        assert_eq!(buy_event.base_output, simulated_buy.base_output);
+        // 4500 raw A after fees buys ~2.24 whole B less price impact
+        assert_eq!(buy_event.base_output, 8_959_681);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let simulated_sell = simulate_sell(&pool_data, buy_event.base_output, 0).unwrap();
+        runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                buy_event.base_output,
+                0,
+            )
+            .unwrap();
+        let sell_event = runner.last_events::<SellEvent>().pop().unwrap();
+        assert_eq!(sell_event.base_decimals, 9);
+        assert_eq!(sell_event.quote_output, simulated_sell.quote_output);
+        assert!(sell_event.quote_output < 5000);
+    }
+
+    #[test]
+    fn test_buy_virtual_token_event_includes_mint_and_decimals() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                1,
+            )
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let events = runner.last_events::<BuyEvent>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].base_decimals, pool_data.base_mint_decimals);
+        assert_eq!(events[0].quote_mint.to_bytes(), quote_mint.to_bytes());
     }
 
     #[test]
@@ -279,6 +1116,67 @@ mod tests {
         assert!(result_buy_min_too_high.is_err());
     }
 
+    #[test]
+    fn test_buy_virtual_token_zero_output_logs_min_quote_amount() {
+        let (mut runner, payer, another_wallet, pool, payer_ata, quote_mint) = setup_test();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config = Pubkey::from(pool_data.platform_config.to_bytes());
+        // 1 B costs about 500_000 A before fees
+        let expensive_pool = runner.create_pool_mock(
+            &another_wallet,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&payer, quote_mint, &expensive_pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), expensive_pool.pool, 0);
+
+        let err = runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                expensive_pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .unwrap_err();
+        assert!(err.message.contains("AmountTooSmall"));
+        let min_quote_amount: u64 = err
+            .message
+            .split("minimum quote_amount is ")
+            .nth(1)
+            .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+            .and_then(|digits| digits.parse().ok())
+            .unwrap();
+        assert_eq!(min_quote_amount, 555_559);
+
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                expensive_pool.pool,
+                virtual_token_account,
+                min_quote_amount,
+                1,
+            )
+            .unwrap();
+    }
+
     #[test]
     fn test_buy_virtual_token_wrong_virtual_account_owner() {
         let (mut runner, payer, another_wallet, pool, payer_ata, quote_mint) = setup_test();
@@ -300,4 +1198,201 @@ mod tests {
         );
         assert!(result_buy_another_virtual_account.is_err());
     }
+
+    #[test_case(50, true; "within_slippage")]
+    #[test_case(40, false; "too_tight")]
+    fn test_buy_virtual_token_slippage_bps(slippage_bps: u16, success: bool) {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+
+        // 4500 after fees, ideal output 9000, actual output 8959 (~45.5 bps short)
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        let result = runner.buy_virtual_token_with_args(
+            &payer,
+            payer_ata,
+            quote_mint,
+            pool.pool,
+            virtual_token_account,
+            BuyVirtualTokenArgs {
+                quote_amount: 5000,
+                base_amount_min: 0,
+                slippage_bps: Some(slippage_bps),
+                allowlist_proof: vec![],
+                memo: None,
+            },
+        );
+        assert_eq!(result.is_ok(), success);
+
+        if success {
+            let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+            let vta_data =
+                VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+            assert_eq!(vta_data.balance, 8959);
+        }
+    }
+
+    #[test]
+    fn test_buy_virtual_token_slippage_bps_and_min_amount_rejected() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        let result = runner.buy_virtual_token_with_args(
+            &payer,
+            payer_ata,
+            quote_mint,
+            pool.pool,
+            virtual_token_account,
+            BuyVirtualTokenArgs {
+                quote_amount: 5000,
+                base_amount_min: 1,
+                slippage_bps: Some(10_000),
+                allowlist_proof: vec![],
+                memo: None,
+            },
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_buy_virtual_token_allowlist() {
+        let (mut runner, payer, another_wallet, pool, payer_ata, quote_mint) = setup_test();
+        let another_ata =
+            runner.create_associated_token_account(&payer, quote_mint, &another_wallet.pubkey());
+        runner.mint_to(&payer, &quote_mint, another_ata, 10_000_000);
+
+        // Two-leaf tree over the payer and an outsider, so each proof is the other leaf
+        let payer_leaf = merkle_leaf(payer.pubkey().as_ref());
+        let outsider_leaf = merkle_leaf(Pubkey::new_unique().as_ref());
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.allowlist_root = Some(merkle_node(&payer_leaf, &outsider_leaf));
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        let args = |allowlist_proof| BuyVirtualTokenArgs {
+            quote_amount: 5000,
+            base_amount_min: 1,
+            slippage_bps: None,
+            allowlist_proof,
+            memo: None,
+        };
+
+        let payer_vta = runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let result = runner.buy_virtual_token_with_args(
+            &payer,
+            payer_ata,
+            quote_mint,
+            pool.pool,
+            payer_vta,
+            args(vec![]),
+        );
+        assert!(result.is_err());
+
+        let result = runner.buy_virtual_token_with_args(
+            &payer,
+            payer_ata,
+            quote_mint,
+            pool.pool,
+            payer_vta,
+            args(vec![outsider_leaf]),
+        );
+        assert!(result.is_ok());
+
+        // A proof from the allowlist does not carry over to another buyer
+        let another_vta =
+            runner.create_virtual_token_account_mock(another_wallet.pubkey(), pool.pool, 0);
+        let result = runner.buy_virtual_token_with_args(
+            &another_wallet,
+            another_ata,
+            quote_mint,
+            pool.pool,
+            another_vta,
+            args(vec![outsider_leaf]),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_buy_virtual_token_fee_on_transfer_mint() {
+        use anchor_spl::token_2022::spl_token_2022::{
+            extension::StateWithExtensions, state::Account as TokenAccount,
+        };
+
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+
+        // The mint withholds 1% of every transfer
+        let quote_mint = runner.create_transfer_fee_mint(&payer, 9, 100, u64::MAX);
+        let payer_ata = runner.create_associated_token_account(&payer, quote_mint, &payer.pubkey());
+        runner.mint_to(&payer, &quote_mint, payer_ata, 10_000_000_000);
+
+        let platform_config = runner.create_platform_config_mock(
+            &payer,
+            quote_mint,
+            5,
+            5,
+            2,
+            1,
+            200,
+            600,
+            200,
+            None,
+        );
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let pool_ata = runner.create_associated_token_account(&payer, quote_mint, &pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                1,
+            )
+            .unwrap();
+
+        // Only 4950 arrives; fees and reserves are computed on that, not on the nominal 5000
+        let pool_ata_account = runner.svm.get_account(&pool_ata).unwrap();
+        let pool_ata_amount = StateWithExtensions::<TokenAccount>::unpack(&pool_ata_account.data)
+            .unwrap()
+            .base
+            .amount;
+        assert_eq!(pool_ata_amount, 4950);
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.creator_fees_balance, 99);
+        assert_eq!(pool_data.buyback_fees_balance, 297);
+        assert_eq!(pool_data.platform_fees_balance, 99);
+        assert_eq!(pool_data.quote_reserve, 4455);
+        assert_eq!(
+            pool_data.quote_reserve
+                + pool_data.creator_fees_balance
+                + pool_data.buyback_fees_balance
+                + pool_data.platform_fees_balance,
+            pool_ata_amount
+        );
+    }
 }
@@ -0,0 +1,299 @@
+use crate::errors::CbmmError;
+use crate::instructions::{execute_buy, BuyVirtualTokenArgs};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{
+    close_account, sync_native, transfer_checked, CloseAccount, Mint, SyncNative, TokenAccount,
+    TokenInterface, TransferChecked,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyWithSol<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Temporary wSOL account, closed back to the payer at the end of the instruction
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program
+    )]
+    pub payer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mutable so the trade can add to `lifetime_platform_fees`
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        address = pool.quote_mint @ CbmmError::InvalidMint,
+        constraint = quote_mint.key() == native_mint::ID @ CbmmError::InvalidMint,
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Same as `buy_virtual_token`, but spends native SOL: `quote_amount` lamports are
+/// wrapped into the payer's wSOL account, traded, and the account is closed again.
+pub fn buy_with_sol(ctx: Context<BuyWithSol>, args: BuyVirtualTokenArgs) -> Result<()> {
+    // Wrap the lamports
+    let cpi_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.payer.to_account_info(),
+            to: ctx.accounts.payer_ata.to_account_info(),
+        },
+    );
+    transfer(cpi_context, args.quote_amount)?;
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        SyncNative {
+            account: ctx.accounts.payer_ata.to_account_info(),
+        },
+    );
+    sync_native(cpi_context)?;
+
+    let (event, _) = execute_buy(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.virtual_token_account,
+        &mut ctx.accounts.platform_config,
+        args.quote_amount,
+        &args,
+        ctx.accounts.payer.key(),
+    )?;
+
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            mint: ctx.accounts.quote_mint.to_account_info(),
+            from: ctx.accounts.payer_ata.to_account_info(),
+            to: ctx.accounts.pool_ata.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        },
+    );
+    transfer_checked(
+        cpi_context,
+        args.quote_amount,
+        ctx.accounts.quote_mint.decimals,
+    )?;
+
+    // Unwrap whatever is left and reclaim the rent
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.payer_ata.to_account_info(),
+            destination: ctx.accounts.payer.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        },
+    );
+    close_account(cpi_context)?;
+
+    if ctx.accounts.pool.take_trade_event_slot(Clock::get()?.unix_timestamp) {
+        emit_cpi!(event);
+    } else {
+        emit_cpi!(event.throttled());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::VirtualTokenAccount;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const TX_FEE: u64 = 5000;
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool, Pubkey) {
+        let creator_fee_bp = 200;
+        let buyback_fee_bp = 600;
+        let platform_fee_bp = 200;
+
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let native_mint = runner.create_native_mint();
+
+        let platform_config = runner.create_platform_config_mock(
+            &payer,
+            native_mint,
+            5,
+            5,
+            2,
+            1,
+            creator_fee_bp,
+            buyback_fee_bp,
+            platform_fee_bp,
+            None,
+        );
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            native_mint,
+            0,
+            1_000_000_000,
+            1_000_000_000_000,
+            1_000_000_000_000,
+            6,
+            creator_fee_bp,
+            buyback_fee_bp,
+            platform_fee_bp,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&payer, native_mint, &pool.pool);
+
+        (runner, payer, pool, native_mint)
+    }
+
+    fn token_balance(runner: &TestRunner, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        let ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(owner.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
+        );
+        let account = runner
+            .svm
+            .get_account(&Pubkey::from(ata.to_bytes()))
+            .unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    #[test]
+    fn test_buy_with_sol_and_sell_to_sol_round_trip() {
+        let (mut runner, payer, pool, native_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let quote_amount = 100_000_000;
+
+        let lamports_start = runner.svm.get_balance(&payer.pubkey()).unwrap();
+        runner
+            .buy_with_sol(&payer, pool.pool, virtual_token_account, quote_amount, 1)
+            .unwrap();
+
+        // Exactly the traded lamports left the wallet, the temporary wSOL account is gone
+        let lamports_after_buy = runner.svm.get_balance(&payer.pubkey()).unwrap();
+        assert_eq!(lamports_start - lamports_after_buy, quote_amount + TX_FEE);
+        assert_eq!(token_balance(&runner, &pool.pool, &native_mint), quote_amount);
+        let payer_wsol_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(payer.pubkey().to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(native_mint.to_bytes()),
+        );
+        assert!(runner
+            .svm
+            .get_account(&Pubkey::from(payer_wsol_ata.to_bytes()))
+            .is_none());
+
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let base_balance = VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice())
+            .unwrap()
+            .balance;
+        assert!(base_balance > 0);
+
+        runner
+            .sell_to_sol(&payer, pool.pool, virtual_token_account, base_balance, 1)
+            .unwrap();
+
+        // Proceeds arrive as lamports, the pool keeps only what it did not pay out
+        let lamports_after_sell = runner.svm.get_balance(&payer.pubkey()).unwrap();
+        let quote_output = lamports_after_sell + TX_FEE - lamports_after_buy;
+        assert!(quote_output > 0 && quote_output < quote_amount);
+        assert_eq!(
+            token_balance(&runner, &pool.pool, &native_mint),
+            quote_amount - quote_output
+        );
+        assert_eq!(
+            lamports_start - lamports_after_sell,
+            quote_amount - quote_output + 2 * TX_FEE
+        );
+
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert_eq!(vta_data.balance, 0);
+    }
+
+    #[test]
+    fn test_buy_with_sol_rejects_non_native_pool() {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config = runner.create_platform_config_mock(
+            &payer,
+            quote_mint,
+            5,
+            5,
+            2,
+            1,
+            200,
+            600,
+            200,
+            None,
+        );
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&payer, quote_mint, &pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        let result = runner.buy_with_sol(&payer, pool.pool, virtual_token_account, 5000, 0);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct CancelLimitBuy<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ CbmmError::InvalidOwner,
+        seeds = [
+            LIMIT_BUY_ORDER_SEED,
+            order.pool.as_ref(),
+            owner.key().as_ref(),
+            order.order_id.to_le_bytes().as_ref(),
+        ],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, LimitBuyOrder>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Refunds whatever is left in escrow and closes the order.
+pub fn cancel_limit_buy(ctx: Context<CancelLimitBuy>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let order_account_info = order.to_account_info();
+    let remaining = ctx.accounts.escrow_ata.amount;
+    if remaining > 0 {
+        order.transfer_out(
+            remaining,
+            &order_account_info,
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.escrow_ata,
+            &ctx.accounts.owner_ata,
+            &ctx.accounts.token_program,
+        )?;
+    }
+    order.close_escrow_ata(
+        &order_account_info,
+        &ctx.accounts.escrow_ata,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.token_program,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TestRunner;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn token_balance(runner: &TestRunner, ata: Pubkey) -> u64 {
+        let account = runner.svm.get_account(&ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    #[test]
+    fn test_cancel_limit_buy_refunds_escrow() {
+        let mut runner = TestRunner::new();
+        let owner = Keypair::new();
+        runner.airdrop(&owner.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&owner, 9);
+        let owner_ata = runner.create_associated_token_account(&owner, quote_mint, &owner.pubkey());
+        runner.mint_to(&owner, &quote_mint, owner_ata, 1_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&owner, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &owner,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+
+        let order = runner
+            .place_limit_buy(&owner, owner_ata, pool.pool, 0, 400_000, 1 << 64)
+            .unwrap();
+        assert_eq!(token_balance(&runner, owner_ata), 600_000);
+
+        // Only the owner can cancel
+        let other = Keypair::new();
+        runner.airdrop(&other.pubkey(), 10_000_000_000);
+        let other_ata = runner.create_associated_token_account(&other, quote_mint, &other.pubkey());
+        assert!(runner.cancel_limit_buy(&other, other_ata, order).is_err());
+
+        runner.cancel_limit_buy(&owner, owner_ata, order).unwrap();
+        assert!(runner.get_limit_buy_order(&order).is_none());
+        assert_eq!(token_balance(&runner, owner_ata), 1_000_000);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            order.pool.as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ CbmmError::InvalidOwner,
+        seeds = [
+            ORDER_SEED,
+            order.pool.as_ref(),
+            owner.key().as_ref(),
+            order.order_id.to_le_bytes().as_ref(),
+        ],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+}
+
+/// Returns the escrowed base amount to the owner's virtual token account.
+pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+    ctx.accounts
+        .virtual_token_account
+        .add(ctx.accounts.order.base_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{OrderDirection, VirtualTokenAccount};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_cancel_order_returns_escrow() {
+        let mut runner = TestRunner::new();
+        let owner = Keypair::new();
+        runner.airdrop(&owner.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&owner, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&owner, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &owner,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(owner.pubkey(), pool.pool, 1000);
+        let order = runner
+            .place_order(&owner, pool.pool, 0, 600, 1 << 64, OrderDirection::StopLoss)
+            .unwrap();
+
+        // Only the owner can cancel
+        let other = Keypair::new();
+        runner.airdrop(&other.pubkey(), 10_000_000_000);
+        runner.create_virtual_token_account_mock(other.pubkey(), pool.pool, 0);
+        assert!(runner.cancel_order(&other, order).is_err());
+
+        runner.cancel_order(&owner, order).unwrap();
+        assert!(runner.get_order(&order).is_none());
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert_eq!(vta_data.balance, 1000);
+    }
+}
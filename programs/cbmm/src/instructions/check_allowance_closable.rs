@@ -0,0 +1,114 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CheckAllowanceClosable<'info> {
+    #[account(
+        seeds = [
+            USER_BURN_ALLOWANCE_SEED,
+            user_burn_allowance.user.as_ref(),
+            platform_config.key().as_ref(),
+            &[user_burn_allowance.burn_tier_index],
+            user_burn_allowance.burn_tier_update_timestamp.to_le_bytes().as_ref(),
+        ],
+        bump = user_burn_allowance.bump,
+    )]
+    pub user_burn_allowance: Account<'info, UserBurnAllowance>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Read-only check whether `close_user_burn_allowance` would currently succeed.
+/// The result is returned via return data.
+pub fn check_allowance_closable(ctx: Context<CheckAllowanceClosable>) -> Result<bool> {
+    let now = Clock::get()?.unix_timestamp;
+    let platform_config = &ctx.accounts.platform_config;
+    Ok(ctx.accounts.user_burn_allowance.is_closable(
+        platform_config.burn_tiers_updated_at,
+        platform_config.burn_reset_interval_seconds,
+        now,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::PlatformConfig;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    const NOW: i64 = 1682899200;
+    const DAY: i64 = 86400;
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config = runner.create_platform_config_mock(
+            &payer,
+            quote_mint,
+            5,
+            5,
+            2,
+            1,
+            200,
+            600,
+            200,
+            None,
+        );
+        runner.set_system_clock(NOW);
+        (runner, payer, platform_config)
+    }
+
+    #[test_case(0, NOW - 3600, true; "zero_burns")]
+    #[test_case(1, NOW - 3600, false; "burned_recently")]
+    #[test_case(1, NOW - DAY, true; "past_reset_interval")]
+    fn test_check_allowance_closable(burns_today: u16, last_burn_timestamp: i64, expected: bool) {
+        let (mut runner, payer, platform_config) = setup_test();
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            payer.pubkey(),
+            payer.pubkey(),
+            platform_config,
+            burns_today,
+            last_burn_timestamp,
+            false,
+            NOW - 2 * DAY,
+        );
+
+        let closable = runner
+            .check_allowance_closable(&payer, user_burn_allowance, platform_config)
+            .unwrap();
+        assert_eq!(closable, expected);
+    }
+
+    #[test]
+    fn test_check_allowance_closable_stale_tier() {
+        let (mut runner, payer, platform_config) = setup_test();
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            payer.pubkey(),
+            payer.pubkey(),
+            platform_config,
+            1,
+            NOW - 3600,
+            false,
+            NOW - 2 * DAY,
+        );
+        assert!(!runner
+            .check_allowance_closable(&payer, user_burn_allowance, platform_config)
+            .unwrap());
+
+        // Updating the burn tiers invalidates the allowance, making it closable
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.burn_tiers_updated_at = NOW - 60;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        assert!(runner
+            .check_allowance_closable(&payer, user_burn_allowance, platform_config)
+            .unwrap());
+    }
+}
@@ -20,8 +20,9 @@ pub struct ClaimCreatorFees<'info> {
         seeds = [
             CBMM_POOL_SEED,
             pool.pool_index.to_le_bytes().as_ref(),
-            pool.creator.as_ref(),
+            pool.seed_creator.as_ref(),
             pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
         ],
         bump = pool.bump,
     )]
@@ -40,22 +41,57 @@ pub struct ClaimCreatorFees<'info> {
 }
 
 pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {        
-    let pool = &mut ctx.accounts.pool;
+    transfer_creator_fees(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.pool_ata,
+        &ctx.accounts.owner_ata,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.token_program,
+    )
+}
+
+/// Moves the whole creator fee balance from the pool ATA to `owner_ata`, checking that nothing
+/// else left the pool. Leaves `pool_ata` reloaded.
+pub(crate) fn transfer_creator_fees<'info>(
+    pool: &mut Account<'info, CbmmPool>,
+    pool_ata: &mut InterfaceAccount<'info, TokenAccount>,
+    owner_ata: &InterfaceAccount<'info, TokenAccount>,
+    quote_mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
     let amount = pool.creator_fees_balance;
     if amount == 0 {
         return Ok(()); // No-op
     }
+    let quote_reserve = pool.quote_reserve;
+    let buyback_fees_balance = pool.buyback_fees_balance;
+    let platform_fees_balance = pool.platform_fees_balance;
+    let pool_ata_balance = pool_ata.amount;
+
     pool.creator_fees_balance = 0;
     let pool_account_info = pool.to_account_info();
     pool.transfer_out(
         amount,
         &pool_account_info,
-        &ctx.accounts.quote_mint,
-        &ctx.accounts.pool_ata,
-        &ctx.accounts.owner_ata,
-        &ctx.accounts.token_program,
+        quote_mint,
+        pool_ata,
+        owner_ata,
+        token_program,
     )?;
 
+    // The creator may only ever withdraw their own fees, never reserves or other fee balances
+    pool_ata.reload()?;
+    require!(
+        pool_ata_balance.saturating_sub(pool_ata.amount) <= amount,
+        CbmmError::InvariantViolated
+    );
+    require!(
+        pool.quote_reserve == quote_reserve
+            && pool.buyback_fees_balance == buyback_fees_balance
+            && pool.platform_fees_balance == platform_fees_balance,
+        CbmmError::InvariantViolated
+    );
+
     Ok(())
 }
 
@@ -173,4 +209,50 @@ mod tests {
         );
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_claim_creator_fees_leaves_reserves_untouched() {
+        let (mut runner, owner, pool, owner_ata, quote_mint) = setup_test();
+
+        // Give the pool trading reserves and other fee balances next to the creator fees
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.quote_reserve = 50_000;
+        pool_data.buyback_fees_balance = 3000;
+        pool_data.platform_fees_balance = 1000;
+        runner.put_account_on_chain(&pool, pool_data);
+        runner.mint_tokens(&owner, pool, quote_mint, 54_000);
+
+        runner
+            .claim_creator_fees(&owner, owner_ata, quote_mint, pool)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let final_pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(final_pool_data.creator_fees_balance, 0);
+        assert_eq!(final_pool_data.quote_reserve, 50_000);
+        assert_eq!(final_pool_data.buyback_fees_balance, 3000);
+        assert_eq!(final_pool_data.platform_fees_balance, 1000);
+
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(quote_mint.to_bytes()),
+        );
+        let pool_ata_account = runner
+            .svm
+            .get_account(&Pubkey::from(pool_ata.to_bytes()))
+            .unwrap();
+        let pool_ata_balance =
+            anchor_spl::token::spl_token::state::Account::unpack(&pool_ata_account.data)
+                .unwrap()
+                .amount;
+        assert_eq!(pool_ata_balance, 54_000);
+
+        let owner_ata_account = runner.svm.get_account(&owner_ata).unwrap();
+        let owner_balance =
+            anchor_spl::token::spl_token::state::Account::unpack(&owner_ata_account.data)
+                .unwrap()
+                .amount;
+        assert_eq!(owner_balance, 1000);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::errors::CbmmError;
+use crate::instructions::{transfer_creator_fees, wind_down_pool};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct ClaimCreatorFeesAndClose<'info> {
+    #[account(mut, address = pool.creator @ CbmmError::InvalidPoolOwner)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_REGISTRY_SEED, pool.platform_config.as_ref()],
+        bump = platform_registry.bump,
+    )]
+    pub platform_registry: Account<'info, PlatformRegistry>,
+
+    /// Registry slot of the pool, omitted for pools that predate slots
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_POOL_ENTRY_SEED,
+            pool.platform_config.as_ref(),
+            pool_entry.index.to_le_bytes().as_ref(),
+        ],
+        bump = pool_entry.bump,
+        constraint = pool_entry.pool == pool.key() @ CbmmError::InvalidPoolEntry,
+    )]
+    pub pool_entry: Option<Account<'info, PlatformPoolEntry>>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// `claim_creator_fees` followed by `close_pool` if the pool is closable once the creator fees
+/// are out. A pool that is still live is only claimed from.
+pub fn claim_creator_fees_and_close(ctx: Context<ClaimCreatorFeesAndClose>) -> Result<()> {
+    transfer_creator_fees(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.pool_ata,
+        &ctx.accounts.owner_ata,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.token_program,
+    )?;
+    if !ctx.accounts.pool.is_closable() {
+        return Ok(());
+    }
+
+    let owner = ctx.accounts.owner.to_account_info();
+    wind_down_pool(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.pool_ata,
+        &ctx.accounts.owner_ata,
+        &owner,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.platform_registry,
+        ctx.accounts.pool_entry.as_ref(),
+    )?;
+    ctx.accounts.pool.close(owner)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn token_balance(runner: &TestRunner, ata: &Pubkey) -> u64 {
+        let account = runner.svm.get_account(ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    /// Pool with 1_000 A of creator fees and 5_000 A of buyback fees in its ATA
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let owner = Keypair::new();
+        runner.airdrop(&owner.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&owner, 9);
+        let owner_ata =
+            runner.create_associated_token_account(&owner, quote_mint, &owner.pubkey());
+        let platform_config =
+            runner.create_platform_config_mock(&owner, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner
+            .create_pool(&owner, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.creator_fees_balance = 1_000;
+        pool_data.buyback_fees_balance = 5_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        runner.mint_tokens(&owner, pool.pool, quote_mint, 6_000);
+
+        (runner, owner, platform_config, pool.pool, owner_ata, quote_mint)
+    }
+
+    #[test]
+    fn test_claim_creator_fees_and_close_live_pool_only_claims() {
+        let (mut runner, owner, platform_config, pool, owner_ata, quote_mint) = setup_test();
+        // Virtual tokens still held by users
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.base_reserve -= 1_000;
+        runner.put_account_on_chain(&pool, pool_data);
+
+        runner
+            .claim_creator_fees_and_close(&owner, owner_ata, quote_mint, pool)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.creator_fees_balance, 0);
+        assert_eq!(pool_data.buyback_fees_balance, 5_000);
+        assert_eq!(token_balance(&runner, &owner_ata), 1_000);
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 1);
+    }
+
+    #[test]
+    fn test_claim_creator_fees_and_close_closable_pool() {
+        let (mut runner, owner, platform_config, pool, owner_ata, quote_mint) = setup_test();
+        let owner_lamports = runner.svm.get_balance(&owner.pubkey()).unwrap();
+
+        runner
+            .claim_creator_fees_and_close(&owner, owner_ata, quote_mint, pool)
+            .unwrap();
+
+        assert!(runner.svm.get_account(&pool).is_none());
+        assert!(runner.get_pool_entry(platform_config, 0).is_none());
+        // Only the creator fees, the buyback fees stay in the pool ATA
+        assert_eq!(token_balance(&runner, &owner_ata), 1_000);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(quote_mint.to_bytes()),
+        );
+        assert_eq!(token_balance(&runner, &Pubkey::from(pool_ata.to_bytes())), 5_000);
+        // Rent of the pool and its registry entry outweighs the transaction fee
+        assert!(runner.svm.get_balance(&owner.pubkey()).unwrap() > owner_lamports);
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 0);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct ClaimGraduatedTokens<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.graduated @ CbmmError::NotGraduated,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut, address = pool.base_mint @ CbmmError::InvalidMint)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = base_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_base_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Mints the owner's virtual balance 1:1 as real B tokens and zeroes the virtual balance.
+pub fn claim_graduated_tokens(ctx: Context<ClaimGraduatedTokens>) -> Result<()> {
+    let amount = ctx.accounts.virtual_token_account.balance;
+    require_gt!(amount, 0, CbmmError::AmountTooSmall);
+    ctx.accounts.virtual_token_account.sub(amount)?;
+
+    let pool = &ctx.accounts.pool;
+    pool.mint_base_to(
+        amount,
+        &pool.to_account_info(),
+        &ctx.accounts.base_mint,
+        &ctx.accounts.owner_base_ata,
+        &ctx.accounts.token_program,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::VirtualTokenAccount;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_claim_graduated_tokens() {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+
+        let holders = [(Keypair::new(), 1234), (Keypair::new(), 5678)];
+        let mut virtual_token_accounts = vec![];
+        for (holder, balance) in &holders {
+            runner.airdrop(&holder.pubkey(), 10_000_000_000);
+            virtual_token_accounts.push(runner.create_virtual_token_account_mock(
+                holder.pubkey(),
+                pool.pool,
+                *balance,
+            ));
+        }
+
+        // Nothing to claim before graduation
+        assert!(runner
+            .claim_graduated_tokens(&holders[0].0, pool.pool)
+            .is_err());
+
+        runner.graduate_pool(&admin, pool.pool).unwrap();
+        for ((holder, balance), virtual_token_account) in
+            holders.iter().zip(&virtual_token_accounts)
+        {
+            let owner_base_ata = runner.claim_graduated_tokens(holder, pool.pool).unwrap();
+            let ata_account = runner.svm.get_account(&owner_base_ata).unwrap();
+            let claimed = anchor_spl::token::spl_token::state::Account::unpack(&ata_account.data)
+                .unwrap()
+                .amount;
+            assert_eq!(claimed, *balance);
+
+            let vta_account = runner.svm.get_account(virtual_token_account).unwrap();
+            let vta_data =
+                VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+            assert_eq!(vta_data.balance, 0);
+
+            // A second claim finds an empty balance
+            runner.svm.expire_blockhash();
+            assert!(runner.claim_graduated_tokens(holder, pool.pool).is_err());
+        }
+    }
+}
@@ -4,15 +4,21 @@ use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimPlatformFeesArgs {
+    /// Amount of Mint A to claim, including decimals. `None` claims the whole balance.
+    pub amount: Option<u64>,
+}
+
 #[derive(Accounts)]
 pub struct ClaimPlatformFees<'info> {
     #[account(mut, address = platform_config.admin @ CbmmError::InvalidPlatformAdmin)]
     pub admin: Signer<'info>,
 
     #[account(mut,
-        associated_token::mint = quote_mint,
-        associated_token::authority = admin,
-        associated_token::token_program = token_program
+        token::mint = quote_mint,
+        token::token_program = token_program,
+        constraint = admin_ata.owner == platform_config.platform_fee_recipient() @ CbmmError::InvalidFeeRecipient,
     )]
     pub admin_ata: InterfaceAccount<'info, TokenAccount>,
 
@@ -21,8 +27,9 @@ pub struct ClaimPlatformFees<'info> {
         seeds = [
             CBMM_POOL_SEED,
             pool.pool_index.to_le_bytes().as_ref(),
-            pool.creator.as_ref(),
+            pool.seed_creator.as_ref(),
             pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
         ],
         bump = pool.bump,
     )]
@@ -44,13 +51,19 @@ pub struct ClaimPlatformFees<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn claim_platform_fees(ctx: Context<ClaimPlatformFees>) -> Result<()> {
+/// Claims `args.amount` of the platform fees, or all of them, leaving the rest in the pool for
+/// a later claim.
+pub fn claim_platform_fees(
+    ctx: Context<ClaimPlatformFees>,
+    args: ClaimPlatformFeesArgs,
+) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
-    let amount = pool.platform_fees_balance;
+    let amount = args.amount.unwrap_or(pool.platform_fees_balance);
+    require_gte!(pool.platform_fees_balance, amount, CbmmError::AmountTooBig);
     if amount == 0 {
         return Ok(()); // No-op
     }
-    pool.platform_fees_balance = 0;
+    pool.platform_fees_balance -= amount;
     let pool_account_info = pool.to_account_info();
     pool.transfer_out(
         amount,
@@ -66,8 +79,9 @@ pub fn claim_platform_fees(ctx: Context<ClaimPlatformFees>) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use crate::state::CbmmPool;
-    use crate::test_utils::TestRunner;
+    use crate::instructions::ClaimPlatformFeesArgs;
+    use crate::state::{CbmmPool, PlatformConfig};
+    use crate::test_utils::{TestRunner, TransactionError};
     use anchor_lang::prelude::*;
     use anchor_lang::solana_program::program_pack::Pack;
     use solana_sdk::instruction::AccountMeta;
@@ -149,19 +163,24 @@ mod tests {
         )
     }
 
-    #[test]
-    fn test_claim_platform_fees() {
-        let (mut runner, admin, pool, pool_ata, admin_ata, quote_mint) = setup_test();
-
+    /// Builds the instruction by hand, as a reference for `TestRunner::claim_platform_fees`
+    fn claim_platform_fees_manual(
+        runner: &mut TestRunner,
+        signer: &Keypair,
+        recipient_ata: SdkPubkey,
+        pool: SdkPubkey,
+        pool_ata: SdkPubkey,
+        quote_mint: SdkPubkey,
+        amount: Option<u64>,
+    ) -> std::result::Result<(), TransactionError> {
         let pool_account = runner.svm.get_account(&pool).unwrap();
         let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
-        // platform_config is likely SdkPubkey or Anchor Pubkey depending on CbmmPool definition.
         // CbmmPool uses Anchor Pubkey. We need SdkPubkey for AccountMeta.
         let platform_config = SdkPubkey::new_from_array(pool_data.platform_config.to_bytes());
 
         let accounts = vec![
-            AccountMeta::new(admin.pubkey(), true),
-            AccountMeta::new(admin_ata, false),
+            AccountMeta::new(signer.pubkey(), true),
+            AccountMeta::new(recipient_ata, false),
             AccountMeta::new(pool, false),
             AccountMeta::new(pool_ata, false),
             AccountMeta::new_readonly(platform_config, false),
@@ -182,7 +201,37 @@ mod tests {
             ),
         ];
 
-        let result = runner.send_instruction("claim_platform_fees", accounts, (), &[&admin]);
+        runner.send_instruction(
+            "claim_platform_fees",
+            accounts,
+            ClaimPlatformFeesArgs { amount },
+            &[signer],
+        )
+    }
+
+    fn token_balance(runner: &TestRunner, ata: &SdkPubkey) -> u64 {
+        let account = runner.svm.get_account(ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    fn set_fee_recipient(runner: &mut TestRunner, pool: &SdkPubkey, fee_recipient: SdkPubkey) {
+        let pool_account = runner.svm.get_account(pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config = SdkPubkey::new_from_array(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.fee_recipient = Pubkey::new_from_array(fee_recipient.to_bytes());
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+    }
+
+    #[test]
+    fn test_claim_platform_fees() {
+        let (mut runner, admin, pool, _, admin_ata, quote_mint) = setup_test();
+
+        let result = runner.claim_platform_fees(&admin, admin_ata, quote_mint, pool, None);
         if let Err(ref e) = result {
             eprintln!("claim_platform_fees error: {:?}", e);
         }
@@ -194,51 +243,129 @@ mod tests {
         assert_eq!(pool_data.platform_fees_balance, 0);
 
         // Check that admin ATA balance increased by the claimed amount
-        let admin_ata_account = runner.svm.get_account(&admin_ata).unwrap();
-        let final_admin_balance =
-            anchor_spl::token::spl_token::state::Account::unpack(&admin_ata_account.data)
+        assert_eq!(token_balance(&runner, &admin_ata), 500);
+    }
+
+    #[test]
+    fn test_claim_platform_fees_helper_matches_manual() {
+        let (mut helper, admin, pool, pool_ata, admin_ata, quote_mint) = setup_test();
+        helper
+            .claim_platform_fees(&admin, admin_ata, quote_mint, pool, Some(200))
+            .unwrap();
+
+        let (mut manual, m_admin, m_pool, m_pool_ata, m_admin_ata, m_quote_mint) = setup_test();
+        claim_platform_fees_manual(
+            &mut manual,
+            &m_admin,
+            m_admin_ata,
+            m_pool,
+            m_pool_ata,
+            m_quote_mint,
+            Some(200),
+        )
+        .unwrap();
+
+        let platform_fees_balance = |runner: &TestRunner, pool: &SdkPubkey| {
+            let pool_account = runner.svm.get_account(pool).unwrap();
+            CbmmPool::try_deserialize(&mut pool_account.data.as_slice())
                 .unwrap()
-                .amount;
-        assert_eq!(final_admin_balance, 500);
+                .platform_fees_balance
+        };
+        assert_eq!(platform_fees_balance(&helper, &pool), 300);
+        assert_eq!(
+            platform_fees_balance(&helper, &pool),
+            platform_fees_balance(&manual, &m_pool)
+        );
+        assert_eq!(token_balance(&helper, &admin_ata), 200);
+        assert_eq!(
+            token_balance(&helper, &admin_ata),
+            token_balance(&manual, &m_admin_ata)
+        );
+        assert_eq!(
+            token_balance(&helper, &pool_ata),
+            token_balance(&manual, &m_pool_ata)
+        );
+    }
+
+    #[test]
+    fn test_claim_platform_fees_partial() {
+        let (mut runner, admin, pool, pool_ata, admin_ata, quote_mint) = setup_test();
+
+        runner
+            .claim_platform_fees(&admin, admin_ata, quote_mint, pool, Some(200))
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.platform_fees_balance, 300);
+        assert_eq!(token_balance(&runner, &admin_ata), 200);
+        assert_eq!(token_balance(&runner, &pool_ata), 300);
+
+        // The rest stays claimable
+        runner
+            .claim_platform_fees(&admin, admin_ata, quote_mint, pool, None)
+            .unwrap();
+        assert_eq!(token_balance(&runner, &admin_ata), 500);
+    }
+
+    #[test]
+    fn test_claim_platform_fees_over_claim() {
+        let (mut runner, admin, pool, _, admin_ata, quote_mint) = setup_test();
+
+        assert!(runner
+            .claim_platform_fees(&admin, admin_ata, quote_mint, pool, Some(501))
+            .is_err());
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.platform_fees_balance, 500);
+        assert_eq!(token_balance(&runner, &admin_ata), 0);
     }
 
     #[test]
     fn test_claim_platform_fees_wrong_authority() {
-        let (mut runner, _, pool, pool_ata, _, quote_mint) = setup_test();
+        let (mut runner, _, pool, _, _, quote_mint) = setup_test();
 
         let other_user = Keypair::new();
         runner.airdrop(&other_user.pubkey(), 10_000_000_000);
         let other_user_ata =
             runner.create_associated_token_account(&other_user, quote_mint, &other_user.pubkey());
 
-        let pool_account = runner.svm.get_account(&pool).unwrap();
-        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
-        let platform_config = SdkPubkey::new_from_array(pool_data.platform_config.to_bytes());
+        let result =
+            runner.claim_platform_fees(&other_user, other_user_ata, quote_mint, pool, None);
+        assert!(result.is_err());
+    }
 
-        let accounts = vec![
-            AccountMeta::new(other_user.pubkey(), true),
-            AccountMeta::new(other_user_ata, false),
-            AccountMeta::new(pool, false),
-            AccountMeta::new(pool_ata, false),
-            AccountMeta::new_readonly(platform_config, false),
-            AccountMeta::new_readonly(quote_mint, false),
-            AccountMeta::new_readonly(
-                SdkPubkey::new_from_array(anchor_spl::token::spl_token::ID.to_bytes()),
-                false,
-            ),
-            AccountMeta::new_readonly(
-                SdkPubkey::new_from_array(anchor_spl::associated_token::ID.to_bytes()),
-                false,
-            ),
-            AccountMeta::new_readonly(
-                SdkPubkey::new_from_array(
-                    anchor_lang::solana_program::system_program::ID.to_bytes(),
-                ),
-                false,
-            ),
-        ];
+    #[test]
+    fn test_claim_platform_fees_to_fee_recipient() {
+        let (mut runner, admin, pool, _, admin_ata, quote_mint) = setup_test();
+        let treasury = Keypair::new();
+        let treasury_ata =
+            runner.create_associated_token_account(&admin, quote_mint, &treasury.pubkey());
+        set_fee_recipient(&mut runner, &pool, treasury.pubkey());
 
-        let result = runner.send_instruction("claim_platform_fees", accounts, (), &[&other_user]);
-        assert!(result.is_err());
+        // The admin still triggers the claim, but can no longer receive the fees
+        assert!(runner
+            .claim_platform_fees(&admin, admin_ata, quote_mint, pool, None)
+            .is_err());
+        runner
+            .claim_platform_fees(&admin, treasury_ata, quote_mint, pool, None)
+            .unwrap();
+        assert_eq!(token_balance(&runner, &treasury_ata), 500);
+        assert_eq!(token_balance(&runner, &admin_ata), 0);
+    }
+
+    #[test]
+    fn test_claim_platform_fees_recipient_cannot_claim() {
+        let (mut runner, _, pool, _, _, quote_mint) = setup_test();
+        let treasury = Keypair::new();
+        runner.airdrop(&treasury.pubkey(), 10_000_000_000);
+        let treasury_ata =
+            runner.create_associated_token_account(&treasury, quote_mint, &treasury.pubkey());
+        set_fee_recipient(&mut runner, &pool, treasury.pubkey());
+
+        assert!(runner
+            .claim_platform_fees(&treasury, treasury_ata, quote_mint, pool, None)
+            .is_err());
     }
 }
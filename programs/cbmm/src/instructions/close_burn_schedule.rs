@@ -0,0 +1,23 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseBurnSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = authority @ CbmmError::InvalidOwner,
+        seeds = [BURN_SCHEDULE_SEED, burn_schedule.pool.as_ref()],
+        bump = burn_schedule.bump,
+    )]
+    pub burn_schedule: Account<'info, BurnSchedule>,
+}
+
+/// Stops a burn schedule and refunds its rent to the authority.
+pub fn close_burn_schedule(_ctx: Context<CloseBurnSchedule>) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ClosePlatformConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: Empty if no pool was ever created on the platform, otherwise a `PlatformRegistry`
+    #[account(
+        mut,
+        seeds = [PLATFORM_REGISTRY_SEED, platform_config.key().as_ref()],
+        bump,
+    )]
+    pub platform_registry: UncheckedAccount<'info>,
+}
+
+/// Closes a platform once all of its pools are closed, refunding the rent of the config and
+/// its registry to the admin. Platform and buyback fees accrue on the pools and a pool only
+/// closes once drained, so with no open pools nothing is left to claim.
+pub fn close_platform_config(ctx: Context<ClosePlatformConfig>) -> Result<()> {
+    let registry = ctx.accounts.platform_registry.to_account_info();
+    // The registry is created by the first `create_pool`, so a platform without pools has none
+    if registry.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(*registry.owner, crate::ID, CbmmError::InvalidOwner);
+    let registry_data = PlatformRegistry::try_deserialize(&mut &registry.try_borrow_data()?[..])?;
+    require_eq!(registry_data.pool_count, 0, CbmmError::PlatformHasOpenPools);
+
+    // Same steps as Anchor's `close` constraint
+    let admin = ctx.accounts.admin.to_account_info();
+    admin.add_lamports(registry.lamports())?;
+    registry.sub_lamports(registry.lamports())?;
+    registry.assign(&System::id());
+    registry.resize(0)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::PlatformRegistry;
+    use crate::test_utils::TestRunner;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        (runner, admin, platform_config, quote_mint)
+    }
+
+    #[test]
+    fn test_close_platform_config_after_pools_closed() {
+        let (mut runner, admin, platform_config, _) = setup_test();
+        // Every pool the platform had is closed again
+        let registry = runner.get_platform_registry_pda(platform_config);
+        runner.put_account_on_chain(
+            &registry,
+            PlatformRegistry {
+                platform_config: anchor_lang::prelude::Pubkey::from(platform_config.to_bytes()),
+                pool_count: 0,
+                total_pools_created: 2,
+                ..Default::default()
+            },
+        );
+        let admin_lamports = runner.svm.get_balance(&admin.pubkey()).unwrap();
+
+        runner
+            .close_platform_config(&admin, platform_config)
+            .unwrap();
+
+        assert!(runner.svm.get_account(&platform_config).is_none());
+        assert!(runner.svm.get_account(&registry).is_none());
+        assert!(runner.svm.get_balance(&admin.pubkey()).unwrap() > admin_lamports);
+    }
+
+    #[test]
+    fn test_close_platform_config_never_had_pools() {
+        let (mut runner, admin, platform_config, _) = setup_test();
+        runner
+            .close_platform_config(&admin, platform_config)
+            .unwrap();
+        assert!(runner.svm.get_account(&platform_config).is_none());
+        assert!(runner.get_platform_registry(platform_config).is_none());
+    }
+
+    #[test]
+    fn test_close_platform_config_rejects_open_pools() {
+        let (mut runner, admin, platform_config, quote_mint) = setup_test();
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        assert!(runner
+            .close_platform_config(&admin, platform_config)
+            .is_err());
+        assert!(runner.svm.get_account(&platform_config).is_some());
+    }
+
+    #[test]
+    fn test_close_platform_config_wrong_admin() {
+        let (mut runner, _, platform_config, _) = setup_test();
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 1_000_000_000);
+        assert!(runner
+            .close_platform_config(&stranger, platform_config)
+            .is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(mut, address = pool.creator @ CbmmError::InvalidPoolOwner)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.is_closable() @ CbmmError::PoolNotClosable,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_REGISTRY_SEED, pool.platform_config.as_ref()],
+        bump = platform_registry.bump,
+    )]
+    pub platform_registry: Account<'info, PlatformRegistry>,
+
+    /// Registry slot of the pool, omitted for pools that predate slots
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_POOL_ENTRY_SEED,
+            pool.platform_config.as_ref(),
+            pool_entry.index.to_le_bytes().as_ref(),
+        ],
+        bump = pool_entry.bump,
+        constraint = pool_entry.pool == pool.key() @ CbmmError::InvalidPoolEntry,
+    )]
+    pub pool_entry: Option<Account<'info, PlatformPoolEntry>>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+    wind_down_pool(
+        &mut ctx.accounts.pool,
+        &ctx.accounts.pool_ata,
+        &ctx.accounts.owner_ata,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.token_program,
+        &mut ctx.accounts.platform_registry,
+        ctx.accounts.pool_entry.as_ref(),
+    )
+}
+
+/// Pays the creator's share of the pool ATA out, closes the ATA if nothing else is left in it
+/// and unregisters the pool. Closing the pool account itself is left to the caller.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn wind_down_pool<'info>(
+    pool: &mut Account<'info, CbmmPool>,
+    pool_ata: &InterfaceAccount<'info, TokenAccount>,
+    owner_ata: &InterfaceAccount<'info, TokenAccount>,
+    owner: &AccountInfo<'info>,
+    quote_mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    platform_registry: &mut Account<'info, PlatformRegistry>,
+    pool_entry: Option<&Account<'info, PlatformPoolEntry>>,
+) -> Result<()> {
+    require!(
+        pool.registry_index.is_none() || pool_entry.is_some(),
+        CbmmError::InvalidPoolEntry
+    );
+    let pool_account_info = pool.to_account_info();
+
+    // With no tokens in circulation the residual reserve has no claimants left. Buyback fees
+    // and locked liquidity are not the creator's to take and stay in the pool ATA.
+    let retained = pool.non_creator_balance()?;
+    let creator_share = pool_ata.amount.saturating_sub(retained);
+    if creator_share > 0 {
+        pool.transfer_out(
+            creator_share,
+            &pool_account_info,
+            quote_mint,
+            pool_ata,
+            owner_ata,
+            token_program,
+        )?;
+    }
+    if retained == 0 {
+        pool.close_pool_ata(&pool_account_info, pool_ata, owner, token_program)?;
+    }
+
+    platform_registry.unregister_pool()?;
+    if let Some(pool_entry) = pool_entry {
+        pool_entry.close(owner.clone())?;
+    }
+    Ok(())
+}
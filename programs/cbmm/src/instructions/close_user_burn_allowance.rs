@@ -25,17 +25,111 @@ pub struct CloseUserBurnAllowance<'info> {
     pub platform_config: Account<'info, PlatformConfig>,
 
     /// CHECK: Checked that it's the same as the payer in the user burn allowance account.
-    #[account(address = user_burn_allowance.payer @ CbmmError::InvalidBurnAccountPayer)]
+    /// Writable since it receives the rent refund.
+    #[account(mut, address = user_burn_allowance.payer @ CbmmError::InvalidBurnAccountPayer)]
     pub burn_allowance_open_payer: AccountInfo<'info>,
 }
 
 pub fn close_user_burn_allowance(ctx: Context<CloseUserBurnAllowance>) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
-    let is_closable = ctx
-        .accounts
-        .user_burn_allowance
-        .is_closable(ctx.accounts.platform_config.burn_tiers_updated_at, now);
+    let platform_config = &ctx.accounts.platform_config;
+    let is_closable = ctx.accounts.user_burn_allowance.is_closable(
+        platform_config.burn_tiers_updated_at,
+        platform_config.burn_reset_interval_seconds,
+        now,
+    );
     require!(is_closable, CbmmError::CannotCloseActiveBurnAllowance);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TestRunner;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+    const DAY: i64 = 86400;
+
+    fn setup_test(burns_today: u16) -> (TestRunner, Keypair, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let user = Keypair::new();
+        let funder = Keypair::new();
+        runner.airdrop(&user.pubkey(), 10_000_000_000);
+        runner.airdrop(&funder.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&user, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&user, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        runner.set_system_clock(NOW);
+        // Funded by someone else than the user it belongs to
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            user.pubkey(),
+            funder.pubkey(),
+            platform_config,
+            burns_today,
+            NOW - 3600,
+            false,
+            NOW - 2 * DAY,
+        );
+        (runner, user, funder, platform_config, user_burn_allowance)
+    }
+
+    #[test]
+    fn test_close_user_burn_allowance_refunds_payer() {
+        let (mut runner, user, funder, platform_config, user_burn_allowance) = setup_test(0);
+        let rent = runner.svm.get_balance(&user_burn_allowance).unwrap();
+        let funder_balance = runner.svm.get_balance(&funder.pubkey()).unwrap();
+        let user_balance = runner.svm.get_balance(&user.pubkey()).unwrap();
+
+        runner
+            .close_user_burn_allowance(
+                &user,
+                user.pubkey(),
+                platform_config,
+                user_burn_allowance,
+                funder.pubkey(),
+            )
+            .unwrap();
+
+        assert!(runner.get_user_burn_allowance(&user_burn_allowance).is_err());
+        assert_eq!(
+            runner.svm.get_balance(&funder.pubkey()).unwrap(),
+            funder_balance + rent
+        );
+        // The signer only pays the transaction fee
+        assert!(runner.svm.get_balance(&user.pubkey()).unwrap() < user_balance);
+    }
+
+    #[test]
+    fn test_close_user_burn_allowance_not_closable() {
+        let (mut runner, user, funder, platform_config, user_burn_allowance) = setup_test(1);
+
+        assert!(runner
+            .close_user_burn_allowance(
+                &user,
+                user.pubkey(),
+                platform_config,
+                user_burn_allowance,
+                funder.pubkey(),
+            )
+            .is_err());
+        assert!(runner.get_user_burn_allowance(&user_burn_allowance).is_ok());
+    }
+
+    #[test]
+    fn test_close_user_burn_allowance_wrong_refund_recipient() {
+        let (mut runner, user, _, platform_config, user_burn_allowance) = setup_test(0);
+
+        assert!(runner
+            .close_user_burn_allowance(
+                &user,
+                user.pubkey(),
+                platform_config,
+                user_burn_allowance,
+                user.pubkey(),
+            )
+            .is_err());
+        assert!(runner.get_user_burn_allowance(&user_burn_allowance).is_ok());
+    }
+}
@@ -0,0 +1,201 @@
+use crate::errors::CbmmError;
+use crate::instructions::emit_topup_event;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CrankTopupArgs {
+    /// Fewest A the topup must move from the buyback fees into the real reserve
+    pub min_topup_amount: u64,
+    /// Highest virtual reserve the pool may end up with (None = no bound)
+    pub max_virtual_reserve: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct CrankTopup<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(address = pool.platform_config @ CbmmError::InvalidPlatformConfig)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Permissionless crank: spends the buyback fees on a topup outside of any trade. The guards
+/// let a keeper revert if a trade landing just before it moved the topup away from what was
+/// quoted.
+pub fn crank_topup(ctx: Context<CrankTopup>, args: CrankTopupArgs) -> Result<()> {
+    require!(
+        !ctx.accounts.platform_config.paused,
+        CbmmError::PlatformPaused
+    );
+    let pool = &mut ctx.accounts.pool;
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+
+    let topup_paid = pool.topup()?;
+    require_gte!(
+        topup_paid,
+        args.min_topup_amount,
+        CbmmError::SlippageExceeded
+    );
+    if let Some(max_virtual_reserve) = args.max_virtual_reserve {
+        require_gte!(
+            max_virtual_reserve,
+            pool.quote_virtual_reserve,
+            CbmmError::SlippageExceeded
+        );
+    }
+    emit_topup_event(pool, topup_paid);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::CbmmError;
+    use crate::instructions::{CrankTopupArgs, TopupEvent};
+    use crate::state::{CbmmPool, PlatformConfig};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    fn get_pool(runner: &TestRunner, pool: &Pubkey) -> CbmmPool {
+        let account = runner.svm.get_account(pool).unwrap();
+        CbmmPool::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey) {
+        let mut runner = TestRunner::new();
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&keeper, 9);
+        let platform_config = runner
+            .create_platform_config_mock(&keeper, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &keeper,
+            platform_config,
+            quote_mint,
+            500_000,
+            500_000,
+            500_000,
+            1_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            100_000,
+            300_000,
+        );
+        // Burns left the pool below the optimal curve of V = 1M and A = 1M
+        let mut pool_data = get_pool(&runner, &pool.pool);
+        pool_data.quote_starting_virtual_reserve = 1_000_000;
+        pool_data.base_starting_total_supply = 1_000_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        (runner, keeper, pool.pool)
+    }
+
+    /// Pool state the crank should produce, replayed off-chain
+    fn expected_topup(runner: &TestRunner, pool: &Pubkey) -> (u64, CbmmPool) {
+        let mut pool_data = get_pool(runner, pool);
+        let topup_paid = pool_data.topup().unwrap();
+        (topup_paid, pool_data)
+    }
+
+    #[test]
+    fn test_crank_topup_guard_passes() {
+        let (mut runner, keeper, pool) = setup_test();
+        let (topup_paid, expected) = expected_topup(&runner, &pool);
+        assert_eq!(topup_paid, 100_000);
+
+        runner
+            .crank_topup(
+                &keeper,
+                pool,
+                CrankTopupArgs {
+                    min_topup_amount: topup_paid,
+                    max_virtual_reserve: Some(expected.quote_virtual_reserve),
+                },
+            )
+            .unwrap();
+
+        let pool_data = get_pool(&runner, &pool);
+        assert_eq!(pool_data.buyback_fees_balance, 0);
+        assert_eq!(pool_data.quote_reserve, 600_000);
+        assert_eq!(
+            pool_data.quote_virtual_reserve,
+            expected.quote_virtual_reserve
+        );
+        let events = runner.last_events::<TopupEvent>();
+        assert_eq!(events[0].real_topup_amount, topup_paid);
+        assert_eq!(
+            events[0].quote_outstanding_topup,
+            pool_data.quote_outstanding_topup
+        );
+    }
+
+    #[test_case(1, 0; "topup_too_small")]
+    #[test_case(0, 1; "virtual_reserve_too_high")]
+    fn test_crank_topup_guard_trips(topup_shortfall: u64, virtual_reserve_shortfall: u64) {
+        let (mut runner, keeper, pool) = setup_test();
+        let (topup_paid, expected) = expected_topup(&runner, &pool);
+
+        assert!(runner
+            .crank_topup(
+                &keeper,
+                pool,
+                CrankTopupArgs {
+                    min_topup_amount: topup_paid + topup_shortfall,
+                    max_virtual_reserve: Some(
+                        expected.quote_virtual_reserve - virtual_reserve_shortfall
+                    ),
+                },
+            )
+            .is_err());
+
+        let pool_data = get_pool(&runner, &pool);
+        assert_eq!(pool_data.buyback_fees_balance, 100_000);
+        assert_eq!(pool_data.quote_reserve, 500_000);
+    }
+
+    #[test]
+    fn test_crank_topup_paused_platform() {
+        let (mut runner, keeper, pool) = setup_test();
+        let platform_config = Pubkey::from(get_pool(&runner, &pool).platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.paused = true;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let err = runner
+            .crank_topup(
+                &keeper,
+                pool,
+                CrankTopupArgs {
+                    min_topup_amount: 0,
+                    max_virtual_reserve: None,
+                },
+            )
+            .unwrap_err();
+        assert!(err
+            .message
+            .contains(&format!("Custom({})", u32::from(CbmmError::PlatformPaused))));
+
+        // The buyback fees wait for the platform to resume
+        assert_eq!(get_pool(&runner, &pool).buyback_fees_balance, 100_000);
+    }
+}
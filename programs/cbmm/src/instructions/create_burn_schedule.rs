@@ -0,0 +1,76 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateBurnScheduleArgs {
+    pub tier_index: u8,
+    pub interval_seconds: i64,
+}
+
+#[derive(Accounts)]
+pub struct CreateBurnSchedule<'info> {
+    #[account(mut, address = pool.creator @ CbmmError::InvalidPoolCreator)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + BurnSchedule::INIT_SPACE,
+        seeds = [BURN_SCHEDULE_SEED, pool.key().as_ref()],
+        bump,
+    )]
+    pub burn_schedule: Account<'info, BurnSchedule>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Lets the pool creator hand a burn cadence to keepers. The first run is due immediately and
+/// every run spends one burn from the creator's allowance on `tier_index`.
+pub fn create_burn_schedule(
+    ctx: Context<CreateBurnSchedule>,
+    args: CreateBurnScheduleArgs,
+) -> Result<()> {
+    require_gt!(
+        ctx.accounts.platform_config.burn_tiers.len() as u8,
+        args.tier_index,
+        CbmmError::InvalidBurnTierIndex
+    );
+    // Runs burn as the creator, who may only use the PoolOwner tier.
+    require!(
+        matches!(
+            ctx.accounts.platform_config.burn_tiers[args.tier_index as usize].role,
+            BurnRole::PoolOwner
+        ),
+        CbmmError::CreatorMustUsePoolOwnerTier
+    );
+    require_gt!(
+        args.interval_seconds,
+        0,
+        CbmmError::InvalidBurnScheduleInterval
+    );
+
+    ctx.accounts.burn_schedule.set_inner(BurnSchedule {
+        bump: ctx.bumps.burn_schedule,
+        pool: ctx.accounts.pool.key(),
+        authority: ctx.accounts.creator.key(),
+        tier_index: args.tier_index,
+        interval_seconds: args.interval_seconds,
+        next_run_ts: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -4,11 +4,52 @@ use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::errors::CbmmError;
 
+#[event]
+pub struct PoolCreated {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub platform_config: Pubkey,
+    pub quote_mint: Pubkey,
+
+    /// Number of open pools on the platform, including this one
+    pub pool_count: u64,
+    /// Index of the pool's `PlatformPoolEntry`
+    pub registry_index: u64,
+}
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreatePoolArgs {
     /// quote_virtual_reserve is the virtual reserve of the A mint including decimals
     pub quote_virtual_reserve: u64,
+
+    /// Seconds a buyer must wait after buying before they can sell (0 = no lockup)
+    pub sell_lockup_seconds: u32,
+
+    /// Floor for the B reserve that burns cannot cross, including decimals
+    pub min_base_reserve: u64,
+
+    /// Merkle root of allowlisted buyers, see `helpers::merkle` (None = open pool)
+    pub allowlist_root: Option<[u8; 32]>,
+
+    /// Most B a single virtual token account may hold, including decimals (0 = unlimited)
+    pub max_vta_balance: u64,
+
+    /// Whether trades top up before or after the swap, see `TopupOrder`
+    pub topup_order: TopupOrder,
+
+    /// Round swap outputs up rather than down, see `CbmmPool::round_in_favor_of_trader`
+    pub round_in_favor_of_trader: bool,
+
+    /// Unix timestamp until which trades pay no fees, see `CbmmPool::fee_holiday_until`. At most
+    /// `PlatformConfig::max_fee_holiday_seconds` from now.
+    pub fee_holiday_until: i64,
+
+    /// Require the creator to co-sign every burn by someone else, see
+    /// `CbmmPool::require_creator_cosign_on_burn`
+    pub require_creator_cosign_on_burn: bool,
+
+    /// Seconds between trades that emit full events, see `CbmmPool::min_event_interval_seconds`
+    pub min_event_interval_seconds: u32,
 }
 #[derive(Accounts)]
 pub struct CreatePool<'info> {
@@ -16,7 +57,7 @@ pub struct CreatePool<'info> {
     pub payer: Signer<'info>,
     #[account(
         mut,
-        constraint = quote_mint.key() == platform_config.quote_mint @ CbmmError::InvalidMint
+        constraint = platform_config.is_quote_mint_allowed(&quote_mint.key()) @ CbmmError::InvalidMint
     )]
     pub quote_mint: InterfaceAccount<'info, Mint>,    
     
@@ -27,7 +68,8 @@ pub struct CreatePool<'info> {
             CBMM_POOL_SEED,
             CBMM_POOL_INDEX_SEED.to_le_bytes().as_ref(),
             payer.key().as_ref(),
-            platform_config.key().as_ref()
+            platform_config.key().as_ref(),
+            quote_mint.key().as_ref()
           ],
          bump
     )]
@@ -49,24 +91,435 @@ pub struct CreatePool<'info> {
     )]
     pub platform_config: Account<'info, PlatformConfig>,
 
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PlatformRegistry::INIT_SPACE + 8,
+        seeds = [PLATFORM_REGISTRY_SEED, platform_config.key().as_ref()],
+        bump
+    )]
+    pub platform_registry: Account<'info, PlatformRegistry>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PlatformPoolEntry::INIT_SPACE + 8,
+        seeds = [
+            PLATFORM_POOL_ENTRY_SEED,
+            platform_config.key().as_ref(),
+            platform_registry.total_pools_created.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub pool_entry: Account<'info, PlatformPoolEntry>,
+
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn create_pool(ctx: Context<CreatePool>, args: CreatePoolArgs) -> Result<()> {
-    require_gte!(args.quote_virtual_reserve, MIN_VIRTUAL_RESERVE, CbmmError::InvalidVirtualReserve);
     let platform_config = &ctx.accounts.platform_config;
+    platform_config.validate_fee_holiday(args.fee_holiday_until, Clock::get()?.unix_timestamp)?;
+    platform_config.validate_event_interval(args.min_event_interval_seconds)?;
+    if platform_config.require_no_freeze_authority {
+        require!(
+            ctx.accounts.quote_mint.freeze_authority.is_none(),
+            CbmmError::InvalidMint
+        );
+    }
     ctx.accounts.pool.set_inner(CbmmPool::try_new(
         ctx.bumps.pool,
         ctx.accounts.payer.key(),
         CBMM_POOL_INDEX_SEED,
         ctx.accounts.platform_config.key(),
         ctx.accounts.quote_mint.key(),
-        args.quote_virtual_reserve,
-        platform_config.pool_creator_fee_bp,
-        platform_config.pool_topup_fee_bp,
-        platform_config.platform_fee_bp,
+        NewPoolParams {
+            quote_virtual_reserve: args.quote_virtual_reserve,
+            creator_fee_bp: platform_config.pool_creator_fee_bp,
+            buyback_fee_bp: platform_config.pool_topup_fee_bp,
+            platform_fee_bp: platform_config.platform_fee_bp,
+            sell_lockup_seconds: args.sell_lockup_seconds,
+            min_base_reserve: args.min_base_reserve,
+            allowlist_root: args.allowlist_root,
+            max_vta_balance: args.max_vta_balance,
+            topup_order: args.topup_order,
+            round_in_favor_of_trader: args.round_in_favor_of_trader,
+            fee_holiday_until: args.fee_holiday_until,
+            require_creator_cosign_on_burn: args.require_creator_cosign_on_burn,
+            min_event_interval_seconds: args.min_event_interval_seconds,
+        },
     )?);
+
+    let platform_registry = &mut ctx.accounts.platform_registry;
+    platform_registry.initialize_if_needed(ctx.bumps.platform_registry, platform_config.key());
+    let registry_index = platform_registry.total_pools_created;
+    let pool_count = platform_registry.register_pool()?;
+    ctx.accounts.pool_entry.set_inner(PlatformPoolEntry {
+        bump: ctx.bumps.pool_entry,
+        platform_config: platform_config.key(),
+        index: registry_index,
+        pool: ctx.accounts.pool.key(),
+    });
+    ctx.accounts.pool.registry_index = Some(registry_index);
+
+    emit!(PoolCreated {
+        pool: ctx.accounts.pool.key(),
+        creator: ctx.accounts.payer.key(),
+        platform_config: platform_config.key(),
+        quote_mint: ctx.accounts.quote_mint.key(),
+        pool_count,
+        registry_index,
+    });
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::errors::CbmmError;
+    use crate::state::{CbmmPool, PlatformConfig, MIN_VIRTUAL_RESERVE};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config = runner.create_platform_config_mock(
+            &admin,
+            quote_mint,
+            5,
+            5,
+            2,
+            1,
+            200,
+            600,
+            200,
+            None,
+        );
+        (runner, admin, platform_config, quote_mint)
+    }
+
+    #[test_case(0, false; "zero")]
+    #[test_case(MIN_VIRTUAL_RESERVE - 1, false; "below_min")]
+    #[test_case(MIN_VIRTUAL_RESERVE, true; "at_min")]
+    fn test_create_pool_min_virtual_reserve(quote_virtual_reserve: u64, success: bool) {
+        let (mut runner, _, platform_config, quote_mint) = setup_test();
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+
+        let result = runner.create_pool(&creator, platform_config, quote_mint, quote_virtual_reserve);
+        assert_eq!(result.is_ok(), success);
+    }
+
+    #[test_case(false, false, true; "no_freeze_authority_not_required")]
+    #[test_case(true, false, true; "freeze_authority_not_required")]
+    #[test_case(false, true, true; "no_freeze_authority_required")]
+    #[test_case(true, true, false; "freeze_authority_required")]
+    fn test_create_pool_freeze_authority(
+        has_freeze_authority: bool,
+        require_no_freeze_authority: bool,
+        success: bool,
+    ) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = if has_freeze_authority {
+            runner.create_mint_with_freeze_authority(&admin, 9, &admin.pubkey())
+        } else {
+            runner.create_mint(&admin, 9)
+        };
+        let platform_config = runner.create_platform_config_mock(
+            &admin,
+            quote_mint,
+            5,
+            5,
+            2,
+            1,
+            200,
+            600,
+            200,
+            None,
+        );
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.require_no_freeze_authority = require_no_freeze_authority;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let result = runner.create_pool(&creator, platform_config, quote_mint, 1_000_000);
+        assert_eq!(result.is_ok(), success);
+    }
+
+    #[test]
+    fn test_create_pool_registers_in_platform_registry() {
+        let (mut runner, _, platform_config, quote_mint) = setup_test();
+        assert!(runner.get_platform_registry(platform_config).is_none());
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 1);
+        assert_eq!(registry.total_pools_created, 1);
+        assert_eq!(registry.platform_config.to_bytes(), platform_config.to_bytes());
+
+        let another_creator = Keypair::new();
+        runner.airdrop(&another_creator.pubkey(), 10_000_000_000);
+        runner
+            .create_pool(&another_creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 2);
+        assert_eq!(registry.total_pools_created, 2);
+
+        // Each pool is listed under the index it was created at
+        let another_pool = runner
+            .get_pool_entry(platform_config, 1)
+            .unwrap()
+            .pool;
+        let pool_account = runner
+            .svm
+            .get_account(&Pubkey::from(another_pool.to_bytes()))
+            .unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.registry_index, Some(1));
+        assert_eq!(pool_data.creator.to_bytes(), another_creator.pubkey().to_bytes());
+        assert!(runner.get_pool_entry(platform_config, 0).is_some());
+    }
+
+    #[test]
+    fn test_close_pool_decrements_platform_registry() {
+        let (mut runner, _, platform_config, quote_mint) = setup_test();
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        let pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        let another_creator = Keypair::new();
+        runner.airdrop(&another_creator.pubkey(), 10_000_000_000);
+        runner
+            .create_pool(&another_creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        runner
+            .close_pool(&creator, creator_ata, quote_mint, pool.pool)
+            .unwrap();
+        assert!(runner.svm.get_account(&pool.pool).is_none());
+
+        // Open pool count goes down, the monotonic counter does not
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 1);
+        assert_eq!(registry.total_pools_created, 2);
+        assert!(runner.get_pool_entry(platform_config, 0).is_none());
+        assert!(runner.get_pool_entry(platform_config, 1).is_some());
+    }
+
+    #[test]
+    fn test_close_pool_leaves_non_creator_balances() {
+        let (mut runner, admin, platform_config, quote_mint) = setup_test();
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        let pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        // 300 A of residual reserve next to buyback fees and locked liquidity
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.quote_reserve = 300;
+        pool_data.buyback_fees_balance = 5_000;
+        pool_data.locked_quote_reserve = 2_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        runner.mint_tokens(&admin, pool.pool, quote_mint, 7_300);
+
+        runner
+            .close_pool(&creator, creator_ata, quote_mint, pool.pool)
+            .unwrap();
+        assert!(runner.svm.get_account(&pool.pool).is_none());
+
+        let token_balance = |runner: &TestRunner, ata: Pubkey| {
+            let account = runner.svm.get_account(&ata).unwrap();
+            anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+                .unwrap()
+                .amount
+        };
+        assert_eq!(token_balance(&runner, creator_ata), 300);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(quote_mint.to_bytes()),
+        );
+        assert_eq!(token_balance(&runner, Pubkey::from(pool_ata.to_bytes())), 7_000);
+    }
+
+    #[test]
+    fn test_close_pool_with_unclaimed_platform_fees_fails() {
+        let (mut runner, admin, platform_config, quote_mint) = setup_test();
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        let pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.platform_fees_balance = 1_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        runner.mint_tokens(&admin, pool.pool, quote_mint, 1_000);
+
+        let err = runner
+            .close_pool(&creator, creator_ata, quote_mint, pool.pool)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::PoolNotClosable)
+        )));
+    }
+
+    #[test]
+    fn test_close_legacy_pool_without_registry_entry() {
+        let (mut runner, _, platform_config, quote_mint) = setup_test();
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        let pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        // Pools created before registry entries read `registry_index` as None
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.registry_index = None;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner
+            .close_pool(&creator, creator_ata, quote_mint, pool.pool)
+            .unwrap();
+        assert!(runner.svm.get_account(&pool.pool).is_none());
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 0);
+    }
+
+    #[test]
+    fn test_close_pool_with_circulating_supply_fails() {
+        let (mut runner, _, platform_config, quote_mint) = setup_test();
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        let pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        // Simulate virtual tokens held by users
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.base_reserve -= 1_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        let result = runner.close_pool(&creator, creator_ata, quote_mint, pool.pool);
+        assert!(result.is_err());
+
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 1);
+    }
+
+    #[test]
+    fn test_create_pools_for_two_quote_mints_under_one_creator() {
+        let (mut runner, admin, platform_config, quote_mint) = setup_test();
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let first_pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        // Switch the platform to another quote mint
+        let other_quote_mint = runner.create_mint(&admin, 6);
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.quote_mint =
+            anchor_lang::prelude::Pubkey::new_from_array(other_quote_mint.to_bytes());
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let second_pool = runner
+            .create_pool(&creator, platform_config, other_quote_mint, 1_000_000)
+            .unwrap();
+        assert_ne!(first_pool.pool, second_pool.pool);
+
+        for (pool, mint) in [(first_pool.pool, quote_mint), (second_pool.pool, other_quote_mint)] {
+            let pool_account = runner.svm.get_account(&pool).unwrap();
+            let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+            assert_eq!(pool_data.quote_mint.to_bytes(), mint.to_bytes());
+            assert_eq!(pool_data.creator.to_bytes(), creator.pubkey().to_bytes());
+        }
+    }
+
+    fn allow_quote_mint(runner: &mut TestRunner, platform_config: Pubkey, quote_mint: Pubkey) {
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data
+            .allowed_quote_mints
+            .push(anchor_lang::prelude::Pubkey::new_from_array(quote_mint.to_bytes()));
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+    }
+
+    #[test]
+    fn test_create_pools_for_allowed_quote_mints() {
+        let (mut runner, admin, platform_config, quote_mint) = setup_test();
+        let other_quote_mint = runner.create_mint(&admin, 6);
+        allow_quote_mint(&mut runner, platform_config, other_quote_mint);
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        for mint in [quote_mint, other_quote_mint] {
+            let pool = runner
+                .create_pool(&creator, platform_config, mint, 1_000_000)
+                .unwrap();
+            let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+            let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+            assert_eq!(pool_data.quote_mint.to_bytes(), mint.to_bytes());
+        }
+        let registry = runner.get_platform_registry(platform_config).unwrap();
+        assert_eq!(registry.pool_count, 2);
+    }
+
+    #[test]
+    fn test_create_pool_rejects_unlisted_quote_mint() {
+        let (mut runner, admin, platform_config, _) = setup_test();
+        let allowed_quote_mint = runner.create_mint(&admin, 6);
+        allow_quote_mint(&mut runner, platform_config, allowed_quote_mint);
+        let unlisted_quote_mint = runner.create_mint(&admin, 6);
+
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        assert!(runner
+            .create_pool(&creator, platform_config, unlisted_quote_mint, 1_000_000)
+            .is_err());
+    }
+}
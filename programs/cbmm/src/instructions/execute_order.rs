@@ -0,0 +1,337 @@
+use crate::errors::CbmmError;
+use crate::instructions::{execute_sell, SellVirtualTokenArgs};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteOrder<'info> {
+    /// Anyone can execute a triggered order
+    pub keeper: Signer<'info>,
+
+    /// CHECK: Checked against the order, receives the proceeds and the order rent
+    #[account(mut, address = order.owner @ CbmmError::InvalidOwner)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            ORDER_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            order.order_id.to_le_bytes().as_ref(),
+        ],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+
+    #[account(mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mutable so the trade can add to `lifetime_platform_fees`
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Sells the escrowed base amount at market once the pool price crosses the order trigger,
+/// paying the owner and closing the order.
+pub fn execute_order(ctx: Context<ExecuteOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let price_x64 = ctx.accounts.pool.spot_price_x64()?;
+    require!(order.is_triggered(price_x64), CbmmError::OrderNotTriggered);
+
+    // Hand the escrow back to the VTA so the regular sell path can consume it
+    ctx.accounts.virtual_token_account.add(order.base_amount)?;
+    let args = SellVirtualTokenArgs {
+        base_amount: order.base_amount,
+        min_quote_amount: order.min_quote_amount,
+        memo: None,
+    };
+    let event = execute_sell(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.virtual_token_account,
+        &mut ctx.accounts.platform_config,
+        &args,
+        ctx.accounts.owner.key(),
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    let pool_account_info = pool.to_account_info();
+    pool.transfer_out(
+        event.quote_output,
+        &pool_account_info,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.pool_ata,
+        &ctx.accounts.owner_ata,
+        &ctx.accounts.token_program,
+    )?;
+
+    if ctx.accounts.pool.take_trade_event_slot(Clock::get()?.unix_timestamp) {
+        emit_cpi!(event);
+    } else {
+        emit_cpi!(event.throttled());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::CbmmError;
+    use crate::state::{CbmmPool, OrderDirection, VirtualTokenAccount};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    struct Trader {
+        wallet: Keypair,
+        ata: Pubkey,
+        vta: Pubkey,
+    }
+
+    fn setup_test() -> (TestRunner, Trader, Trader, TestPool, Pubkey) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&payer, quote_mint, &pool.pool);
+
+        let trader = |runner: &mut TestRunner| {
+            let wallet = Keypair::new();
+            runner.airdrop(&wallet.pubkey(), 10_000_000_000);
+            let ata = runner.create_associated_token_account(&payer, quote_mint, &wallet.pubkey());
+            runner.mint_to(&payer, &quote_mint, ata, 10_000_000_000);
+            let vta = runner.create_virtual_token_account_mock(wallet.pubkey(), pool.pool, 0);
+            Trader { wallet, ata, vta }
+        };
+        let owner = trader(&mut runner);
+        let other = trader(&mut runner);
+
+        (runner, owner, other, pool, quote_mint)
+    }
+
+    fn buy(
+        runner: &mut TestRunner,
+        trader: &Trader,
+        pool: Pubkey,
+        quote_mint: Pubkey,
+        amount: u64,
+    ) {
+        runner
+            .buy_virtual_token(
+                &trader.wallet,
+                trader.ata,
+                quote_mint,
+                pool,
+                trader.vta,
+                amount,
+                0,
+            )
+            .unwrap();
+    }
+
+    fn pool_data(runner: &TestRunner, pool: Pubkey) -> CbmmPool {
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap()
+    }
+
+    fn vta_balance(runner: &TestRunner, vta: Pubkey) -> u64 {
+        let vta_account = runner.svm.get_account(&vta).unwrap();
+        VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice())
+            .unwrap()
+            .balance
+    }
+
+    fn token_balance(runner: &TestRunner, ata: Pubkey) -> u64 {
+        let account = runner.svm.get_account(&ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    #[test]
+    fn test_execute_take_profit_order() {
+        let (mut runner, owner, other, pool, quote_mint) = setup_test();
+        buy(&mut runner, &owner, pool.pool, quote_mint, 5000);
+        let base_amount = vta_balance(&runner, owner.vta);
+
+        let price_x64 = pool_data(&runner, pool.pool).spot_price_x64().unwrap();
+        let order = runner
+            .place_order(
+                &owner.wallet,
+                pool.pool,
+                0,
+                base_amount,
+                price_x64 * 2,
+                OrderDirection::TakeProfit,
+            )
+            .unwrap();
+
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        assert!(runner.execute_order(&keeper, order).is_err());
+
+        // Push the price well above the trigger
+        buy(&mut runner, &other, pool.pool, quote_mint, 2_000_000);
+        assert!(pool_data(&runner, pool.pool).spot_price_x64().unwrap() >= price_x64 * 2);
+
+        let balance_before = token_balance(&runner, owner.ata);
+        runner.svm.expire_blockhash();
+        runner.execute_order(&keeper, order).unwrap();
+
+        assert!(runner.get_order(&order).is_none());
+        assert_eq!(vta_balance(&runner, owner.vta), 0);
+        assert!(token_balance(&runner, owner.ata) > balance_before);
+    }
+
+    #[test]
+    fn test_execute_stop_loss_order() {
+        let (mut runner, owner, other, pool, quote_mint) = setup_test();
+        buy(&mut runner, &other, pool.pool, quote_mint, 1_000_000);
+        buy(&mut runner, &owner, pool.pool, quote_mint, 5000);
+        let base_amount = vta_balance(&runner, owner.vta);
+
+        let price_x64 = pool_data(&runner, pool.pool).spot_price_x64().unwrap();
+        let order = runner
+            .place_order(
+                &owner.wallet,
+                pool.pool,
+                0,
+                base_amount,
+                price_x64 / 2,
+                OrderDirection::StopLoss,
+            )
+            .unwrap();
+
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        assert!(runner.execute_order(&keeper, order).is_err());
+
+        // The other trader dumps their whole position
+        let other_balance = vta_balance(&runner, other.vta);
+        runner
+            .sell_virtual_token(
+                &other.wallet,
+                other.ata,
+                quote_mint,
+                pool.pool,
+                other.vta,
+                other_balance,
+                0,
+            )
+            .unwrap();
+        assert!(pool_data(&runner, pool.pool).spot_price_x64().unwrap() <= price_x64 / 2);
+
+        runner.svm.expire_blockhash();
+        runner.execute_order(&keeper, order).unwrap();
+        assert!(runner.get_order(&order).is_none());
+        assert_eq!(vta_balance(&runner, owner.vta), 0);
+    }
+
+    #[test]
+    fn test_execute_order_respects_min_quote_amount() {
+        let (mut runner, owner, other, pool, quote_mint) = setup_test();
+        buy(&mut runner, &other, pool.pool, quote_mint, 1_000_000);
+        buy(&mut runner, &owner, pool.pool, quote_mint, 5000);
+        let base_amount = vta_balance(&runner, owner.vta);
+
+        // Selling at half the entry price cannot return 4000 of the 5000 spent
+        let price_x64 = pool_data(&runner, pool.pool).spot_price_x64().unwrap();
+        let order = runner
+            .place_order_with_min_output(
+                &owner.wallet,
+                pool.pool,
+                0,
+                base_amount,
+                price_x64 / 2,
+                OrderDirection::StopLoss,
+                4000,
+            )
+            .unwrap();
+        assert_eq!(runner.get_order(&order).unwrap().min_quote_amount, 4000);
+
+        // The price gets pushed through the trigger right before the keeper executes
+        let other_balance = vta_balance(&runner, other.vta);
+        runner
+            .sell_virtual_token(
+                &other.wallet,
+                other.ata,
+                quote_mint,
+                pool.pool,
+                other.vta,
+                other_balance,
+                0,
+            )
+            .unwrap();
+        assert!(pool_data(&runner, pool.pool).spot_price_x64().unwrap() <= price_x64 / 2);
+
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        let err = runner.execute_order(&keeper, order).unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::SlippageExceeded)
+        )));
+        assert!(runner.get_order(&order).is_some());
+        assert_eq!(vta_balance(&runner, owner.vta), 0);
+    }
+}
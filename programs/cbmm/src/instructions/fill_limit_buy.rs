@@ -0,0 +1,265 @@
+use crate::errors::CbmmError;
+use crate::instructions::{execute_buy, BuyVirtualTokenArgs};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FillLimitBuyArgs {
+    /// Quote amount to spend from escrow. Capped at the remaining escrow.
+    pub quote_amount: u64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FillLimitBuy<'info> {
+    /// Anyone can fill a limit buy while the price allows it
+    pub keeper: Signer<'info>,
+
+    /// CHECK: Checked against the order, receives the rent once the order is fully filled
+    #[account(mut, address = order.owner @ CbmmError::InvalidOwner)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            LIMIT_BUY_ORDER_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            order.order_id.to_le_bytes().as_ref(),
+        ],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, LimitBuyOrder>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mutable so the trade can add to `lifetime_platform_fees`
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Spends up to `args.quote_amount` of the escrow on a buy for the order owner, as long as
+/// the spot price is at or below the limit and the fill's average price, fees included,
+/// stays within it too. A fully spent order is closed.
+pub fn fill_limit_buy(ctx: Context<FillLimitBuy>, args: FillLimitBuyArgs) -> Result<()> {
+    let order = &ctx.accounts.order;
+    let max_price_x64 = order.max_price_x64;
+    let price_x64 = ctx.accounts.pool.spot_price_x64()?;
+    require_gte!(max_price_x64, price_x64, CbmmError::OrderNotTriggered);
+
+    let fill_amount = args.quote_amount.min(order.quote_amount);
+    require_gt!(fill_amount, 0, CbmmError::AmountTooSmall);
+
+    let balance_before = ctx.accounts.pool_ata.amount;
+    order.transfer_out(
+        fill_amount,
+        &order.to_account_info(),
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.escrow_ata,
+        &ctx.accounts.pool_ata,
+        &ctx.accounts.token_program,
+    )?;
+    ctx.accounts.pool_ata.reload()?;
+    let quote_received = ctx
+        .accounts
+        .pool_ata
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(CbmmError::Underflow)?;
+
+    // Getting at least quote / max_price base keeps the average price within the limit
+    let base_amount_min = ((fill_amount as u128) << 64).div_ceil(max_price_x64.max(1));
+    let buy_args = BuyVirtualTokenArgs {
+        quote_amount: fill_amount,
+        base_amount_min: base_amount_min.min(u64::MAX as u128) as u64,
+        slippage_bps: None,
+        allowlist_proof: vec![],
+        memo: None,
+    };
+    let (event, _) = execute_buy(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.virtual_token_account,
+        &mut ctx.accounts.platform_config,
+        quote_received,
+        &buy_args,
+        ctx.accounts.owner.key(),
+    )?;
+
+    let order = &mut ctx.accounts.order;
+    order.quote_amount -= fill_amount;
+    if order.quote_amount == 0 {
+        order.close_escrow_ata(
+            &order.to_account_info(),
+            &ctx.accounts.escrow_ata,
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+        )?;
+        order.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    if ctx.accounts.pool.take_trade_event_slot(Clock::get()?.unix_timestamp) {
+        emit_cpi!(event);
+    } else {
+        emit_cpi!(event.throttled());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{CbmmPool, VirtualTokenAccount};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey, TestPool) {
+        let mut runner = TestRunner::new();
+        let owner = Keypair::new();
+        runner.airdrop(&owner.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&owner, 9);
+        let owner_ata = runner.create_associated_token_account(&owner, quote_mint, &owner.pubkey());
+        runner.mint_to(&owner, &quote_mint, owner_ata, 10_000_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&owner, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &owner,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&owner, quote_mint, &pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(owner.pubkey(), pool.pool, 0);
+        (runner, owner, owner_ata, virtual_token_account, pool)
+    }
+
+    fn spot_price_x64(runner: &TestRunner, pool: Pubkey) -> u128 {
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        CbmmPool::try_deserialize(&mut pool_account.data.as_slice())
+            .unwrap()
+            .spot_price_x64()
+            .unwrap()
+    }
+
+    fn vta_balance(runner: &TestRunner, vta: Pubkey) -> u64 {
+        let vta_account = runner.svm.get_account(&vta).unwrap();
+        VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice())
+            .unwrap()
+            .balance
+    }
+
+    fn token_balance(runner: &TestRunner, ata: Pubkey) -> u64 {
+        let account = runner.svm.get_account(&ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    #[test]
+    fn test_fill_limit_buy_partial_then_full() {
+        let (mut runner, owner, owner_ata, virtual_token_account, pool) = setup_test();
+        let max_price_x64 = spot_price_x64(&runner, pool.pool) * 2;
+        let order = runner
+            .place_limit_buy(&owner, owner_ata, pool.pool, 0, 10_000, max_price_x64)
+            .unwrap();
+
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        runner.fill_limit_buy(&keeper, order, 4000).unwrap();
+
+        let order_data = runner.get_limit_buy_order(&order).unwrap();
+        assert_eq!(order_data.quote_amount, 6000);
+        let first_fill_balance = vta_balance(&runner, virtual_token_account);
+        assert!(first_fill_balance > 0);
+
+        // Asking for more than is left spends the rest and closes the order
+        runner.fill_limit_buy(&keeper, order, 1_000_000).unwrap();
+        assert!(runner.get_limit_buy_order(&order).is_none());
+        assert!(vta_balance(&runner, virtual_token_account) > first_fill_balance);
+        assert_eq!(token_balance(&runner, owner_ata), 10_000_000_000 - 10_000);
+    }
+
+    #[test]
+    fn test_fill_limit_buy_price_above_limit() {
+        let (mut runner, owner, owner_ata, virtual_token_account, pool) = setup_test();
+        let max_price_x64 = spot_price_x64(&runner, pool.pool) / 2;
+        let order = runner
+            .place_limit_buy(&owner, owner_ata, pool.pool, 0, 10_000, max_price_x64)
+            .unwrap();
+
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        assert!(runner.fill_limit_buy(&keeper, order, 10_000).is_err());
+        assert_eq!(vta_balance(&runner, virtual_token_account), 0);
+        assert_eq!(
+            runner.get_limit_buy_order(&order).unwrap().quote_amount,
+            10_000
+        );
+    }
+
+    #[test]
+    fn test_fill_limit_buy_average_price_above_limit() {
+        let (mut runner, owner, owner_ata, _, pool) = setup_test();
+        // Spot is within the limit, but fees alone push the average fill price above it
+        let max_price_x64 = spot_price_x64(&runner, pool.pool);
+        let order = runner
+            .place_limit_buy(&owner, owner_ata, pool.pool, 0, 10_000, max_price_x64)
+            .unwrap();
+
+        let keeper = Keypair::new();
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        assert!(runner.fill_limit_buy(&keeper, order, 10_000).is_err());
+    }
+}
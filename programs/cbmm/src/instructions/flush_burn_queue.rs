@@ -0,0 +1,299 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event]
+pub struct FlushBurnQueueEvent {
+    pub burn_amount: u64,
+    pub topup_paid: u64,
+    pub keeper_reward: u64,
+
+    pub new_b_reserve: u64,
+    pub new_a_reserve: u64,
+    pub new_buyback_fees_balance: u64,
+    pub quote_outstanding_topup: u64,
+
+    pub keeper: Pubkey,
+    pub pool: Pubkey,
+}
+
+/// Emitted next to the burn or crank event whenever executed B came partly out of the queue,
+/// so indexers can tell delayed burns from fresh ones
+#[event]
+pub struct QueueFlushed {
+    pub pool: Pubkey,
+    pub executed_amount: u64,
+    /// Shares still waiting in the queue, in x10k basis points
+    pub remaining_shares: u64,
+}
+
+pub(crate) fn emit_queue_flushed_event(pool: &Account<CbmmPool>, burn_result: &BurnResult) {
+    if !burn_result.flushed_queue {
+        return;
+    }
+    emit!(QueueFlushed {
+        pool: pool.key(),
+        executed_amount: burn_result.burn_amount,
+        remaining_shares: pool.burn_limiter.pending_queue_shares_bp_x10k,
+    });
+}
+
+/// Emitted by every trade, burn and crank path whose topup moved buyback fees into the reserve
+#[event]
+pub struct TopupEvent {
+    pub pool: Pubkey,
+    /// Buyback fees moved into the real reserve
+    pub real_topup_amount: u64,
+    pub new_quote_reserve: u64,
+    pub new_virtual_reserve: u64,
+    pub remaining_buyback_fees: u64,
+    pub quote_outstanding_topup: u64,
+}
+
+/// Emits a `TopupEvent` for a fee-funded topup of the pool, if it moved anything.
+pub(crate) fn emit_topup_event(pool: &Account<CbmmPool>, real_topup_amount: u64) {
+    if real_topup_amount == 0 {
+        return;
+    }
+    emit!(TopupEvent {
+        pool: pool.key(),
+        real_topup_amount,
+        new_quote_reserve: pool.quote_reserve,
+        new_virtual_reserve: pool.quote_virtual_reserve,
+        remaining_buyback_fees: pool.buyback_fees_balance,
+        quote_outstanding_topup: pool.quote_outstanding_topup,
+    });
+}
+
+#[derive(Accounts)]
+pub struct FlushBurnQueue<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = keeper,
+        associated_token::token_program = token_program
+    )]
+    pub keeper_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless crank: executes whatever the rate limiter lets out of the burn queue and
+/// applies any outstanding topup. If either did something, the keeper is paid
+/// `keeper_reward_bp` of the remaining buyback fees.
+pub fn flush_burn_queue(ctx: Context<FlushBurnQueue>) -> Result<()> {
+    let platform_config = &ctx.accounts.platform_config;
+    require!(!platform_config.paused, CbmmError::PlatformPaused);
+    let pool = &mut ctx.accounts.pool;
+
+    // A zero-sized request enqueues nothing and only drains what is already pending
+    let burn_result = pool.burn(&platform_config.burn_rate_config, 0)?;
+    emit_queue_flushed_event(pool, &burn_result);
+    let topup_paid = pool.topup()?;
+    emit_topup_event(pool, topup_paid);
+
+    let keeper_reward = if burn_result.burn_amount > 0 || topup_paid > 0 {
+        pool.take_keeper_reward(platform_config.keeper_reward_bp)?
+    } else {
+        0
+    };
+    if keeper_reward > 0 {
+        let pool_account_info = pool.to_account_info();
+        pool.transfer_out(
+            keeper_reward,
+            &pool_account_info,
+            &ctx.accounts.quote_mint,
+            &ctx.accounts.pool_ata,
+            &ctx.accounts.keeper_ata,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    emit!(FlushBurnQueueEvent {
+        burn_amount: burn_result.burn_amount,
+        topup_paid,
+        keeper_reward,
+        new_b_reserve: pool.base_reserve,
+        new_a_reserve: pool.quote_reserve,
+        new_buyback_fees_balance: pool.buyback_fees_balance,
+        quote_outstanding_topup: pool.quote_outstanding_topup,
+        keeper: ctx.accounts.keeper.key(),
+        pool: pool.key(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::CbmmError;
+    use crate::instructions::QueueFlushed;
+    use crate::state::{CbmmPool, PlatformConfig};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const BUYBACK_FEES_BALANCE: u64 = 100_000;
+    const KEEPER_REWARD_BP: u16 = 50;
+
+    fn setup_test(
+        pending_queue_shares_bp_x10k: u64,
+    ) -> (TestRunner, Keypair, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        let keeper = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let keeper_ata =
+            runner.create_associated_token_account(&keeper, quote_mint, &keeper.pubkey());
+
+        let platform_config = runner.create_platform_config_mock(
+            &payer, quote_mint, 5, 5, 1_000, 20_000, 200, 600, 200, None,
+        );
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.keeper_reward_bp = KEEPER_REWARD_BP;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            500_000,
+            1_000_000,
+            1_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            BUYBACK_FEES_BALANCE,
+            0,
+        );
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.burn_limiter.pending_queue_shares_bp_x10k = pending_queue_shares_bp_x10k;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner.create_associated_token_account(&payer, quote_mint, &pool.pool);
+        runner.mint_tokens(&payer, pool.pool, quote_mint, BUYBACK_FEES_BALANCE);
+
+        (runner, keeper, keeper_ata, pool.pool, quote_mint)
+    }
+
+    fn token_balance(runner: &TestRunner, ata: &Pubkey) -> u64 {
+        let account = runner.svm.get_account(ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    #[test]
+    fn test_flush_burn_queue_pays_keeper() {
+        // 1% of the reserve is waiting in the queue
+        let (mut runner, keeper, keeper_ata, pool, quote_mint) = setup_test(1_000_000);
+
+        runner
+            .flush_burn_queue(&keeper, keeper_ata, quote_mint, pool)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.burn_limiter.pending_queue_shares_bp_x10k, 0);
+        assert_eq!(pool_data.base_total_supply, 990_000);
+
+        let expected_reward = BUYBACK_FEES_BALANCE * KEEPER_REWARD_BP as u64 / 10_000;
+        assert_eq!(token_balance(&runner, &keeper_ata), expected_reward);
+        assert_eq!(
+            pool_data.buyback_fees_balance,
+            BUYBACK_FEES_BALANCE - expected_reward
+        );
+    }
+
+    #[test]
+    fn test_flush_burn_queue_noop_pays_nothing() {
+        let (mut runner, keeper, keeper_ata, pool, quote_mint) = setup_test(0);
+
+        runner
+            .flush_burn_queue(&keeper, keeper_ata, quote_mint, pool)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_total_supply, 1_000_000);
+        assert_eq!(pool_data.buyback_fees_balance, BUYBACK_FEES_BALANCE);
+        assert_eq!(token_balance(&runner, &keeper_ata), 0);
+        assert!(runner.last_events::<QueueFlushed>().is_empty());
+    }
+
+    #[test]
+    fn test_flush_burn_queue_emits_queue_flushed() {
+        let (mut runner, keeper, keeper_ata, pool, quote_mint) = setup_test(1_000_000);
+
+        runner
+            .flush_burn_queue(&keeper, keeper_ata, quote_mint, pool)
+            .unwrap();
+
+        let events = runner.last_events::<QueueFlushed>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].executed_amount, 10_000);
+        assert_eq!(events[0].remaining_shares, 0);
+    }
+
+    #[test]
+    fn test_flush_burn_queue_paused_platform() {
+        let (mut runner, keeper, keeper_ata, pool, quote_mint) = setup_test(1_000_000);
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config = Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.paused = true;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let err = runner
+            .flush_burn_queue(&keeper, keeper_ata, quote_mint, pool)
+            .unwrap_err();
+        assert!(err
+            .message
+            .contains(&format!("Custom({})", u32::from(CbmmError::PlatformPaused))));
+
+        // The queue waits for the platform to resume
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.burn_limiter.pending_queue_shares_bp_x10k, 1_000_000);
+        assert_eq!(token_balance(&runner, &keeper_ata), 0);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Burn allowance as the next burn would see it, with the lazy daily reset already applied
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BurnAllowanceStatus {
+    /// Burns counted in the current day, 0 if the stored count is from an earlier day
+    pub burns_today_effective: u16,
+    /// The next burn restarts the daily count
+    pub would_reset: bool,
+    /// Burns left in the current day, queued burns included (None = unlimited tier). 0 once the
+    /// burn tiers were updated, as the allowance can no longer burn.
+    pub remaining_today: Option<u16>,
+    /// See `check_allowance_closable`
+    pub closable: bool,
+}
+
+#[derive(Accounts)]
+pub struct GetBurnAllowanceStatus<'info> {
+    #[account(
+        seeds = [
+            USER_BURN_ALLOWANCE_SEED,
+            user_burn_allowance.user.as_ref(),
+            platform_config.key().as_ref(),
+            &[user_burn_allowance.burn_tier_index],
+            user_burn_allowance.burn_tier_update_timestamp.to_le_bytes().as_ref(),
+        ],
+        bump = user_burn_allowance.bump,
+    )]
+    pub user_burn_allowance: Account<'info, UserBurnAllowance>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Read-only `BurnAllowanceStatus` of the allowance at the current time.
+/// The result is returned via return data.
+pub fn get_burn_allowance_status(
+    ctx: Context<GetBurnAllowanceStatus>,
+) -> Result<BurnAllowanceStatus> {
+    let now = Clock::get()?.unix_timestamp;
+    let platform_config = &ctx.accounts.platform_config;
+    let allowance = &ctx.accounts.user_burn_allowance;
+    let reset_interval_seconds = platform_config.burn_reset_interval_seconds;
+
+    let burns_today_effective = allowance.effective_burns_today(now, reset_interval_seconds);
+    let queued_today_effective = allowance.effective_queued_today(now, reset_interval_seconds);
+    let stale = platform_config.burn_tiers_updated_at > allowance.burn_tier_update_timestamp;
+    let remaining_today = match platform_config
+        .burn_tiers
+        .get(allowance.burn_tier_index as usize)
+    {
+        Some(_) if stale => Some(0),
+        Some(tier) if tier.max_daily_burns == 0 => None,
+        Some(tier) => Some(
+            tier.max_daily_burns
+                .saturating_sub(burns_today_effective)
+                .saturating_sub(queued_today_effective),
+        ),
+        None => Some(0),
+    };
+
+    Ok(BurnAllowanceStatus {
+        burns_today_effective,
+        would_reset: allowance.should_reset(now, reset_interval_seconds),
+        remaining_today,
+        closable: allowance.is_closable(
+            platform_config.burn_tiers_updated_at,
+            reset_interval_seconds,
+            now,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::BurnAllowanceStatus;
+    use crate::state::PlatformConfig;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    const NOW: i64 = 1682899200;
+    const DAY: i64 = 86400;
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        (runner, payer, platform_config)
+    }
+
+    // The allowance was created exactly two days before NOW, so days start at NOW
+    #[test_case(NOW - 1, 3, false, Some(2), false; "same_day")]
+    // Closing waits for a full interval since the last burn, not just the day boundary
+    #[test_case(NOW, 0, true, Some(5), false; "day_boundary")]
+    #[test_case(NOW + DAY - 1, 0, true, Some(5), true; "end_of_next_day")]
+    fn test_get_burn_allowance_status_across_day_boundary(
+        now: i64,
+        burns_today_effective: u16,
+        would_reset: bool,
+        remaining_today: Option<u16>,
+        closable: bool,
+    ) {
+        let (mut runner, payer, platform_config) = setup_test();
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            payer.pubkey(),
+            payer.pubkey(),
+            platform_config,
+            3,
+            NOW - 3600,
+            false,
+            NOW - 2 * DAY,
+        );
+        runner.set_system_clock(now);
+
+        let status = runner
+            .get_burn_allowance_status(&payer, user_burn_allowance, platform_config)
+            .unwrap();
+        assert_eq!(
+            status,
+            BurnAllowanceStatus {
+                burns_today_effective,
+                would_reset,
+                remaining_today,
+                closable,
+            }
+        );
+        // The stored count itself only resets on the next burn
+        let allowance = runner.get_user_burn_allowance(&user_burn_allowance).unwrap();
+        assert_eq!(allowance.burns_today, 3);
+    }
+
+    #[test]
+    fn test_get_burn_allowance_status_stale_tier() {
+        let (mut runner, payer, platform_config) = setup_test();
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            payer.pubkey(),
+            payer.pubkey(),
+            platform_config,
+            1,
+            NOW - 3600,
+            false,
+            NOW - 2 * DAY,
+        );
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.burn_tiers_updated_at = NOW - 60;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+        runner.set_system_clock(NOW);
+
+        let status = runner
+            .get_burn_allowance_status(&payer, user_burn_allowance, platform_config)
+            .unwrap();
+        assert_eq!(status.remaining_today, Some(0));
+        assert!(status.closable);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetEffectivePrice<'info> {
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Read-only marginal price of buying one B, creator, buyback and platform fees included, as
+/// Q64.64. Unlike the spot price `(A + V) / B` this is what a small buy actually pays.
+/// The result is returned via return data.
+pub fn get_effective_buy_price(ctx: Context<GetEffectivePrice>) -> Result<u128> {
+    ctx.accounts.pool.effective_buy_price_x64()
+}
+
+/// Read-only marginal price received for selling one B, fees included, as Q64.64.
+/// The result is returned via return data.
+pub fn get_effective_sell_price(ctx: Context<GetEffectivePrice>) -> Result<u128> {
+    ctx.accounts.pool.effective_sell_price_x64()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::CbmmPool;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        (runner, payer, pool)
+    }
+
+    #[test]
+    fn test_get_effective_prices_bracket_spot_price() {
+        let (runner, payer, pool) = setup_test();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let spot_price_x64 = pool_data.spot_price_x64().unwrap();
+
+        let buy_price_x64 = runner.get_effective_buy_price(&payer, pool.pool).unwrap();
+        let sell_price_x64 = runner.get_effective_sell_price(&payer, pool.pool).unwrap();
+
+        assert!(buy_price_x64 > spot_price_x64);
+        assert!(spot_price_x64 > sell_price_x64);
+        // 10% fees in total
+        assert_eq!(buy_price_x64, (spot_price_x64 * 10).div_ceil(9));
+        assert_eq!(sell_price_x64, spot_price_x64 * 9 / 10);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetMarketCap<'info> {
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Read-only fully diluted market cap of the pool in A, see `CbmmPool::market_cap_quote`.
+/// The result is returned via return data.
+pub fn get_market_cap(ctx: Context<GetMarketCap>) -> Result<u128> {
+    ctx.accounts.pool.market_cap_quote()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_get_market_cap_matches_manual_computation() {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        // 1.5M of the 2M supply left in the reserve after buys
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            300_000,
+            1_000_000,
+            1_500_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+
+        let market_cap = runner.get_market_cap(&payer, pool.pool).unwrap();
+        let expected = (pool_data.quote_reserve as u128 + pool_data.quote_virtual_reserve as u128)
+            * pool_data.base_total_supply as u128
+            / pool_data.base_reserve as u128;
+        assert_eq!(market_cap, expected);
+        // (300k + 1M) * 2M / 1.5M
+        assert_eq!(market_cap, 1_733_333);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Snapshot of a pool for clients, kept stable across account layout changes. New fields are
+/// only ever appended.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct PoolStateView {
+    /// Spot price of B in A as Q64.64, 0 once no B is left in the pool
+    pub price_x64: u128,
+    /// See `CbmmPool::market_cap_quote`
+    pub market_cap: u128,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub virtual_reserve: u64,
+    pub creator_fees: u64,
+    pub buyback_fees: u64,
+    pub platform_fees: u64,
+    /// Whether the pool's platform has trading paused
+    pub paused: bool,
+    pub graduated: bool,
+}
+
+#[derive(Accounts)]
+pub struct GetPoolState<'info> {
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(address = pool.platform_config)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Read-only `PoolStateView` of the pool. The result is returned via return data.
+pub fn get_pool_state(ctx: Context<GetPoolState>) -> Result<PoolStateView> {
+    let pool = &ctx.accounts.pool;
+    let price_x64 = if pool.base_reserve == 0 {
+        0
+    } else {
+        pool.spot_price_x64()?
+    };
+    Ok(PoolStateView {
+        price_x64,
+        market_cap: pool.market_cap_quote()?,
+        base_reserve: pool.base_reserve,
+        quote_reserve: pool.quote_reserve,
+        virtual_reserve: pool.quote_virtual_reserve,
+        creator_fees: pool.creator_fees_balance,
+        buyback_fees: pool.buyback_fees_balance,
+        platform_fees: pool.platform_fees_balance,
+        paused: ctx.accounts.platform_config.paused,
+        graduated: pool.graduated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[test]
+    fn test_get_pool_state_matches_raw_state() {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            300_000,
+            1_000_000,
+            1_500_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            1_000,
+            2_000,
+            0,
+        );
+        runner
+            .set_platform_paused(&payer, platform_config, true)
+            .unwrap();
+
+        let view = runner.get_pool_state(&payer, pool.pool).unwrap();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(view.price_x64, pool_data.spot_price_x64().unwrap());
+        assert_eq!(view.market_cap, 1_733_333);
+        assert_eq!(view.base_reserve, pool_data.base_reserve);
+        assert_eq!(view.quote_reserve, pool_data.quote_reserve);
+        assert_eq!(view.virtual_reserve, pool_data.quote_virtual_reserve);
+        assert_eq!(view.creator_fees, pool_data.creator_fees_balance);
+        assert_eq!(view.buyback_fees, pool_data.buyback_fees_balance);
+        assert_eq!(view.platform_fees, pool_data.platform_fees_balance);
+        assert!(view.paused);
+        assert!(!view.graduated);
+    }
+}
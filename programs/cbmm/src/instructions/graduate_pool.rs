@@ -0,0 +1,178 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenInterface};
+
+#[event]
+pub struct PoolGraduated {
+    pub pool: Pubkey,
+    pub base_mint: Pubkey,
+
+    /// B tokens held by users, claimable 1:1 as real tokens
+    pub circulating_supply: u64,
+}
+
+#[derive(Accounts)]
+pub struct GraduatePool<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = !pool.graduated @ CbmmError::AlreadyGraduated,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [GRADUATED_MINT_SEED, pool.key().as_ref()],
+        bump,
+        mint::decimals = pool.base_mint_decimals,
+        mint::authority = pool,
+        mint::token_program = token_program,
+    )]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Ends trading on the curve and provisions the real B mint, with the pool as mint authority.
+pub fn graduate_pool(ctx: Context<GraduatePool>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.graduated = true;
+    pool.base_mint = ctx.accounts.base_mint.key();
+
+    emit!(PoolGraduated {
+        pool: pool.key(),
+        base_mint: pool.base_mint,
+        circulating_supply: pool.base_total_supply - pool.base_reserve,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::PoolGraduated;
+    use crate::state::CbmmPool;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let admin_ata = runner.create_associated_token_account(&admin, quote_mint, &admin.pubkey());
+        runner.mint_to(&admin, &quote_mint, admin_ata, 10_000_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&admin, quote_mint, &pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(admin.pubkey(), pool.pool, 0);
+        (
+            runner,
+            admin,
+            pool,
+            quote_mint,
+            admin_ata,
+            virtual_token_account,
+        )
+    }
+
+    #[test]
+    fn test_graduate_pool_freezes_trading() {
+        let (mut runner, admin, pool, quote_mint, admin_ata, virtual_token_account) = setup_test();
+        runner
+            .buy_virtual_token(
+                &admin,
+                admin_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .unwrap();
+
+        let base_mint = runner.graduate_pool(&admin, pool.pool).unwrap();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert!(pool_data.graduated);
+        assert_eq!(pool_data.base_mint.to_bytes(), base_mint.to_bytes());
+        let events = runner.last_events::<PoolGraduated>();
+        assert_eq!(
+            events[0].circulating_supply,
+            pool_data.base_total_supply - pool_data.base_reserve
+        );
+
+        assert!(runner
+            .buy_virtual_token(
+                &admin,
+                admin_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                6000,
+                0
+            )
+            .is_err());
+        assert!(runner
+            .sell_virtual_token(
+                &admin,
+                admin_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1,
+                0
+            )
+            .is_err());
+
+        // Graduating twice is rejected
+        runner.svm.expire_blockhash();
+        assert!(runner.graduate_pool(&admin, pool.pool).is_err());
+    }
+
+    #[test]
+    fn test_graduate_pool_wrong_admin() {
+        let (mut runner, _, pool, _, _, _) = setup_test();
+        let other = Keypair::new();
+        runner.airdrop(&other.pubkey(), 10_000_000_000);
+        assert!(runner.graduate_pool(&other, pool.pool).is_err());
+    }
+}
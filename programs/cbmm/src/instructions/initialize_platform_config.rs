@@ -7,6 +7,12 @@ pub struct InitializePlatformConfigArgs {
     pub creator_fee_bp: u16,
     pub topup_fee_bp: u16,
     pub platform_fee_bp: u16,
+    /// Reward for keepers cranking `flush_burn_queue`, in bp of the buyback fees balance
+    pub keeper_reward_bp: u16,
+    /// Share of the platform fee paid to referrers, in bp of the buy amount
+    pub referral_fee_bp: u16,
+    /// Reject pool creation for quote mints with a freeze authority
+    pub require_no_freeze_authority: bool,
 
     /// Optional global burn authority required to sign all burns on this platform
     pub burn_authority: Option<Pubkey>,
@@ -14,6 +20,8 @@ pub struct InitializePlatformConfigArgs {
     pub burn_limit_bp_x100: u64,
     pub burn_min_burn_bp_x100: u64,
     pub burn_decay_rate_per_sec_bp_x100: u64,
+    /// Interval after which user burn allowances reset, in seconds
+    pub burn_reset_interval_seconds: i64,
     pub burn_tiers: Vec<BurnTier>,
 }
 
@@ -49,9 +57,13 @@ pub fn initialize_platform_config(
             args.creator_fee_bp,
             args.topup_fee_bp,
             args.platform_fee_bp,
+            args.keeper_reward_bp,
+            args.referral_fee_bp,
+            args.require_no_freeze_authority,
             args.burn_limit_bp_x100,
             args.burn_min_burn_bp_x100,
             args.burn_decay_rate_per_sec_bp_x100,
+            args.burn_reset_interval_seconds,
         )?);
     Ok(())
 }
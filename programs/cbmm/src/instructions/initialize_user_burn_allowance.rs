@@ -41,39 +41,52 @@ pub struct InitializeUserBurnAllowance<'info> {
         seeds = [
             CBMM_POOL_SEED,
             pool.pool_index.to_le_bytes().as_ref(),
-            pool.creator.as_ref(),
+            pool.seed_creator.as_ref(),
             platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
         ],
         bump = pool.bump,
     )]
     pub pool: Option<Account<'info, CbmmPool>>,
 }
 
-pub fn initialize_user_burn_allowance(
-    ctx: Context<InitializeUserBurnAllowance>,
-    args: InitializeUserBurnAllowanceArgs,
+/// Checks that `owner` may hold an allowance for the given tier
+pub(crate) fn check_burn_tier_role(
+    platform_config: &PlatformConfig,
+    burn_tier_index: u8,
+    owner: Pubkey,
+    pool: Option<&CbmmPool>,
 ) -> Result<()> {
     require_gt!(
-        ctx.accounts.platform_config.burn_tiers.len() as u8,
-        args.burn_tier_index,
+        platform_config.burn_tiers.len() as u8,
+        burn_tier_index,
         CbmmError::InvalidBurnTierIndex
     );
 
-    let burn_tier = &ctx.accounts.platform_config.burn_tiers[args.burn_tier_index as usize];
+    let burn_tier = &platform_config.burn_tiers[burn_tier_index as usize];
     match burn_tier.role {
         BurnRole::PoolOwner => {
-            require!(
-                ctx.accounts.pool.is_some(),
-                CbmmError::PoolCreatorBurnTierRequiresPool
-            );
-            let pool = &ctx.accounts.pool.as_ref().unwrap();
-            require_keys_eq!(pool.creator, ctx.accounts.owner.key());
+            let pool = pool.ok_or(CbmmError::PoolCreatorBurnTierRequiresPool)?;
+            require_keys_eq!(pool.creator, owner);
         }
         BurnRole::SpecificPubkey(pubkey) => {
-            require_keys_eq!(pubkey, ctx.accounts.owner.key());
+            require_keys_eq!(pubkey, owner);
         }
         BurnRole::Anyone => {}
     }
+    Ok(())
+}
+
+pub fn initialize_user_burn_allowance(
+    ctx: Context<InitializeUserBurnAllowance>,
+    args: InitializeUserBurnAllowanceArgs,
+) -> Result<()> {
+    check_burn_tier_role(
+        &ctx.accounts.platform_config,
+        args.burn_tier_index,
+        ctx.accounts.owner.key(),
+        ctx.accounts.pool.as_deref(),
+    )?;
 
     ctx.accounts
         .user_burn_allowance
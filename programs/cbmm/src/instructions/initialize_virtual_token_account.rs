@@ -33,3 +33,82 @@ pub fn initialize_virtual_token_account(ctx: Context<InitializeVirtualTokenAccou
         ));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::pda;
+    use crate::state::VirtualTokenAccount;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool) {
+        let mut runner = TestRunner::new();
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&creator, 9);
+        let platform_config = runner
+            .create_platform_config_mock(&creator, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        (runner, creator, pool)
+    }
+
+    #[test]
+    fn test_initialize_virtual_token_account_canonical_regardless_of_payer() {
+        let (mut runner, creator, pool) = setup_test();
+        let other_payer = Keypair::new();
+        runner.airdrop(&other_payer.pubkey(), 1_000_000_000);
+
+        // Whoever pays, the account lives at the address derived from pool and owner alone
+        for payer in [&creator, &other_payer] {
+            let owner = Pubkey::new_unique();
+            let virtual_token_account = runner
+                .initialize_virtual_token_account(payer, owner, pool.pool)
+                .unwrap();
+            let (canonical, _) = pda::vta_pda(
+                &anchor_lang::prelude::Pubkey::from(pool.pool.to_bytes()),
+                &anchor_lang::prelude::Pubkey::from(owner.to_bytes()),
+            );
+            assert_eq!(virtual_token_account.to_bytes(), canonical.to_bytes());
+
+            let account = runner.svm.get_account(&virtual_token_account).unwrap();
+            let data = VirtualTokenAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+            assert_eq!(data.owner.to_bytes(), owner.to_bytes());
+        }
+    }
+
+    #[test]
+    fn test_initialize_virtual_token_account_second_payer_rejected() {
+        let (mut runner, creator, pool) = setup_test();
+        let owner = Pubkey::new_unique();
+        let griefer = Keypair::new();
+        runner.airdrop(&griefer.pubkey(), 1_000_000_000);
+
+        let virtual_token_account = runner
+            .initialize_virtual_token_account(&griefer, owner, pool.pool)
+            .unwrap();
+        // Already opened, but still the owner's to use
+        assert!(runner
+            .initialize_virtual_token_account(&creator, owner, pool.pool)
+            .is_err());
+        let account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let data = VirtualTokenAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(data.owner.to_bytes(), owner.to_bytes());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{create_account, CreateAccount};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitializeVirtualTokenAccountsBatchArgs {
+    /// Skip owners whose virtual token account already exists instead of failing the batch
+    pub skip_existing: bool,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVirtualTokenAccountsBatch<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub pool: Account<'info, CbmmPool>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates one virtual token account per owner. `remaining_accounts` holds
+/// `[owner, virtual_token_account]` pairs, with the account writable.
+pub fn initialize_virtual_token_accounts_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, InitializeVirtualTokenAccountsBatch<'info>>,
+    args: InitializeVirtualTokenAccountsBatchArgs,
+) -> Result<()> {
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(2),
+        CbmmError::InvalidVirtualTokenAccount
+    );
+
+    let pool = ctx.accounts.pool.key();
+    let space = VirtualTokenAccount::INIT_SPACE + 8;
+    let lamports = Rent::get()?.minimum_balance(space);
+
+    for pair in ctx.remaining_accounts.chunks(2) {
+        let (owner, virtual_token_account) = (&pair[0], &pair[1]);
+        let (expected, bump) = Pubkey::find_program_address(
+            &[
+                VIRTUAL_TOKEN_ACCOUNT_SEED,
+                pool.as_ref(),
+                owner.key.as_ref(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            virtual_token_account.key(),
+            expected,
+            CbmmError::InvalidVirtualTokenAccount
+        );
+
+        if virtual_token_account.owner == ctx.program_id {
+            require!(
+                args.skip_existing,
+                CbmmError::VirtualTokenAccountAlreadyExists
+            );
+            continue;
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.as_ref(),
+            owner.key.as_ref(),
+            &[bump],
+        ]];
+        create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: virtual_token_account.clone(),
+                },
+                signer_seeds,
+            ),
+            lamports,
+            space as u64,
+            ctx.program_id,
+        )?;
+
+        VirtualTokenAccount::try_new(bump, pool, owner.key())
+            .try_serialize(&mut &mut virtual_token_account.try_borrow_mut_data()?[..])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::VirtualTokenAccount;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        (runner, payer, pool)
+    }
+
+    #[test]
+    fn test_initialize_virtual_token_accounts_batch() {
+        let (mut runner, payer, pool) = setup_test();
+        let owners = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+
+        let virtual_token_accounts = runner
+            .initialize_virtual_token_accounts_batch(&payer, pool.pool, &owners, false)
+            .unwrap();
+        for (owner, virtual_token_account) in owners.iter().zip(&virtual_token_accounts) {
+            let account = runner.svm.get_account(virtual_token_account).unwrap();
+            let data = VirtualTokenAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+            assert_eq!(data.owner.to_bytes(), owner.to_bytes());
+            assert_eq!(data.pool.to_bytes(), pool.pool.to_bytes());
+            assert_eq!(data.balance, 0);
+        }
+    }
+
+    #[test]
+    fn test_initialize_virtual_token_accounts_batch_existing() {
+        let (mut runner, payer, pool) = setup_test();
+        let existing = Pubkey::new_unique();
+        runner.create_virtual_token_account_mock(existing, pool.pool, 100);
+        let owners = [Pubkey::new_unique(), existing];
+
+        assert!(runner
+            .initialize_virtual_token_accounts_batch(&payer, pool.pool, &owners, false)
+            .is_err());
+
+        // Skipping leaves the existing account untouched
+        let virtual_token_accounts = runner
+            .initialize_virtual_token_accounts_batch(&payer, pool.pool, &owners, true)
+            .unwrap();
+        let account = runner.svm.get_account(&virtual_token_accounts[1]).unwrap();
+        let data = VirtualTokenAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(data.balance, 100);
+        assert!(runner.svm.get_account(&virtual_token_accounts[0]).is_some());
+    }
+}
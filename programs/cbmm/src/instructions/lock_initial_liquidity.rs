@@ -0,0 +1,245 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[event]
+pub struct InitialLiquidityLocked {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    /// A actually received by the pool ATA
+    pub amount: u64,
+    pub locked_quote_reserve: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LockInitialLiquidityArgs {
+    /// Amount of Mint A to lock, including decimals
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct LockInitialLiquidity<'info> {
+    #[account(address = pool.creator @ CbmmError::InvalidPoolCreator)]
+    pub creator: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program
+    )]
+    pub creator_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Locks creator A in the pool so it can trade on platforms with a `min_initial_lock`. Without
+/// it a creator could open a pool priced purely by its virtual reserve and dump on the first
+/// buyers at no cost. The lock only comes back through `close_pool`, once no B is left in
+/// circulation.
+pub fn lock_initial_liquidity(
+    ctx: Context<LockInitialLiquidity>,
+    args: LockInitialLiquidityArgs,
+) -> Result<()> {
+    require_gt!(args.amount, 0, CbmmError::AmountTooSmall);
+
+    // As with buys, a transfer fee on the mint may deliver less than requested
+    let balance_before = ctx.accounts.pool_ata.amount;
+    let cpi_accounts = TransferChecked {
+        mint: ctx.accounts.quote_mint.to_account_info(),
+        from: ctx.accounts.creator_ata.to_account_info(),
+        to: ctx.accounts.pool_ata.to_account_info(),
+        authority: ctx.accounts.creator.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_context, args.amount, ctx.accounts.quote_mint.decimals)?;
+    ctx.accounts.pool_ata.reload()?;
+    let amount = ctx
+        .accounts
+        .pool_ata
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(CbmmError::Underflow)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.locked_quote_reserve = pool
+        .locked_quote_reserve
+        .checked_add(amount)
+        .ok_or(CbmmError::MathOverflow)?;
+    emit!(InitialLiquidityLocked {
+        pool: pool.key(),
+        creator: ctx.accounts.creator.key(),
+        amount,
+        locked_quote_reserve: pool.locked_quote_reserve,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::InitialLiquidityLocked;
+    use crate::state::{CbmmPool, UpdatePlatformConfigArgs};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const MIN_INITIAL_LOCK: u64 = 10_000;
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&creator, 9);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        runner.mint_to(&creator, &quote_mint, creator_ata, 1_000_000);
+        let platform_config = runner
+            .create_platform_config_mock(&creator, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        runner
+            .update_platform_config(
+                &creator,
+                platform_config,
+                UpdatePlatformConfigArgs {
+                    min_initial_lock: Some(MIN_INITIAL_LOCK),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&creator, quote_mint, &pool.pool);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(creator.pubkey(), pool.pool, 0);
+        (
+            runner,
+            creator,
+            pool,
+            quote_mint,
+            creator_ata,
+            virtual_token_account,
+        )
+    }
+
+    #[test]
+    fn test_lock_initial_liquidity_missing_blocks_trading() {
+        let (mut runner, creator, pool, quote_mint, creator_ata, virtual_token_account) =
+            setup_test();
+        assert!(runner
+            .buy_virtual_token(
+                &creator,
+                creator_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0
+            )
+            .is_err());
+
+        // Locking less than the minimum is not enough
+        runner
+            .lock_initial_liquidity(&creator, pool.pool, creator_ata, MIN_INITIAL_LOCK - 1)
+            .unwrap();
+        runner.svm.expire_blockhash();
+        assert!(runner
+            .buy_virtual_token(
+                &creator,
+                creator_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_lock_initial_liquidity_opens_trading() {
+        let (mut runner, creator, pool, quote_mint, creator_ata, virtual_token_account) =
+            setup_test();
+        runner
+            .lock_initial_liquidity(&creator, pool.pool, creator_ata, MIN_INITIAL_LOCK)
+            .unwrap();
+        let event = runner
+            .last_events::<InitialLiquidityLocked>()
+            .pop()
+            .unwrap();
+        assert_eq!(event.amount, MIN_INITIAL_LOCK);
+
+        runner
+            .buy_virtual_token(
+                &creator,
+                creator_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.locked_quote_reserve, MIN_INITIAL_LOCK);
+        // The lock stays outside the reserves and fee balances
+        assert_eq!(
+            pool_data.quote_reserve
+                + pool_data.creator_fees_balance
+                + pool_data.buyback_fees_balance
+                + pool_data.platform_fees_balance,
+            5000
+        );
+    }
+
+    #[test]
+    fn test_lock_initial_liquidity_wrong_creator() {
+        let (mut runner, _, pool, quote_mint, _, _) = setup_test();
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 1_000_000_000);
+        let stranger_ata =
+            runner.create_associated_token_account(&stranger, quote_mint, &stranger.pubkey());
+        assert!(runner
+            .lock_initial_liquidity(&stranger, pool.pool, stranger_ata, MIN_INITIAL_LOCK)
+            .is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::errors::CbmmError;
+use crate::helpers::{BurnRateConfig, MigrationAmm};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// Pays the rent for the grown account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Dispatched on its discriminator and re-serialized in place
+    #[account(mut, owner = crate::ID @ CbmmError::InvalidOwner)]
+    pub account: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Upgrades a `PlatformConfig`, `CbmmPool` or `VirtualTokenAccount` to the current layout.
+/// New fields are appended, so the account is zero-extended before being read in the new layout.
+pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+    let discriminator = ctx.accounts.account.try_borrow_data()?[..8].to_vec();
+    if discriminator == PlatformConfig::DISCRIMINATOR {
+        migrate::<PlatformConfig>(ctx, upgrade_legacy_burn_tiers, |account| {
+            account.version = CURRENT_ACCOUNT_VERSION
+        })
+    } else if discriminator == CbmmPool::DISCRIMINATOR {
+        migrate::<CbmmPool>(ctx, append_only, |account| {
+            account.version = CURRENT_ACCOUNT_VERSION
+        })
+    } else if discriminator == VirtualTokenAccount::DISCRIMINATOR {
+        migrate::<VirtualTokenAccount>(ctx, append_only, |account| {
+            account.version = CURRENT_ACCOUNT_VERSION
+        })
+    } else {
+        err!(CbmmError::InvalidVersionedAccount)
+    }
+}
+
+/// `PlatformConfig` fields stored ahead of `burn_tiers`
+#[derive(AnchorDeserialize)]
+#[allow(dead_code)]
+struct PlatformConfigHead {
+    bump: u8,
+    admin: Pubkey,
+    creator: Pubkey,
+    quote_mint: Pubkey,
+    pool_creator_fee_bp: u16,
+    pool_topup_fee_bp: u16,
+    platform_fee_bp: u16,
+    keeper_reward_bp: u16,
+    referral_fee_bp: u16,
+    require_no_freeze_authority: bool,
+    lifetime_platform_fees: u128,
+    migration_amm: Option<MigrationAmm>,
+    burn_authority: Option<Pubkey>,
+    burn_rate_config: BurnRateConfig,
+    burn_reset_interval_seconds: i64,
+    burn_tiers_updated_at: i64,
+}
+
+/// `BurnTier` as stored before version 5
+#[derive(AnchorSerialize, AnchorDeserialize)]
+struct LegacyBurnTier {
+    burn_bp_x100: u32,
+    role: BurnRole,
+    max_daily_burns: u16,
+}
+
+/// Layouts that only ever appended fields need no rewrite before being zero-extended
+fn append_only(data: &[u8]) -> Result<Vec<u8>> {
+    Ok(data.to_vec())
+}
+
+/// Inserts `BurnTier::expires_at`, added in version 5, as `None` into every stored tier. The
+/// fields after `burn_tiers` move back accordingly.
+fn upgrade_legacy_burn_tiers(data: &[u8]) -> Result<Vec<u8>> {
+    let mut rest = &data[8..];
+    PlatformConfigHead::deserialize(&mut rest)?;
+    let head_len = data.len() - rest.len();
+    let legacy_tiers = Vec::<LegacyBurnTier>::deserialize(&mut rest)?;
+    let tiers_end = data.len() - rest.len();
+
+    let burn_tiers: Vec<BurnTier> = legacy_tiers
+        .into_iter()
+        .map(|tier| BurnTier {
+            burn_bp_x100: tier.burn_bp_x100,
+            role: tier.role,
+            max_daily_burns: tier.max_daily_burns,
+            expires_at: None,
+        })
+        .collect();
+    let mut upgraded = data[..head_len].to_vec();
+    burn_tiers.serialize(&mut upgraded)?;
+    upgraded.extend_from_slice(&data[tiers_end..]);
+    Ok(upgraded)
+}
+
+/// Accounts are always allocated at their full size, so one shorter than `T` predates the
+/// current layout and is rewritten by `upgrade_layout` before being zero-extended.
+fn migrate<T>(
+    ctx: Context<MigrateAccount>,
+    upgrade_layout: impl FnOnce(&[u8]) -> Result<Vec<u8>>,
+    set_version: impl FnOnce(&mut T),
+) -> Result<()>
+where
+    T: AccountSerialize + AccountDeserialize + Space,
+{
+    let account = ctx.accounts.account.to_account_info();
+    let space = T::INIT_SPACE + 8;
+    if account.data_len() < space {
+        let upgraded = upgrade_layout(&account.try_borrow_data()?)?;
+        let missing_lamports = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if missing_lamports > 0 {
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                missing_lamports,
+            )?;
+        }
+        account.resize(space)?;
+        account.try_borrow_mut_data()?[..upgraded.len()].copy_from_slice(&upgraded);
+    }
+
+    let mut data = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    set_version(&mut data);
+    data.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{upgrade_legacy_burn_tiers, LegacyBurnTier};
+    use crate::state::{
+        BurnRole, BurnTier, CbmmPool, PlatformConfig, VirtualTokenAccount,
+        CURRENT_ACCOUNT_VERSION, PLATFORM_CONFIG_V3_EXTRA_SPACE, POOL_V2_EXTRA_SPACE,
+        RESERVED_SPACE,
+    };
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            1000,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            10,
+            20,
+            0,
+        );
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 12345);
+        (
+            runner,
+            payer,
+            platform_config,
+            pool.pool,
+            virtual_token_account,
+        )
+    }
+
+    /// What version 3 appended to a mocked `PlatformConfig`, whose empty `allowed_quote_mints`
+    /// serializes without the room for five mints
+    const V3_CONFIG_TAIL: usize = PLATFORM_CONFIG_V3_EXTRA_SPACE - 5 * 32;
+
+    /// `PlatformConfig` data as stored before version 5, whose tiers lack `expires_at`
+    fn legacy_config_data(config: &PlatformConfig) -> Vec<u8> {
+        let mut tiered = Vec::new();
+        config.try_serialize(&mut tiered).unwrap();
+        let mut untiered_config = config.clone();
+        untiered_config.burn_tiers.clear();
+        let mut untiered = Vec::new();
+        untiered_config.try_serialize(&mut untiered).unwrap();
+        // The tier count is the first byte the two serializations differ in
+        let tiers_offset = tiered
+            .iter()
+            .zip(&untiered)
+            .position(|(a, b)| a != b)
+            .unwrap();
+
+        let legacy_tiers: Vec<LegacyBurnTier> = config
+            .burn_tiers
+            .iter()
+            .map(|tier| LegacyBurnTier {
+                burn_bp_x100: tier.burn_bp_x100,
+                role: tier.role.clone(),
+                max_daily_burns: tier.max_daily_burns,
+            })
+            .collect();
+        let mut legacy = untiered[..tiers_offset].to_vec();
+        legacy_tiers.serialize(&mut legacy).unwrap();
+        legacy.extend_from_slice(&untiered[tiers_offset + 4..]);
+        legacy
+    }
+
+    /// Mocked tiers are stored in the current layout, which a short account never holds
+    fn clear_burn_tiers(runner: &mut TestRunner, platform_config: &Pubkey) {
+        let account = runner.svm.get_account(platform_config).unwrap();
+        let mut config_data = PlatformConfig::try_deserialize(&mut account.data.as_slice()).unwrap();
+        config_data.burn_tiers.clear();
+        runner.put_account_on_chain(platform_config, config_data);
+    }
+
+    /// Rewrites a mocked account to an older layout by dropping its last `dropped_len` bytes
+    fn truncate_account(runner: &mut TestRunner, address: &Pubkey, dropped_len: usize) -> Vec<u8> {
+        let mut account = runner.svm.get_account(address).unwrap();
+        let legacy_len = account.data.len() - dropped_len;
+        account.data.truncate(legacy_len);
+        runner.svm.set_account(*address, account.clone()).unwrap();
+        account.data
+    }
+
+    fn assert_migrated<T: AccountDeserialize + Space>(
+        runner: &TestRunner,
+        address: &Pubkey,
+        legacy_data: &[u8],
+    ) -> T {
+        let account = runner.svm.get_account(address).unwrap();
+        assert!(account.data.len() >= T::INIT_SPACE + 8);
+        assert_eq!(&account.data[..legacy_data.len()], legacy_data);
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_account_preserves_fields() {
+        let (mut runner, payer, platform_config, pool, virtual_token_account) = setup_test();
+        clear_burn_tiers(&mut runner, &platform_config);
+        // Pre-versioning layouts end before `version` and everything appended after it
+        let legacy_config =
+            truncate_account(&mut runner, &platform_config, 1 + RESERVED_SPACE + V3_CONFIG_TAIL);
+        let legacy_pool =
+            truncate_account(&mut runner, &pool, 1 + RESERVED_SPACE + POOL_V2_EXTRA_SPACE);
+        let legacy_vta = truncate_account(&mut runner, &virtual_token_account, 1 + RESERVED_SPACE);
+
+        runner.migrate_account(&payer, platform_config).unwrap();
+        runner.migrate_account(&payer, pool).unwrap();
+        runner
+            .migrate_account(&payer, virtual_token_account)
+            .unwrap();
+
+        let config_data =
+            assert_migrated::<PlatformConfig>(&runner, &platform_config, &legacy_config);
+        assert_eq!(config_data.version, CURRENT_ACCOUNT_VERSION);
+        assert_eq!(config_data.platform_fee_bp, 200);
+
+        let pool_data = assert_migrated::<CbmmPool>(&runner, &pool, &legacy_pool);
+        assert_eq!(pool_data.version, CURRENT_ACCOUNT_VERSION);
+        assert_eq!(pool_data.quote_reserve, 1000);
+        assert_eq!(pool_data.creator_fees_balance, 10);
+        assert_eq!(pool_data.buyback_fees_balance, 20);
+
+        let vta_data =
+            assert_migrated::<VirtualTokenAccount>(&runner, &virtual_token_account, &legacy_vta);
+        assert_eq!(vta_data.version, CURRENT_ACCOUNT_VERSION);
+        assert_eq!(vta_data.balance, 12345);
+        assert_eq!(vta_data.owner.to_bytes(), payer.pubkey().to_bytes());
+    }
+
+    #[test]
+    fn test_migrate_account_grows_v1_pool() {
+        let (mut runner, payer, _, pool, _) = setup_test();
+        let legacy_pool = truncate_account(&mut runner, &pool, POOL_V2_EXTRA_SPACE);
+        assert!(CbmmPool::try_deserialize(&mut legacy_pool.as_slice()).is_err());
+
+        runner.migrate_account(&payer, pool).unwrap();
+
+        let pool_data = assert_migrated::<CbmmPool>(&runner, &pool, &legacy_pool);
+        assert_eq!(pool_data.version, CURRENT_ACCOUNT_VERSION);
+        assert_eq!(pool_data.quote_reserve, 1000);
+        assert_eq!(pool_data.cumulative_quote_volume, 0);
+        assert_eq!(pool_data.cumulative_base_volume, 0);
+        // Pools that predate the rounding policy keep rounding down
+        assert!(!pool_data.round_in_favor_of_trader);
+    }
+
+    #[test]
+    fn test_migrate_account_grows_v2_platform_config() {
+        let (mut runner, payer, platform_config, _, _) = setup_test();
+        clear_burn_tiers(&mut runner, &platform_config);
+        let legacy_config = truncate_account(&mut runner, &platform_config, V3_CONFIG_TAIL);
+        assert!(PlatformConfig::try_deserialize(&mut legacy_config.as_slice()).is_err());
+
+        runner.migrate_account(&payer, platform_config).unwrap();
+
+        let config_data =
+            assert_migrated::<PlatformConfig>(&runner, &platform_config, &legacy_config);
+        assert_eq!(config_data.version, CURRENT_ACCOUNT_VERSION);
+        assert_eq!(config_data.platform_fee_bp, 200);
+        assert!(config_data.allowed_quote_mints.is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_legacy_burn_tiers() {
+        let tier = |role: BurnRole| BurnTier {
+            burn_bp_x100: 1_000,
+            role,
+            max_daily_burns: 3,
+            expires_at: None,
+        };
+        let config = PlatformConfig {
+            burn_authority: Some(anchor_lang::prelude::Pubkey::new_unique()),
+            burn_tiers: vec![
+                tier(BurnRole::Anyone),
+                tier(BurnRole::SpecificPubkey(anchor_lang::prelude::Pubkey::new_unique())),
+            ],
+            version: 4,
+            paused: true,
+            fee_recipient: anchor_lang::prelude::Pubkey::new_unique(),
+            ..Default::default()
+        };
+        let mut current = Vec::new();
+        config.try_serialize(&mut current).unwrap();
+
+        let legacy = legacy_config_data(&config);
+        assert_eq!(legacy.len(), current.len() - 2);
+        assert_eq!(upgrade_legacy_burn_tiers(&legacy).unwrap(), current);
+    }
+
+    #[test]
+    fn test_migrate_account_inserts_burn_tier_expiry() {
+        let (mut runner, payer, platform_config, _, _) = setup_test();
+        let mut account = runner.svm.get_account(&platform_config).unwrap();
+        let config_data = PlatformConfig::try_deserialize(&mut account.data.as_slice()).unwrap();
+        account.data = legacy_config_data(&config_data);
+        runner.svm.set_account(platform_config, account).unwrap();
+
+        runner.migrate_account(&payer, platform_config).unwrap();
+
+        let migrated_account = runner.svm.get_account(&platform_config).unwrap();
+        assert_eq!(migrated_account.data.len(), PlatformConfig::INIT_SPACE + 8);
+        let migrated =
+            PlatformConfig::try_deserialize(&mut migrated_account.data.as_slice()).unwrap();
+        assert_eq!(migrated.version, CURRENT_ACCOUNT_VERSION);
+        assert!(migrated.burn_tiers == config_data.burn_tiers);
+        assert!(migrated.burn_tiers.iter().all(|tier| tier.expires_at.is_none()));
+        assert_eq!(migrated.fee_recipient, config_data.fee_recipient);
+        assert_eq!(migrated.min_topup_fee_bp, config_data.min_topup_fee_bp);
+    }
+
+    #[test]
+    fn test_created_pool_has_current_version() {
+        let (mut runner, payer, platform_config, _, _) = setup_test();
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(platform_config_data.quote_mint.to_bytes());
+        let pool = runner
+            .create_pool(&creator, platform_config, quote_mint, 1_000_000)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.version, CURRENT_ACCOUNT_VERSION);
+
+        // Already current, so migrating again is a no-op
+        runner.migrate_account(&payer, pool.pool).unwrap();
+        let migrated_account = runner.svm.get_account(&pool.pool).unwrap();
+        assert_eq!(migrated_account.data, pool_account.data);
+    }
+
+    #[test]
+    fn test_migrate_account_rejects_unversioned_account() {
+        let (mut runner, payer, platform_config, _, _) = setup_test();
+        let registry = runner.get_platform_registry_pda(platform_config);
+        runner.put_account_on_chain(&registry, crate::state::PlatformRegistry::default());
+        assert!(runner.migrate_account(&payer, registry).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::errors::CbmmError;
+use crate::instructions::check_burn_tier_role;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MigrateBurnAllowanceArgs {
+    /// Tier of the new allowance, indexing the current burn tiers
+    pub burn_tier_index: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(args: MigrateBurnAllowanceArgs)]
+pub struct MigrateBurnAllowance<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The user whose burn allowance is being migrated
+    /// CHECK: Can be any account.
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = burn_allowance_open_payer,
+        seeds = [
+            USER_BURN_ALLOWANCE_SEED,
+            owner.key().as_ref(),
+            platform_config.key().as_ref(),
+            &[stale_burn_allowance.burn_tier_index],
+            stale_burn_allowance.burn_tier_update_timestamp.to_le_bytes().as_ref(),
+        ],
+        bump = stale_burn_allowance.bump,
+        constraint = platform_config.burn_tiers_updated_at
+            > stale_burn_allowance.burn_tier_update_timestamp @ CbmmError::BurnAllowanceNotStale,
+    )]
+    pub stale_burn_allowance: Account<'info, UserBurnAllowance>,
+
+    /// CHECK: Checked that it's the same as the payer in the stale burn allowance account.
+    #[account(mut, address = stale_burn_allowance.payer @ CbmmError::InvalidBurnAccountPayer)]
+    pub burn_allowance_open_payer: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + UserBurnAllowance::INIT_SPACE,
+        seeds = [
+            USER_BURN_ALLOWANCE_SEED,
+            owner.key().as_ref(),
+            platform_config.key().as_ref(),
+            &[args.burn_tier_index],
+            platform_config.burn_tiers_updated_at.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub user_burn_allowance: Account<'info, UserBurnAllowance>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Optional pool account - only needed if requesting a pool creator burn tier
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Option<Account<'info, CbmmPool>>,
+}
+
+/// Replaces an allowance invalidated by a burn tier update with one for the current tiers, in a
+/// single transaction. Rent of the stale allowance goes back to whoever opened it.
+pub fn migrate_burn_allowance(
+    ctx: Context<MigrateBurnAllowance>,
+    args: MigrateBurnAllowanceArgs,
+) -> Result<()> {
+    check_burn_tier_role(
+        &ctx.accounts.platform_config,
+        args.burn_tier_index,
+        ctx.accounts.owner.key(),
+        ctx.accounts.pool.as_deref(),
+    )?;
+
+    let platform_config = &ctx.accounts.platform_config;
+    let migrated = ctx.accounts.stale_burn_allowance.migrate(
+        ctx.bumps.user_burn_allowance,
+        ctx.accounts.payer.key(),
+        args.burn_tier_index,
+        platform_config.burn_tiers_updated_at,
+        platform_config.burn_reset_interval_seconds,
+        Clock::get()?.unix_timestamp,
+    );
+    ctx.accounts.user_burn_allowance.set_inner(migrated);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{BurnRole, BurnTier, UpdatePlatformConfigArgs};
+    use crate::test_utils::TestRunner;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+    const DAY: i64 = 86400;
+
+    fn setup_test(last_burn_timestamp: i64) -> (TestRunner, Keypair, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let user = Keypair::new();
+        let funder = Keypair::new();
+        runner.airdrop(&user.pubkey(), 10_000_000_000);
+        runner.airdrop(&funder.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&user, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&user, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        runner.set_system_clock(NOW);
+        let stale_burn_allowance = runner.create_user_burn_allowance_mock(
+            user.pubkey(),
+            funder.pubkey(),
+            platform_config,
+            2,
+            last_burn_timestamp,
+            false,
+            NOW - 2 * DAY,
+        );
+        (runner, user, funder, platform_config, stale_burn_allowance)
+    }
+
+    fn update_burn_tiers(runner: &mut TestRunner, admin: &Keypair, platform_config: Pubkey) {
+        let args = UpdatePlatformConfigArgs {
+            burn_tiers: Some(vec![BurnTier {
+                burn_bp_x100: 500,
+                role: BurnRole::Anyone,
+                max_daily_burns: 3,
+                expires_at: None,
+            }]),
+            ..Default::default()
+        };
+        runner
+            .update_platform_config(admin, platform_config, args)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_migrate_burn_allowance_same_day() {
+        let (mut runner, user, funder, platform_config, stale_burn_allowance) =
+            setup_test(NOW - 3600);
+        let rent = runner.svm.get_balance(&stale_burn_allowance).unwrap();
+        let funder_balance = runner.svm.get_balance(&funder.pubkey()).unwrap();
+        update_burn_tiers(&mut runner, &user, platform_config);
+
+        let user_burn_allowance = runner
+            .migrate_burn_allowance(&user, stale_burn_allowance, 0, None)
+            .unwrap();
+
+        assert!(runner
+            .get_user_burn_allowance(&stale_burn_allowance)
+            .is_err());
+        assert_eq!(
+            runner.svm.get_balance(&funder.pubkey()).unwrap(),
+            funder_balance + rent
+        );
+        let migrated = runner
+            .get_user_burn_allowance(&user_burn_allowance)
+            .unwrap();
+        assert_eq!(migrated.burn_tier_update_timestamp, NOW);
+        assert_eq!(migrated.burns_today, 2);
+        assert_eq!(migrated.last_burn_timestamp, NOW - 3600);
+        assert_eq!(migrated.payer.to_bytes(), user.pubkey().to_bytes());
+    }
+
+    #[test]
+    fn test_migrate_burn_allowance_new_day() {
+        let (mut runner, user, _, platform_config, stale_burn_allowance) = setup_test(NOW - DAY);
+        update_burn_tiers(&mut runner, &user, platform_config);
+
+        let user_burn_allowance = runner
+            .migrate_burn_allowance(&user, stale_burn_allowance, 0, None)
+            .unwrap();
+
+        let migrated = runner
+            .get_user_burn_allowance(&user_burn_allowance)
+            .unwrap();
+        assert_eq!(migrated.burns_today, 0);
+        assert_eq!(migrated.created_at, NOW);
+    }
+
+    #[test]
+    fn test_migrate_burn_allowance_not_stale() {
+        let (mut runner, user, _, _, stale_burn_allowance) = setup_test(NOW - 3600);
+        assert!(runner
+            .migrate_burn_allowance(&user, stale_burn_allowance, 0, None)
+            .is_err());
+        assert!(runner
+            .get_user_burn_allowance(&stale_burn_allowance)
+            .is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event]
+pub struct LiquidityMigrated {
+    pub pool: Pubkey,
+    pub amm_program: Pubkey,
+    pub lp_owner: Pubkey,
+    pub quote_amount: u64,
+    pub base_amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+        constraint = platform_config.migration_amm.is_some() @ CbmmError::MigrationNotConfigured,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = pool.graduated @ CbmmError::NotGraduated,
+        constraint = pool.migrated_lp_owner.is_none() @ CbmmError::AlreadyMigrated,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        associated_token::mint = base_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_base_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = pool.base_mint @ CbmmError::InvalidMint)]
+    pub base_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Recorded on the pool as the owner of the resulting LP position
+    pub lp_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Must match the AMM configured on the platform config
+    #[account(
+        constraint = platform_config.migration_amm.is_some_and(|amm| amm.program_id == amm_program.key())
+            @ CbmmError::InvalidAmmProgram,
+    )]
+    pub amm_program: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Deposits the real quote reserve of a graduated pool, paired with freshly minted B at the
+/// final spot price, into the configured external AMM. AMM-specific accounts are passed
+/// through from `remaining_accounts` after the fixed deposit accounts.
+pub fn migrate_liquidity<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigrateLiquidity<'info>>,
+) -> Result<()> {
+    let amm = ctx.accounts.platform_config.migration_amm.unwrap();
+    let pool = &mut ctx.accounts.pool;
+    let pool_account_info = pool.to_account_info();
+    let amounts = pool.take_migration_amounts()?;
+    pool.migrated_lp_owner = Some(ctx.accounts.lp_owner.key());
+
+    pool.mint_base_to(
+        amounts.base_amount,
+        &pool_account_info,
+        &ctx.accounts.base_mint,
+        &ctx.accounts.pool_base_ata,
+        &ctx.accounts.token_program,
+    )?;
+
+    let mut account_metas = vec![
+        AccountMeta::new_readonly(pool.key(), true),
+        AccountMeta::new(ctx.accounts.pool_ata.key(), false),
+        AccountMeta::new(ctx.accounts.pool_base_ata.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.quote_mint.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.base_mint.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.lp_owner.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+    ];
+    let mut account_infos = vec![
+        pool_account_info,
+        ctx.accounts.pool_ata.to_account_info(),
+        ctx.accounts.pool_base_ata.to_account_info(),
+        ctx.accounts.quote_mint.to_account_info(),
+        ctx.accounts.base_mint.to_account_info(),
+        ctx.accounts.lp_owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.amm_program.to_account_info(),
+    ];
+    for account in ctx.remaining_accounts {
+        account_metas.push(if account.is_writable {
+            AccountMeta::new(account.key(), false)
+        } else {
+            AccountMeta::new_readonly(account.key(), false)
+        });
+        account_infos.push(account.clone());
+    }
+    let ix = amm.interface.deposit_instruction(
+        amm.program_id,
+        account_metas,
+        amounts.quote_amount,
+        amounts.base_amount,
+    );
+
+    let pool_index_bytes = pool.pool_index.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        CBMM_POOL_SEED,
+        pool_index_bytes.as_ref(),
+        pool.seed_creator.as_ref(),
+        pool.platform_config.as_ref(),
+        pool.quote_mint.as_ref(),
+        &[pool.bump],
+    ]];
+    invoke_signed(&ix, &account_infos, signer_seeds)?;
+
+    emit!(LiquidityMigrated {
+        pool: pool.key(),
+        amm_program: amm.program_id,
+        lp_owner: ctx.accounts.lp_owner.key(),
+        quote_amount: amounts.quote_amount,
+        base_amount: amounts.base_amount,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helpers::{AmmInterface, MigrationAmm};
+    use crate::state::{CbmmPool, PlatformConfig};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const QUOTE_RESERVE: u64 = 1_000_000;
+
+    fn setup_test(amm_program: Option<Pubkey>) -> (TestRunner, Keypair, TestPool) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            QUOTE_RESERVE,
+            3_000_000,
+            8_000_000,
+            10_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let pool_ata = runner.create_associated_token_account(&admin, quote_mint, &pool.pool);
+        runner.mint_to(&admin, &quote_mint, pool_ata, QUOTE_RESERVE);
+
+        let platform_config_account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut platform_config_account.data.as_slice()).unwrap();
+        platform_config_data.migration_amm = amm_program.map(|program_id| MigrationAmm {
+            program_id: anchor_lang::prelude::Pubkey::new_from_array(program_id.to_bytes()),
+            interface: AmmInterface::AnchorDepositLiquidity,
+        });
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+        (runner, admin, pool)
+    }
+
+    fn get_pool(runner: &TestRunner, pool: &Pubkey) -> CbmmPool {
+        let pool_account = runner.svm.get_account(pool).unwrap();
+        CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_liquidity_not_configured() {
+        let (mut runner, admin, pool) = setup_test(None);
+        runner.graduate_pool(&admin, pool.pool).unwrap();
+        assert!(runner
+            .migrate_liquidity(&admin, pool.pool, admin.pubkey(), Pubkey::new_unique())
+            .is_err());
+    }
+
+    #[test]
+    fn test_migrate_liquidity_wrong_amm_program() {
+        let (mut runner, admin, pool) = setup_test(Some(Pubkey::new_unique()));
+        runner.graduate_pool(&admin, pool.pool).unwrap();
+        assert!(runner
+            .migrate_liquidity(&admin, pool.pool, admin.pubkey(), Pubkey::new_unique())
+            .is_err());
+    }
+
+    #[test]
+    fn test_migrate_liquidity_not_graduated() {
+        let amm_program = Pubkey::new_unique();
+        let (mut runner, admin, pool) = setup_test(Some(amm_program));
+        assert!(runner
+            .migrate_liquidity(&admin, pool.pool, admin.pubkey(), amm_program)
+            .is_err());
+    }
+
+    #[test]
+    fn test_migrate_liquidity_wrong_admin() {
+        let amm_program = Pubkey::new_unique();
+        let (mut runner, admin, pool) = setup_test(Some(amm_program));
+        runner.graduate_pool(&admin, pool.pool).unwrap();
+        let other = Keypair::new();
+        runner.airdrop(&other.pubkey(), 10_000_000_000);
+        assert!(runner
+            .migrate_liquidity(&other, pool.pool, other.pubkey(), amm_program)
+            .is_err());
+    }
+
+    #[test]
+    fn test_migrate_liquidity_failed_cpi_reverts_accounting() {
+        // Nothing is deployed at the configured AMM, so the deposit CPI fails after the
+        // pre-CPI accounting has run and the whole migration must roll back
+        let amm_program = Pubkey::new_unique();
+        let (mut runner, admin, pool) = setup_test(Some(amm_program));
+        runner.graduate_pool(&admin, pool.pool).unwrap();
+
+        assert!(runner
+            .migrate_liquidity(&admin, pool.pool, admin.pubkey(), amm_program)
+            .is_err());
+        let pool_data = get_pool(&runner, &pool.pool);
+        assert_eq!(pool_data.quote_reserve, QUOTE_RESERVE);
+        assert_eq!(pool_data.migrated_lp_owner, None);
+    }
+}
@@ -1,27 +1,123 @@
+mod accept_pool_creator;
+mod apply_config_change;
+mod approve_virtual;
 mod burn_virtual_token;
+mod burn_virtual_token_batch;
 mod buy_virtual_token;
+mod buy_with_sol;
+mod cancel_limit_buy;
+mod cancel_order;
+mod check_allowance_closable;
 mod claim_creator_fees;
+mod claim_creator_fees_and_close;
+mod claim_graduated_tokens;
 mod claim_platform_fees;
+mod close_burn_schedule;
+mod close_platform_config;
+mod close_pool;
 mod close_user_burn_allowance;
 mod close_virtual_token_account;
+mod crank_topup;
+mod create_burn_schedule;
 mod create_pool;
+mod execute_order;
+mod fill_limit_buy;
+mod flush_burn_queue;
+mod get_burn_allowance_status;
+mod get_effective_price;
+mod get_market_cap;
+mod get_pool_state;
+mod graduate_pool;
 mod initialize_platform_config;
 mod initialize_user_burn_allowance;
 mod initialize_virtual_token_account;
+mod initialize_virtual_token_accounts_batch;
+mod lock_initial_liquidity;
+mod migrate_account;
+mod migrate_burn_allowance;
+mod migrate_liquidity;
+mod place_limit_buy;
+mod place_order;
+mod propose_config_change;
+mod propose_pool_creator;
+mod quote_buy_batch;
+mod rescale_virtual_reserve;
+mod resync_burn_limiter;
+mod run_burn_schedule;
+mod sell_to_sol;
 mod sell_virtual_token;
+mod set_allow_user_burns;
+mod set_fee_holiday;
+mod set_platform_paused;
+mod set_topup_order;
+mod set_vta_frozen;
+mod snapshot_vta;
+mod sweep_treasuries;
+mod sync_reserves;
+mod tip_creator;
+mod topup_with_deposit;
+mod update_burn_rate_config;
 mod update_platform_config;
 
+pub use accept_pool_creator::*;
+pub use apply_config_change::*;
+pub use approve_virtual::*;
 pub use burn_virtual_token::*;
+pub use burn_virtual_token_batch::*;
 pub use buy_virtual_token::*;
+pub use buy_with_sol::*;
+pub use cancel_limit_buy::*;
+pub use cancel_order::*;
+pub use check_allowance_closable::*;
 pub use claim_creator_fees::*;
+pub use claim_creator_fees_and_close::*;
+pub use claim_graduated_tokens::*;
 pub use claim_platform_fees::*;
+pub use close_burn_schedule::*;
+pub use close_platform_config::*;
+pub use close_pool::*;
 pub use close_user_burn_allowance::*;
 pub use close_virtual_token_account::*;
+pub use crank_topup::*;
+pub use create_burn_schedule::*;
 pub use create_pool::*;
+pub use execute_order::*;
+pub use fill_limit_buy::*;
+pub use flush_burn_queue::*;
+pub use get_burn_allowance_status::*;
+pub use get_effective_price::*;
+pub use get_market_cap::*;
+pub use get_pool_state::*;
+pub use graduate_pool::*;
 pub use initialize_platform_config::*;
 pub use initialize_user_burn_allowance::*;
 pub use initialize_virtual_token_account::*;
+pub use initialize_virtual_token_accounts_batch::*;
+pub use lock_initial_liquidity::*;
+pub use migrate_account::*;
+pub use migrate_burn_allowance::*;
+pub use migrate_liquidity::*;
+pub use place_limit_buy::*;
+pub use place_order::*;
+pub use propose_config_change::*;
+pub use propose_pool_creator::*;
+pub use quote_buy_batch::*;
+pub use rescale_virtual_reserve::*;
+pub use resync_burn_limiter::*;
+pub use run_burn_schedule::*;
+pub use sell_to_sol::*;
 pub use sell_virtual_token::*;
+pub use set_allow_user_burns::*;
+pub use set_fee_holiday::*;
+pub use set_platform_paused::*;
+pub use set_topup_order::*;
+pub use set_vta_frozen::*;
+pub use snapshot_vta::*;
+pub use sweep_treasuries::*;
+pub use sync_reserves::*;
+pub use tip_creator::*;
+pub use topup_with_deposit::*;
+pub use update_burn_rate_config::*;
 pub use update_platform_config::*;
 
 // Setup metrics collection for all tests.
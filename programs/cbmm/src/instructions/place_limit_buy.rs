@@ -0,0 +1,90 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PlaceLimitBuyArgs {
+    /// Caller-chosen id, lets one owner keep several orders open on the same pool
+    pub order_id: u64,
+    /// Quote amount to escrow, including decimals
+    pub quote_amount: u64,
+    pub max_price_x64: u128,
+}
+
+#[derive(Accounts)]
+#[instruction(args: PlaceLimitBuyArgs)]
+pub struct PlaceLimitBuy<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program
+    )]
+    pub owner_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = LimitBuyOrder::INIT_SPACE + 8,
+        seeds = [
+            LIMIT_BUY_ORDER_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            args.order_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub order: Account<'info, LimitBuyOrder>,
+
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = quote_mint,
+        associated_token::authority = order,
+        associated_token::token_program = token_program
+    )]
+    pub escrow_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn place_limit_buy(ctx: Context<PlaceLimitBuy>, args: PlaceLimitBuyArgs) -> Result<()> {
+    require_gt!(args.quote_amount, 0, CbmmError::AmountTooSmall);
+
+    let cpi_accounts = TransferChecked {
+        mint: ctx.accounts.quote_mint.to_account_info(),
+        from: ctx.accounts.owner_ata.to_account_info(),
+        to: ctx.accounts.escrow_ata.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(
+        cpi_context,
+        args.quote_amount,
+        ctx.accounts.quote_mint.decimals,
+    )?;
+    // Transfer fees on the mint may deliver less than requested
+    ctx.accounts.escrow_ata.reload()?;
+
+    ctx.accounts.order.set_inner(LimitBuyOrder {
+        bump: ctx.bumps.order,
+        pool: ctx.accounts.pool.key(),
+        owner: ctx.accounts.owner.key(),
+        order_id: args.order_id,
+        quote_amount: ctx.accounts.escrow_ata.amount,
+        max_price_x64: args.max_price_x64,
+    });
+    Ok(())
+}
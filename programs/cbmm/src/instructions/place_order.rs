@@ -0,0 +1,166 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PlaceOrderArgs {
+    /// Caller-chosen id, lets one owner keep several orders open on the same pool
+    pub order_id: u64,
+    pub base_amount: u64,
+    pub trigger_price_x64: u128,
+    pub direction: OrderDirection,
+    /// Least A the owner accepts on execution, guards the order against a pushed price
+    pub min_quote_amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: PlaceOrderArgs)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Order::INIT_SPACE + 8,
+        seeds = [
+            ORDER_SEED,
+            pool.key().as_ref(),
+            owner.key().as_ref(),
+            args.order_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub order: Account<'info, Order>,
+
+    pub pool: Account<'info, CbmmPool>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn place_order(ctx: Context<PlaceOrder>, args: PlaceOrderArgs) -> Result<()> {
+    require_gt!(args.base_amount, 0, CbmmError::AmountTooSmall);
+    ctx.accounts.virtual_token_account.sub(args.base_amount)?;
+
+    ctx.accounts.order.set_inner(Order {
+        bump: ctx.bumps.order,
+        pool: ctx.accounts.pool.key(),
+        owner: ctx.accounts.owner.key(),
+        order_id: args.order_id,
+        base_amount: args.base_amount,
+        trigger_price_x64: args.trigger_price_x64,
+        direction: args.direction,
+        min_quote_amount: args.min_quote_amount,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{OrderDirection, VirtualTokenAccount};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    fn setup_test(balance: u64) -> (TestRunner, Keypair, TestPool, Pubkey) {
+        let mut runner = TestRunner::new();
+        let owner = Keypair::new();
+        runner.airdrop(&owner.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&owner, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&owner, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &owner,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(owner.pubkey(), pool.pool, balance);
+        (runner, owner, pool, virtual_token_account)
+    }
+
+    #[test_case(1000, true; "full_balance")]
+    #[test_case(400, true; "partial_balance")]
+    #[test_case(1001, false; "above_balance")]
+    #[test_case(0, false; "zero_amount")]
+    fn test_place_order_escrows_base_amount(base_amount: u64, success: bool) {
+        let (mut runner, owner, pool, virtual_token_account) = setup_test(1000);
+
+        let result = runner.place_order(
+            &owner,
+            pool.pool,
+            0,
+            base_amount,
+            1 << 64,
+            OrderDirection::StopLoss,
+        );
+        assert_eq!(result.is_ok(), success);
+
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        if success {
+            assert_eq!(vta_data.balance, 1000 - base_amount);
+            let order = runner.get_order(&result.unwrap()).unwrap();
+            assert_eq!(order.base_amount, base_amount);
+            assert_eq!(order.trigger_price_x64, 1 << 64);
+            assert_eq!(order.owner.to_bytes(), owner.pubkey().to_bytes());
+        } else {
+            assert_eq!(vta_data.balance, 1000);
+        }
+    }
+
+    #[test]
+    fn test_place_multiple_orders() {
+        let (mut runner, owner, pool, _) = setup_test(1000);
+
+        let first = runner
+            .place_order(&owner, pool.pool, 0, 300, 1 << 64, OrderDirection::StopLoss)
+            .unwrap();
+        let second = runner
+            .place_order(
+                &owner,
+                pool.pool,
+                1,
+                300,
+                2 << 64,
+                OrderDirection::TakeProfit,
+            )
+            .unwrap();
+        assert_ne!(first, second);
+
+        // Reusing an id fails while the order is open
+        assert!(runner
+            .place_order(
+                &owner,
+                pool.pool,
+                1,
+                300,
+                2 << 64,
+                OrderDirection::TakeProfit
+            )
+            .is_err());
+    }
+}
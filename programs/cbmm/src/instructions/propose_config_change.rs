@@ -0,0 +1,50 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ProposeConfigChange<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PendingConfigChange::INIT_SPACE,
+        seeds = [PENDING_CONFIG_CHANGE_SEED, platform_config.key().as_ref()],
+        bump,
+    )]
+    pub pending_config_change: Account<'info, PendingConfigChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Stages a config change that `apply_config_change` can write once the platform's
+/// `config_change_delay_seconds` have passed. Proposing again replaces the pending change and
+/// restarts the delay.
+pub fn propose_config_change(
+    ctx: Context<ProposeConfigChange>,
+    args: UpdatePlatformConfigArgs,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let effective_at = now
+        .checked_add(ctx.accounts.platform_config.config_change_delay_seconds)
+        .ok_or(CbmmError::MathOverflow)?;
+
+    ctx.accounts
+        .pending_config_change
+        .set_inner(PendingConfigChange {
+            bump: ctx.bumps.pending_config_change,
+            platform_config: ctx.accounts.platform_config.key(),
+            effective_at,
+            args,
+        });
+    Ok(())
+}
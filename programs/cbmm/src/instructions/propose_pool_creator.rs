@@ -0,0 +1,37 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProposePoolCreatorArgs {
+    /// New creator; takes over only once they call `accept_pool_creator`
+    pub new_creator: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct ProposePoolCreator<'info> {
+    #[account(address = pool.creator @ CbmmError::InvalidPoolCreator)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// First step of the creator handover. Proposing again overwrites the pending creator.
+pub fn propose_pool_creator(
+    ctx: Context<ProposePoolCreator>,
+    args: ProposePoolCreatorArgs,
+) -> Result<()> {
+    ctx.accounts.pool.pending_creator = Some(args.new_creator);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use crate::errors::CbmmError;
+use crate::simulate::simulate_buy;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Most amounts `quote_buy_batch` quotes in one call, bounding its compute
+pub const MAX_QUOTE_BATCH_LEN: usize = 10;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct QuoteBuyBatchArgs {
+    /// A to quote buys for, fees included, each against the same pool state
+    pub quote_amounts: Vec<u64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BuyQuote {
+    pub quote_amount: u64,
+    /// B the buy would credit
+    pub base_output: u64,
+    /// A paid per B, fees included, as Q64.64
+    pub effective_price_x64: u128,
+}
+
+#[derive(Accounts)]
+pub struct QuoteBuyBatch<'info> {
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Read-only `simulate_buy` for up to `MAX_QUOTE_BATCH_LEN` amounts at once, e.g. for a depth
+/// chart. Every amount is quoted on its own against the current pool, not chained.
+/// The result is returned via return data.
+pub fn quote_buy_batch(
+    ctx: Context<QuoteBuyBatch>,
+    args: QuoteBuyBatchArgs,
+) -> Result<Vec<BuyQuote>> {
+    require!(
+        !args.quote_amounts.is_empty() && args.quote_amounts.len() <= MAX_QUOTE_BATCH_LEN,
+        CbmmError::InvalidQuoteBatch
+    );
+    let now = Clock::get()?.unix_timestamp;
+    let pool = &ctx.accounts.pool;
+
+    args.quote_amounts
+        .into_iter()
+        .map(|quote_amount| {
+            let buy = simulate_buy(pool, quote_amount, now)?;
+            Ok(BuyQuote {
+                quote_amount,
+                base_output: buy.base_output,
+                effective_price_x64: ((quote_amount as u128) << 64) / buy.base_output as u128,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::MAX_QUOTE_BATCH_LEN;
+    use crate::simulate::simulate_buy;
+    use crate::state::CbmmPool;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        runner.set_system_clock(NOW);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        (runner, payer, pool)
+    }
+
+    #[test]
+    fn test_quote_buy_batch_is_monotonic() {
+        let (runner, payer, pool) = setup_test();
+        let quote_amounts = vec![1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+        let quotes = runner
+            .quote_buy_batch(&payer, pool.pool, quote_amounts.clone())
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(quotes.len(), quote_amounts.len());
+        for (quote, quote_amount) in quotes.iter().zip(quote_amounts) {
+            assert_eq!(quote.quote_amount, quote_amount);
+            assert_eq!(
+                quote.base_output,
+                simulate_buy(&pool_data, quote_amount, NOW).unwrap().base_output
+            );
+        }
+        // Bigger buys get more B, at a worse price
+        for pair in quotes.windows(2) {
+            assert!(pair[1].base_output > pair[0].base_output);
+            assert!(pair[1].effective_price_x64 > pair[0].effective_price_x64);
+        }
+    }
+
+    #[test]
+    fn test_quote_buy_batch_length_bounds() {
+        let (runner, payer, pool) = setup_test();
+
+        assert!(runner.quote_buy_batch(&payer, pool.pool, vec![]).is_err());
+        assert!(runner
+            .quote_buy_batch(&payer, pool.pool, vec![1_000; MAX_QUOTE_BATCH_LEN + 1])
+            .is_err());
+        assert!(runner
+            .quote_buy_batch(&payer, pool.pool, vec![1_000; MAX_QUOTE_BATCH_LEN])
+            .is_ok());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::errors::CbmmError;
+use crate::helpers::calculate_optimal_real_quote_reserve;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct VirtualReserveRescaled {
+    pub old_quote_virtual_reserve: u64,
+    pub new_quote_virtual_reserve: u64,
+
+    pub pool: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct RescaleVirtualReserve<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Moves `quote_virtual_reserve` to `quote_optimal_virtual_reserve`. Only allowed when the
+/// price does not drop, which would dilute holders, and the real reserve still covers a
+/// full exit at the new curve.
+pub fn rescale_virtual_reserve(ctx: Context<RescaleVirtualReserve>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let old_quote_virtual_reserve = pool.quote_virtual_reserve;
+    let new_quote_virtual_reserve = pool.quote_optimal_virtual_reserve;
+
+    // Price is (A + V) / B, so with A and B fixed it only depends on V
+    require_gte!(
+        new_quote_virtual_reserve,
+        old_quote_virtual_reserve,
+        CbmmError::InvalidVirtualReserve
+    );
+    let required_real_reserve = calculate_optimal_real_quote_reserve(
+        pool.base_total_supply,
+        new_quote_virtual_reserve,
+        pool.base_reserve,
+    );
+    require_gte!(
+        pool.quote_reserve,
+        required_real_reserve,
+        CbmmError::InvalidVirtualReserve
+    );
+
+    pool.quote_virtual_reserve = new_quote_virtual_reserve;
+
+    emit!(VirtualReserveRescaled {
+        old_quote_virtual_reserve,
+        new_quote_virtual_reserve,
+        pool: pool.key(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::VirtualReserveRescaled;
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    fn setup_test(
+        quote_virtual_reserve: u64,
+        quote_optimal_virtual_reserve: u64,
+    ) -> (TestRunner, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            0,
+            quote_virtual_reserve,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.quote_optimal_virtual_reserve = quote_optimal_virtual_reserve;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        (runner, admin, platform_config, pool.pool)
+    }
+
+    #[test_case(900_000, 1_000_000, true; "price_increases")]
+    #[test_case(1_000_000, 1_000_000, true; "price_unchanged")]
+    #[test_case(1_000_000, 900_000, false; "price_decreases")]
+    fn test_rescale_virtual_reserve(
+        quote_virtual_reserve: u64,
+        quote_optimal_virtual_reserve: u64,
+        success: bool,
+    ) {
+        let (mut runner, admin, platform_config, pool) =
+            setup_test(quote_virtual_reserve, quote_optimal_virtual_reserve);
+
+        let result = runner.rescale_virtual_reserve(&admin, platform_config, pool);
+        assert_eq!(result.is_ok(), success);
+
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        if success {
+            assert_eq!(
+                pool_data.quote_virtual_reserve,
+                quote_optimal_virtual_reserve
+            );
+            let events = runner.last_events::<VirtualReserveRescaled>();
+            assert_eq!(events[0].old_quote_virtual_reserve, quote_virtual_reserve);
+            assert_eq!(
+                events[0].new_quote_virtual_reserve,
+                quote_optimal_virtual_reserve
+            );
+        } else {
+            assert_eq!(pool_data.quote_virtual_reserve, quote_virtual_reserve);
+        }
+    }
+
+    #[test]
+    fn test_rescale_virtual_reserve_insolvent() {
+        let (mut runner, admin, platform_config, pool) = setup_test(900_000, 1_000_000);
+
+        // Half the supply circulates but the pool holds no real reserve to back a higher price
+        let pool_account = runner.svm.get_account(&pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.base_reserve = 1_000_000;
+        runner.put_account_on_chain(&pool, pool_data);
+
+        assert!(runner
+            .rescale_virtual_reserve(&admin, platform_config, pool)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rescale_virtual_reserve_wrong_admin() {
+        let (mut runner, _, platform_config, pool) = setup_test(900_000, 1_000_000);
+        let other = Keypair::new();
+        runner.airdrop(&other.pubkey(), 10_000_000_000);
+
+        assert!(runner
+            .rescale_virtual_reserve(&other, platform_config, pool)
+            .is_err());
+    }
+}
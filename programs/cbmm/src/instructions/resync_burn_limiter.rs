@@ -0,0 +1,132 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ResyncBurnLimiter<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Restarts a pool's burn limiter from 3/4 of the platform's current total fees. Pools seed the
+/// limiter once at creation, so after a platform fee change their starting stress no longer
+/// matches the envelope the burn tiers are validated against.
+pub fn resync_burn_limiter(ctx: Context<ResyncBurnLimiter>) -> Result<()> {
+    let platform_config = &ctx.accounts.platform_config;
+    let pool = &mut ctx.accounts.pool;
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+
+    let total_fee_bp = platform_config
+        .pool_creator_fee_bp
+        .checked_add(platform_config.pool_topup_fee_bp)
+        .and_then(|sum| sum.checked_add(platform_config.platform_fee_bp))
+        .ok_or(CbmmError::MathOverflow)?;
+    pool.burn_limiter
+        .resync(Clock::get()?.unix_timestamp, total_fee_bp);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{CbmmPool, UpdatePlatformConfigArgs};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+
+    fn get_pool(runner: &TestRunner, pool: &TestPool) -> CbmmPool {
+        let account = runner.svm.get_account(&pool.pool).unwrap();
+        CbmmPool::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, TestPool) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        runner.set_system_clock(NOW);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        (runner, admin, platform_config, pool)
+    }
+
+    #[test]
+    fn test_resync_burn_limiter_after_fee_change() {
+        let (mut runner, admin, platform_config, pool) = setup_test();
+        let burn_limit_bp_x10k = 90_000 * 10_000;
+        // 3/4 of the 10% total fees the pool was created with
+        let pool_data = get_pool(&runner, &pool);
+        assert_eq!(pool_data.burn_limiter.accumulated_stress_bp_x10k, 75_000);
+        let capacity_before =
+            burn_limit_bp_x10k - pool_data.burn_limiter.accumulated_stress_bp_x10k;
+
+        runner
+            .update_platform_config(
+                &admin,
+                platform_config,
+                UpdatePlatformConfigArgs {
+                    platform_fee_bp: Some(400),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        runner.set_system_clock(NOW + 60);
+        runner
+            .resync_burn_limiter(&admin, platform_config, pool.pool)
+            .unwrap();
+
+        // 3/4 of the new 12% total fees
+        let pool_data = get_pool(&runner, &pool);
+        assert_eq!(pool_data.burn_limiter.accumulated_stress_bp_x10k, 90_000);
+        assert_eq!(pool_data.burn_limiter.last_update_ts, NOW + 60);
+        let capacity_after = burn_limit_bp_x10k - pool_data.burn_limiter.accumulated_stress_bp_x10k;
+        assert_eq!(capacity_before - capacity_after, 15_000);
+    }
+
+    #[test]
+    fn test_resync_burn_limiter_wrong_admin() {
+        let (mut runner, _, platform_config, pool) = setup_test();
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 1_000_000_000);
+        assert!(runner
+            .resync_burn_limiter(&stranger, platform_config, pool.pool)
+            .is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::instructions::execute_burn;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RunBurnSchedule<'info> {
+    pub keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BURN_SCHEDULE_SEED, pool.key().as_ref()],
+        bump = burn_schedule.bump,
+        has_one = pool,
+    )]
+    pub burn_schedule: Account<'info, BurnSchedule>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut, seeds = [
+        USER_BURN_ALLOWANCE_SEED,
+        burn_schedule.authority.as_ref(),
+        platform_config.key().as_ref(),
+        &[burn_schedule.tier_index],
+        platform_config.burn_tiers_updated_at.to_le_bytes().as_ref(),
+    ], bump = user_burn_allowance.bump)]
+    pub user_burn_allowance: Account<'info, UserBurnAllowance>,
+
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Optional burn authority, see `BurnVirtualToken`
+    pub burn_authority: Option<Signer<'info>>,
+}
+
+/// Executes a due scheduled burn on behalf of the schedule authority. Anyone can crank it; the
+/// burn itself goes through the same tier and rate limit checks as `burn_virtual_token`.
+pub fn run_burn_schedule(ctx: Context<RunBurnSchedule>) -> Result<()> {
+    ctx.accounts.platform_config.check_burn_authority(
+        ctx.accounts
+            .burn_authority
+            .as_ref()
+            .map(|authority| authority.key()),
+    )?;
+
+    let burn_schedule = &mut ctx.accounts.burn_schedule;
+    burn_schedule.advance(Clock::get()?.unix_timestamp)?;
+
+    let (event, _) = execute_burn(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.user_burn_allowance,
+        &ctx.accounts.platform_config,
+        burn_schedule.authority,
+        None,
+    )?;
+    emit_cpi!(event);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::CbmmError;
+    use crate::instructions::BurnEvent;
+    use crate::state::CbmmPool;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+    const INTERVAL: i64 = 6 * 3600;
+    const CREATOR_TIER: u8 = 1;
+
+    fn setup_test() -> (TestRunner, Keypair, Keypair, TestPool, Pubkey) {
+        let mut runner = TestRunner::new();
+        let creator = Keypair::new();
+        let keeper = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner.airdrop(&keeper.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&creator, 9);
+        let platform_config = runner.create_platform_config_mock(
+            &creator, quote_mint, 5, 5, 1_000, 20_000, 200, 600, 200, None,
+        );
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            0,
+            500_000,
+            1_000_000,
+            1_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let user_burn_allowance = runner
+            .initialize_user_burn_allowance(&creator, creator.pubkey(), platform_config, true)
+            .unwrap();
+        runner.set_system_clock(NOW);
+        runner
+            .create_burn_schedule(&creator, pool.pool, CREATOR_TIER, INTERVAL)
+            .unwrap();
+        (runner, creator, keeper, pool, user_burn_allowance)
+    }
+
+    #[test]
+    fn test_run_burn_schedule_on_time() {
+        let (mut runner, creator, keeper, pool, user_burn_allowance) = setup_test();
+
+        runner
+            .run_burn_schedule(&keeper, pool.pool, user_burn_allowance, None)
+            .unwrap();
+        let event = runner.last_events::<BurnEvent>().pop().unwrap();
+        assert_eq!(event.burner.to_bytes(), creator.pubkey().to_bytes());
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.base_reserve, 980_000);
+        assert_eq!(
+            runner.get_burn_schedule(pool.pool).unwrap().next_run_ts,
+            NOW + INTERVAL
+        );
+
+        runner.set_system_clock(NOW + INTERVAL);
+        runner
+            .run_burn_schedule(&keeper, pool.pool, user_burn_allowance, None)
+            .unwrap();
+        assert_eq!(
+            runner
+                .get_user_burn_allowance(&user_burn_allowance)
+                .unwrap()
+                .burns_today,
+            2
+        );
+    }
+
+    #[test]
+    fn test_run_burn_schedule_rejects_early_run() {
+        let (mut runner, _, keeper, pool, user_burn_allowance) = setup_test();
+        runner
+            .run_burn_schedule(&keeper, pool.pool, user_burn_allowance, None)
+            .unwrap();
+
+        runner.set_system_clock(NOW + INTERVAL - 1);
+        assert!(runner
+            .run_burn_schedule(&keeper, pool.pool, user_burn_allowance, None)
+            .is_err());
+        assert_eq!(
+            runner.get_burn_schedule(pool.pool).unwrap().next_run_ts,
+            NOW + INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_create_burn_schedule_not_creator() {
+        let (mut runner, _, keeper, pool, _) = setup_test();
+        assert!(runner
+            .create_burn_schedule(&keeper, pool.pool, CREATOR_TIER, INTERVAL)
+            .is_err());
+    }
+
+    #[test]
+    fn test_create_burn_schedule_rejects_anyone_tier() {
+        let (mut runner, creator, _, pool, _) = setup_test();
+        runner.close_burn_schedule(&creator, pool.pool).unwrap();
+        let err = runner
+            .create_burn_schedule(&creator, pool.pool, 0, INTERVAL)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::CreatorMustUsePoolOwnerTier)
+        )));
+    }
+
+    #[test]
+    fn test_close_burn_schedule_refunds_rent() {
+        let (mut runner, creator, keeper, pool, user_burn_allowance) = setup_test();
+        let lamports_before = runner.svm.get_account(&creator.pubkey()).unwrap().lamports;
+
+        runner.close_burn_schedule(&creator, pool.pool).unwrap();
+        assert!(runner.get_burn_schedule(pool.pool).is_none());
+        assert!(runner.svm.get_account(&creator.pubkey()).unwrap().lamports > lamports_before);
+        assert!(runner
+            .run_burn_schedule(&keeper, pool.pool, user_burn_allowance, None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_close_burn_schedule_not_authority() {
+        let (mut runner, _, keeper, pool, _) = setup_test();
+        let err = runner.close_burn_schedule(&keeper, pool.pool).unwrap_err();
+        assert!(err
+            .message
+            .contains(&format!("Custom({})", u32::from(CbmmError::InvalidOwner))));
+        assert!(runner.get_burn_schedule(pool.pool).is_some());
+    }
+}
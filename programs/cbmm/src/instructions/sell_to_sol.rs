@@ -0,0 +1,108 @@
+use crate::errors::CbmmError;
+use crate::instructions::{execute_sell, SellVirtualTokenArgs};
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{
+    close_account, CloseAccount, Mint, TokenAccount, TokenInterface,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SellToSol<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Temporary wSOL account, closed back to the payer at the end of the instruction
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = quote_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program
+    )]
+    pub payer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            payer.key().as_ref()
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+
+    #[account(mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mutable so the trade can add to `lifetime_platform_fees`
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        address = pool.quote_mint @ CbmmError::InvalidMint,
+        constraint = quote_mint.key() == native_mint::ID @ CbmmError::InvalidMint,
+    )]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Same as `sell_virtual_token`, but pays out native SOL by unwrapping the
+/// proceeds from the payer's wSOL account.
+pub fn sell_to_sol(ctx: Context<SellToSol>, args: SellVirtualTokenArgs) -> Result<()> {
+    let event = execute_sell(
+        &mut ctx.accounts.pool,
+        &mut ctx.accounts.virtual_token_account,
+        &mut ctx.accounts.platform_config,
+        &args,
+        ctx.accounts.payer.key(),
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    let pool_account_info = pool.to_account_info();
+    pool.transfer_out(
+        event.quote_output,
+        &pool_account_info,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.pool_ata,
+        &ctx.accounts.payer_ata,
+        &ctx.accounts.token_program,
+    )?;
+
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.payer_ata.to_account_info(),
+            destination: ctx.accounts.payer.to_account_info(),
+            authority: ctx.accounts.payer.to_account_info(),
+        },
+    );
+    close_account(cpi_context)?;
+
+    if ctx.accounts.pool.take_trade_event_slot(Clock::get()?.unix_timestamp) {
+        emit_cpi!(event);
+    } else {
+        emit_cpi!(event.throttled());
+    }
+    Ok(())
+}
@@ -1,4 +1,6 @@
 use crate::errors::CbmmError;
+use crate::instructions::{emit_topup_event, ThrottledTradeEvent};
+use crate::simulate::simulate_sell;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{
@@ -19,22 +21,50 @@ pub struct SellEvent {
 
     pub seller: Pubkey,
     pub pool: Pubkey,
+
+    pub quote_mint: Pubkey,
+    pub base_decimals: u8,
+
+    /// Pool volume totals after this trade, see `CbmmPool::cumulative_quote_volume`
+    pub cumulative_quote_volume: u128,
+    pub cumulative_base_volume: u128,
+
+    /// `memo` of the sell, if any
+    pub source: Option<[u8; 32]>,
+}
+
+impl SellEvent {
+    pub fn throttled(&self) -> ThrottledTradeEvent {
+        ThrottledTradeEvent {
+            pool: self.pool,
+            trader: self.seller,
+            is_buy: false,
+            quote_amount: self.quote_output,
+            base_amount: self.base_input,
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct SellVirtualTokenArgs {
     pub base_amount: u64,
     pub min_quote_amount: u64,
+
+    /// Caller-chosen tag echoed into `SellEvent::source`, e.g. to attribute volume to a frontend
+    pub memo: Option<[u8; 32]>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct SellVirtualToken<'info> {
+    /// The virtual token account owner or its delegate
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    /// Owner's quote ATA, which receives the proceeds also on delegated sells
     #[account(mut,
         associated_token::mint = quote_mint,
-        associated_token::authority = payer,
+        associated_token::authority = virtual_token_account.owner,
         associated_token::token_program = token_program        
     )]
     pub payer_ata: InterfaceAccount<'info, TokenAccount>,
@@ -43,7 +73,7 @@ pub struct SellVirtualToken<'info> {
         seeds = [
             VIRTUAL_TOKEN_ACCOUNT_SEED,
             pool.key().as_ref(),
-            payer.key().as_ref()
+            virtual_token_account.owner.as_ref()
         ],
         bump = virtual_token_account.bump,
     )]
@@ -53,8 +83,9 @@ pub struct SellVirtualToken<'info> {
         seeds = [
             CBMM_POOL_SEED,
             pool.pool_index.to_le_bytes().as_ref(),
-            pool.creator.as_ref(),
+            pool.seed_creator.as_ref(),
             platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
         ],
         bump = pool.bump,
     )]
@@ -67,6 +98,8 @@ pub struct SellVirtualToken<'info> {
     )]
     pub pool_ata: InterfaceAccount<'info, TokenAccount>,
 
+    /// Mutable so the trade can add to `lifetime_platform_fees`
+    #[account(mut)]
     pub platform_config: Account<'info, PlatformConfig>,
 
     #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
@@ -79,17 +112,88 @@ pub fn sell_virtual_token(
     ctx: Context<SellVirtualToken>,
     args: SellVirtualTokenArgs,
 ) -> Result<()> {
-    let pool = &mut ctx.accounts.pool;
     let virtual_token_account = &mut ctx.accounts.virtual_token_account;
-    
+    virtual_token_account.spend(ctx.accounts.payer.key(), args.base_amount)?;
+    let seller = virtual_token_account.owner;
+
+    // Fail clearly, before any state changes, rather than inside the final token transfer
+    let quote_output =
+        simulate_sell(&ctx.accounts.pool, args.base_amount, Clock::get()?.unix_timestamp)?
+            .quote_output;
+    // The locked initial liquidity is never paid out
+    require_gte!(
+        ctx.accounts
+            .pool_ata
+            .amount
+            .saturating_sub(ctx.accounts.pool.locked_quote_reserve),
+        quote_output,
+        CbmmError::InsufficientPoolLiquidity
+    );
+
+    let event = execute_sell(
+        &mut ctx.accounts.pool,
+        virtual_token_account,
+        &mut ctx.accounts.platform_config,
+        &args,
+        seller,
+    )?;
+
+    // Transfer Quote tokens from pool to user
+    let pool = &mut ctx.accounts.pool;
+    let pool_account_info = pool.to_account_info();
+    pool.transfer_out(
+        event.quote_output,
+        &pool_account_info,
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.pool_ata,
+        &ctx.accounts.payer_ata,
+        &ctx.accounts.token_program
+    )?;
+
+    if pool.take_trade_event_slot(Clock::get()?.unix_timestamp) {
+        emit_cpi!(event);
+    } else {
+        emit_cpi!(event.throttled());
+    }
+    Ok(())
+}
+
+/// Applies a sell of `args.base_amount` to the pool and the seller's virtual token account.
+/// Moving the quote output out of the pool ATA is left to the caller.
+pub(crate) fn execute_sell(
+    pool: &mut Account<CbmmPool>,
+    virtual_token_account: &mut Account<VirtualTokenAccount>,
+    platform_config: &mut Account<PlatformConfig>,
+    args: &SellVirtualTokenArgs,
+    seller: Pubkey,
+) -> Result<SellEvent> {
+    require!(!platform_config.paused, CbmmError::PlatformPaused);
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+    pool.check_initial_lock(platform_config.min_initial_lock)?;
+    require!(!virtual_token_account.frozen, CbmmError::AccountFrozen);
     require_gte!(virtual_token_account.balance, args.base_amount, CbmmError::InsufficientVirtualTokenBalance);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now.saturating_sub(virtual_token_account.last_buy_ts) >= pool.sell_lockup_seconds as i64,
+        CbmmError::SellLocked
+    );
     
+    // By default the topup runs after the slippage check below. It moves the reserves for the
+    // next trade only, so `min_quote_amount` is checked against the pre-topup curve that
+    // `simulate_sell` and off-chain quotes use.
+    let topup_first = pool.topup_order.topup_before_swap(false);
+    let mut topup_amount = 0;
+    if topup_first {
+        topup_amount = pool.topup()?;
+        emit_topup_event(pool, topup_amount);
+    }
+
     // Calculate swap
     let swap_result = pool.base_to_quote(args.base_amount)?;
     let gross_output = swap_result.quote_amount;
 
     // Calculate fees on output
-    let net_output = pool.collect_fees(gross_output)?;
+    let (net_output, _) = pool.collect_fees(gross_output, platform_config, now)?;
     require_gt!(net_output, 0, CbmmError::AmountTooSmall);
     require_gte!(
         net_output,
@@ -98,40 +202,46 @@ pub fn sell_virtual_token(
     );
 
     let fees = gross_output - net_output;
-    let topup_amount = pool.topup()?;
+    pool.record_volume(gross_output, args.base_amount)?;
+    if !topup_first {
+        topup_amount = pool.topup()?;
+        emit_topup_event(pool, topup_amount);
+    }
 
     // Update user virtual balance
     virtual_token_account.sub(args.base_amount)?;
+    virtual_token_account.last_sell_ts = now;
+    virtual_token_account.quote_received = virtual_token_account
+        .quote_received
+        .checked_add(net_output)
+        .ok_or(CbmmError::MathOverflow)?;
 
-    // Transfer Quote tokens from pool to user
-    let pool_account_info = pool.to_account_info();
-    pool.transfer_out(
-        net_output,
-        &pool_account_info,
-        &ctx.accounts.quote_mint,
-        &ctx.accounts.pool_ata,
-        &ctx.accounts.payer_ata,
-        &ctx.accounts.token_program
-    )?;
-
-    emit!(SellEvent {
+    Ok(SellEvent {
         base_input: args.base_amount,
         quote_output: net_output,
         fees,
         topup_paid: topup_amount,
         new_base_reserve: pool.base_reserve,
         new_quote_reserve: pool.quote_reserve,
-        seller: ctx.accounts.payer.key(),
-        pool: ctx.accounts.pool.key(),
-    }); 
-    Ok(())
+        seller,
+        pool: pool.key(),
+        quote_mint: pool.quote_mint,
+        base_decimals: pool.base_mint_decimals,
+        cumulative_quote_volume: pool.cumulative_quote_volume,
+        cumulative_base_volume: pool.cumulative_base_volume,
+        source: args.memo,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::state::CbmmPool;
+    use crate::errors::CbmmError;
+    use crate::helpers::{calculate_fees, calculate_sell_output_amount};
+    use crate::instructions::{SellEvent, SellVirtualTokenArgs};
+    use crate::state::{CbmmPool, VirtualTokenAccount};
     use crate::test_utils::{TestPool, TestRunner};
     use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
     use solana_sdk::signature::{Keypair, Signer};
     use solana_sdk::pubkey::Pubkey;
 
@@ -249,6 +359,44 @@ mod tests {
         assert_eq!(vta_data.balance, base_amount - base_sell_amount);
     }
 
+    #[test]
+    fn test_sell_virtual_token_insufficient_pool_liquidity() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 1000);
+
+        // Leave the pool ATA short of the 1800 A net output
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(quote_mint.to_bytes()),
+        );
+        let pool_ata = Pubkey::from(pool_ata.to_bytes());
+        let mut ata_account = runner.svm.get_account(&pool_ata).unwrap();
+        let mut token_account =
+            anchor_spl::token::spl_token::state::Account::unpack(&ata_account.data).unwrap();
+        token_account.amount = 1000;
+        anchor_spl::token::spl_token::state::Account::pack(token_account, &mut ata_account.data)
+            .unwrap();
+        runner.svm.set_account(pool_ata, ata_account).unwrap();
+
+        let err = runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                500,
+                0,
+            )
+            .unwrap_err();
+        // InsufficientPoolLiquidity, not a token program failure
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::InsufficientPoolLiquidity)
+        )));
+    }
+
     #[test]
     fn test_sell_virtual_token_insufficient_balance() {
         let (mut runner, _, another_wallet, pool, payer_ata, quote_mint) = setup_test();
@@ -299,6 +447,90 @@ mod tests {
         assert!(result_sell_wrong_owner.is_err());
     }
 
+    #[test]
+    fn test_sell_virtual_token_event_includes_mint_and_decimals() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 1000);
+
+        runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                500,
+                0,
+            )
+            .unwrap();
+
+        let events = runner.last_events::<SellEvent>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].base_decimals, 6);
+        assert_eq!(events[0].quote_mint.to_bytes(), quote_mint.to_bytes());
+    }
+
+    #[test]
+    fn test_sell_virtual_token_event_reports_cumulative_volume() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 1000);
+
+        let mut quote_sold = 0;
+        for base_amount in [300, 500] {
+            runner.svm.expire_blockhash();
+            runner
+                .sell_virtual_token(
+                    &payer,
+                    payer_ata,
+                    quote_mint,
+                    pool.pool,
+                    virtual_token_account,
+                    base_amount,
+                    0,
+                )
+                .unwrap();
+            let event = runner.last_events::<SellEvent>().pop().unwrap();
+            // Volume counts A before fees
+            quote_sold += (event.quote_output + event.fees) as u128;
+            assert_eq!(event.cumulative_quote_volume, quote_sold);
+        }
+
+        let event = runner.last_events::<SellEvent>().pop().unwrap();
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(event.cumulative_base_volume, 800);
+        assert_eq!(pool_data.cumulative_quote_volume, event.cumulative_quote_volume);
+        assert_eq!(pool_data.cumulative_base_volume, event.cumulative_base_volume);
+    }
+
+    #[test]
+    fn test_sell_virtual_token_memo_echoed_as_source() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 1000);
+        let memo = [7u8; 32];
+
+        runner
+            .sell_virtual_token_with_args(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                SellVirtualTokenArgs {
+                    base_amount: 500,
+                    min_quote_amount: 0,
+                    memo: Some(memo),
+                },
+            )
+            .unwrap();
+
+        let event = runner.last_events::<SellEvent>().pop().unwrap();
+        assert_eq!(event.source, Some(memo));
+    }
+
     #[test]
     fn test_sell_virtual_token_slippage_exceeded() {
         let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
@@ -324,4 +556,208 @@ mod tests {
         );
         assert!(result_sell_slippage.is_err());
     }
+
+    #[test]
+    fn test_sell_virtual_token_output_ignores_pending_topup() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        // Half of the optimal real reserve is missing, with enough buyback fees to cover it
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.quote_reserve = 1000;
+        pool_data.buyback_fees_balance = 1000;
+        pool_data.quote_outstanding_topup = 1000;
+        let (quote_reserve, quote_virtual_reserve, base_reserve) = (
+            pool_data.quote_reserve,
+            pool_data.quote_virtual_reserve,
+            pool_data.base_reserve,
+        );
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 100);
+
+        // Quoted off-chain against the reserves before the topup
+        let gross_output =
+            calculate_sell_output_amount(100, base_reserve, quote_reserve, quote_virtual_reserve)
+                .unwrap();
+        let expected_output =
+            gross_output - calculate_fees(gross_output, 200, 600, 200).unwrap().total_fees_amount();
+
+        // Topping up first would have paid out more, which the slippage check must not see
+        assert!(runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                expected_output + 1,
+            )
+            .is_err());
+        runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                expected_output,
+            )
+            .unwrap();
+
+        let event = runner.last_events::<SellEvent>().pop().unwrap();
+        assert_eq!(event.quote_output, expected_output);
+        assert!(event.topup_paid > 0);
+    }
+
+    #[test]
+    fn test_sell_virtual_token_lockup() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let now = 1682899200;
+        let lockup = 60;
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.sell_lockup_seconds = lockup;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        runner.set_system_clock(now);
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                1,
+            )
+            .unwrap();
+
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert_eq!(vta_data.last_buy_ts, now);
+
+        runner.set_system_clock(now + lockup as i64 - 1);
+        let result_early = runner.sell_virtual_token(
+            &payer,
+            payer_ata,
+            quote_mint,
+            pool.pool,
+            virtual_token_account,
+            vta_data.balance,
+            0,
+        );
+        assert!(result_early.is_err());
+
+        runner.set_system_clock(now + lockup as i64);
+        runner.svm.expire_blockhash();
+        let result_after_lockup = runner.sell_virtual_token(
+            &payer,
+            payer_ata,
+            quote_mint,
+            pool.pool,
+            virtual_token_account,
+            vta_data.balance,
+            0,
+        );
+        assert!(result_after_lockup.is_ok());
+    }
+
+    #[test]
+    fn test_trade_timestamps_track_matching_side() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let now = 1682899200;
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+        let get_vta = |runner: &TestRunner| {
+            let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap()
+        };
+
+        runner.set_system_clock(now);
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                1,
+            )
+            .unwrap();
+        let vta_data = get_vta(&runner);
+        assert_eq!(vta_data.last_buy_ts, now);
+        assert_eq!(vta_data.last_sell_ts, 0);
+
+        runner.set_system_clock(now + 100);
+        runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                vta_data.balance / 2,
+                0,
+            )
+            .unwrap();
+        let vta_data = get_vta(&runner);
+        assert_eq!(vta_data.last_buy_ts, now);
+        assert_eq!(vta_data.last_sell_ts, now + 100);
+    }
+
+    #[test]
+    fn test_sell_virtual_token_tracks_quote_spent_and_received() {
+        let (mut runner, payer, _, pool, payer_ata, quote_mint) = setup_test();
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(payer.pubkey(), pool.pool, 0);
+
+        runner
+            .buy_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                5000,
+                0,
+            )
+            .unwrap();
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert_eq!(vta_data.quote_spent, 5000);
+        assert_eq!(vta_data.quote_received, 0);
+
+        runner
+            .sell_virtual_token(
+                &payer,
+                payer_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                vta_data.balance,
+                0,
+            )
+            .unwrap();
+        let events = runner.last_events::<SellEvent>();
+        let vta_account = runner.svm.get_account(&virtual_token_account).unwrap();
+        let vta_data =
+            VirtualTokenAccount::try_deserialize(&mut vta_account.data.as_slice()).unwrap();
+        assert_eq!(vta_data.quote_spent, 5000);
+        assert_eq!(vta_data.quote_received, events[0].quote_output);
+
+        // Fees are paid both ways, so a round trip is a loss
+        assert!(vta_data.realized_pnl() < 0);
+        assert_eq!(
+            vta_data.realized_pnl(),
+            events[0].quote_output as i128 - 5000
+        );
+    }
 }
@@ -0,0 +1,36 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetAllowUserBurnsArgs {
+    pub allow_user_burns: bool,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowUserBurns<'info> {
+    #[account(address = pool.creator @ CbmmError::InvalidPoolCreator)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Lets the creator restrict burns on their pool to the owner and whitelisted tiers.
+pub fn set_allow_user_burns(
+    ctx: Context<SetAllowUserBurns>,
+    args: SetAllowUserBurnsArgs,
+) -> Result<()> {
+    ctx.accounts.pool.allow_user_burns = args.allow_user_burns;
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetFeeHolidayArgs {
+    /// Unix timestamp until which trades pay no fees (0 = end any holiday now)
+    pub fee_holiday_until: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeHoliday<'info> {
+    #[account(
+        constraint = authority.key() == pool.creator || authority.key() == platform_config.admin
+            @ CbmmError::InvalidOwner,
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(address = pool.platform_config @ CbmmError::InvalidPlatformConfig)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Lets the creator or the platform admin waive all trading fees until `fee_holiday_until`.
+/// Topups keep running from the buyback balance already collected. The creator is limited to
+/// the platform's `max_fee_holiday_seconds`.
+pub fn set_fee_holiday(ctx: Context<SetFeeHoliday>, args: SetFeeHolidayArgs) -> Result<()> {
+    if ctx.accounts.authority.key() != ctx.accounts.platform_config.admin {
+        ctx.accounts
+            .platform_config
+            .validate_fee_holiday(args.fee_holiday_until, Clock::get()?.unix_timestamp)?;
+    }
+    ctx.accounts.pool.fee_holiday_until = args.fee_holiday_until;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::CbmmError;
+    use crate::instructions::BuyEvent;
+    use crate::test_utils::{TestPool, TestRunner};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+
+    fn setup_test() -> (TestRunner, Keypair, Keypair, TestPool, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        let creator = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner.set_system_clock(NOW);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        runner.mint_to(&admin, &quote_mint, creator_ata, 1_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        // Buyback fees are waiting for a topup after burns left the pool below its curve
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            100_000,
+            300_000,
+        );
+        runner.create_associated_token_account(&creator, quote_mint, &pool.pool);
+        runner.mint_tokens(&admin, pool.pool, quote_mint, 100_000);
+        (runner, admin, creator, pool, platform_config, creator_ata, quote_mint)
+    }
+
+    fn buy(
+        runner: &mut TestRunner,
+        creator: &Keypair,
+        pool: &TestPool,
+        creator_ata: Pubkey,
+        quote_mint: Pubkey,
+    ) -> BuyEvent {
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(creator.pubkey(), pool.pool, 0);
+        runner
+            .buy_virtual_token(
+                creator,
+                creator_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                10_000,
+                0,
+            )
+            .unwrap();
+        runner.last_events::<BuyEvent>().pop().unwrap()
+    }
+
+    #[test]
+    fn test_set_fee_holiday_waives_fees_until_it_ends() {
+        let (mut runner, _, creator, pool, platform_config, creator_ata, quote_mint) =
+            setup_test();
+        runner
+            .set_fee_holiday(&creator, pool.pool, platform_config, NOW + 3600)
+            .unwrap();
+
+        let event = buy(&mut runner, &creator, &pool, creator_ata, quote_mint);
+        assert_eq!(event.fees, 0);
+        // The existing buyback balance still tops up the pool
+        assert!(event.topup_paid > 0);
+
+        runner.set_system_clock(NOW + 3600);
+        let event = buy(&mut runner, &creator, &pool, creator_ata, quote_mint);
+        assert_eq!(event.fees, 1000);
+    }
+
+    #[test]
+    fn test_set_fee_holiday_by_admin() {
+        let (mut runner, admin, creator, pool, platform_config, creator_ata, quote_mint) =
+            setup_test();
+        runner
+            .set_fee_holiday(&admin, pool.pool, platform_config, NOW + 3600)
+            .unwrap();
+        assert_eq!(
+            buy(&mut runner, &creator, &pool, creator_ata, quote_mint).fees,
+            0
+        );
+
+        // Ending it early restores the fees
+        runner
+            .set_fee_holiday(&admin, pool.pool, platform_config, 0)
+            .unwrap();
+        assert_eq!(
+            buy(&mut runner, &creator, &pool, creator_ata, quote_mint).fees,
+            1000
+        );
+    }
+
+    #[test]
+    fn test_set_fee_holiday_rejects_over_long_holiday() {
+        let (mut runner, admin, creator, pool, platform_config, creator_ata, quote_mint) =
+            setup_test();
+        // The mocked platform lets creators grant at most a week
+        let err = runner
+            .set_fee_holiday(&creator, pool.pool, platform_config, NOW + 7 * 86400 + 1)
+            .unwrap_err();
+        assert!(err.message.contains(&format!(
+            "Custom({})",
+            u32::from(CbmmError::FeeHolidayTooLong)
+        )));
+        runner
+            .set_fee_holiday(&creator, pool.pool, platform_config, NOW + 7 * 86400)
+            .unwrap();
+
+        // The admin is not bound by the maximum
+        runner.svm.expire_blockhash();
+        runner
+            .set_fee_holiday(&admin, pool.pool, platform_config, i64::MAX)
+            .unwrap();
+        assert_eq!(
+            buy(&mut runner, &creator, &pool, creator_ata, quote_mint).fees,
+            0
+        );
+    }
+
+    #[test]
+    fn test_set_fee_holiday_wrong_authority() {
+        let (mut runner, _, _, pool, platform_config, _, _) = setup_test();
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 10_000_000_000);
+
+        assert!(runner
+            .set_fee_holiday(&stranger, pool.pool, platform_config, NOW + 3600)
+            .is_err());
+    }
+}
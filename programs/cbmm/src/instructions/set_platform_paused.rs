@@ -0,0 +1,196 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetPlatformPausedArgs {
+    pub paused: bool,
+}
+
+#[derive(Accounts)]
+pub struct SetPlatformPaused<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Halts or resumes buys, sells, burns, queue flushes and topup cranks on every pool of the
+/// platform. Fee claims stay open.
+pub fn set_platform_paused(
+    ctx: Context<SetPlatformPaused>,
+    args: SetPlatformPausedArgs,
+) -> Result<()> {
+    ctx.accounts.platform_config.paused = args.paused;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{TestPool, TestRunner};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+
+    fn setup_test() -> (
+        TestRunner,
+        Keypair,
+        Keypair,
+        Pubkey,
+        TestPool,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+    ) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        let user = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        runner.airdrop(&user.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let user_ata = runner.create_associated_token_account(&user, quote_mint, &user.pubkey());
+        runner.mint_to(&admin, &quote_mint, user_ata, 1_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        runner.create_associated_token_account(&admin, quote_mint, &platform_config);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            2000,
+            2000,
+            500,
+            1000,
+            6,
+            200,
+            600,
+            200,
+            100,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&admin, quote_mint, &pool.pool);
+        runner.mint_tokens(&admin, pool.pool, quote_mint, 2100);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(user.pubkey(), pool.pool, 500);
+        (
+            runner,
+            admin,
+            user,
+            platform_config,
+            pool,
+            quote_mint,
+            user_ata,
+            virtual_token_account,
+        )
+    }
+
+    #[test]
+    fn test_set_platform_paused_halts_and_resumes_trading() {
+        let (
+            mut runner,
+            admin,
+            user,
+            platform_config,
+            pool,
+            quote_mint,
+            user_ata,
+            virtual_token_account,
+        ) = setup_test();
+        runner.set_system_clock(NOW);
+        let user_burn_allowance = runner.create_user_burn_allowance_mock(
+            user.pubkey(),
+            user.pubkey(),
+            platform_config,
+            1,
+            NOW - 3600,
+            false,
+            NOW - 3600,
+        );
+        runner
+            .set_platform_paused(&admin, platform_config, true)
+            .unwrap();
+
+        assert!(runner
+            .buy_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                0
+            )
+            .is_err());
+        assert!(runner
+            .sell_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0
+            )
+            .is_err());
+        assert!(runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .is_err());
+
+        runner.svm.expire_blockhash();
+        runner
+            .set_platform_paused(&admin, platform_config, false)
+            .unwrap();
+        runner
+            .buy_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                0,
+            )
+            .unwrap();
+        runner
+            .sell_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0,
+            )
+            .unwrap();
+        runner
+            .burn_virtual_token(&user, pool.pool, user_burn_allowance, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_set_platform_paused_allows_fee_claims() {
+        let (mut runner, admin, _, platform_config, pool, quote_mint, _, _) = setup_test();
+        let admin_ata = runner.create_associated_token_account(&admin, quote_mint, &admin.pubkey());
+        runner
+            .set_platform_paused(&admin, platform_config, true)
+            .unwrap();
+
+        runner
+            .claim_creator_fees(&admin, admin_ata, quote_mint, pool.pool)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_set_platform_paused_wrong_admin() {
+        let (mut runner, _, user, platform_config, _, _, _, _) = setup_test();
+        assert!(runner
+            .set_platform_paused(&user, platform_config, true)
+            .is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetTopupOrderArgs {
+    pub topup_order: TopupOrder,
+}
+
+#[derive(Accounts)]
+pub struct SetTopupOrder<'info> {
+    #[account(address = pool.creator @ CbmmError::InvalidPoolCreator)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+}
+
+/// Lets the creator choose whether trades top up before or after the swap.
+pub fn set_topup_order(ctx: Context<SetTopupOrder>, args: SetTopupOrderArgs) -> Result<()> {
+    ctx.accounts.pool.topup_order = args.topup_order;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::{BuyEvent, SellEvent};
+    use crate::state::{CbmmPool, TopupOrder};
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test(
+        topup_order: Option<TopupOrder>,
+    ) -> (TestRunner, Keypair, TestPool, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&creator, 9);
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        runner.mint_to(&creator, &quote_mint, creator_ata, 1_000_000);
+        let platform_config = runner
+            .create_platform_config_mock(&creator, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            500_000,
+            500_000,
+            500_000,
+            1_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            100_000,
+            300_000,
+        );
+        // Burns left the pool below the optimal curve of V = 1M and A = 1M
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.quote_starting_virtual_reserve = 1_000_000;
+        pool_data.base_starting_total_supply = 1_000_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        runner.create_associated_token_account(&creator, quote_mint, &pool.pool);
+        runner.mint_tokens(&creator, pool.pool, quote_mint, 600_000);
+
+        // None leaves the field as a pool created before it existed reads it
+        if let Some(topup_order) = topup_order {
+            runner
+                .set_topup_order(&creator, pool.pool, topup_order)
+                .unwrap();
+        }
+        (runner, creator, pool, creator_ata, quote_mint)
+    }
+
+    fn buy_output(topup_order: Option<TopupOrder>) -> u64 {
+        let (mut runner, creator, pool, creator_ata, quote_mint) = setup_test(topup_order);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(creator.pubkey(), pool.pool, 0);
+        runner
+            .buy_virtual_token(
+                &creator,
+                creator_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                10_000,
+                0,
+            )
+            .unwrap();
+        let event = runner.last_events::<BuyEvent>().pop().unwrap();
+        assert!(event.topup_paid > 0);
+        event.base_output
+    }
+
+    fn sell_output(topup_order: Option<TopupOrder>) -> u64 {
+        let (mut runner, creator, pool, creator_ata, quote_mint) = setup_test(topup_order);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(creator.pubkey(), pool.pool, 10_000);
+        runner
+            .sell_virtual_token(
+                &creator,
+                creator_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                10_000,
+                0,
+            )
+            .unwrap();
+        let event = runner.last_events::<SellEvent>().pop().unwrap();
+        assert!(event.topup_paid > 0);
+        event.quote_output
+    }
+
+    #[test]
+    fn test_set_topup_order_buy_output() {
+        // Topping up first raises the price the buyer pays
+        assert!(
+            buy_output(Some(TopupOrder::BeforeTrade)) < buy_output(Some(TopupOrder::AfterTrade))
+        );
+    }
+
+    #[test]
+    fn test_set_topup_order_sell_output() {
+        // Topping up first raises the price the seller receives
+        assert!(
+            sell_output(Some(TopupOrder::BeforeTrade)) > sell_output(Some(TopupOrder::AfterTrade))
+        );
+    }
+
+    #[test]
+    fn test_legacy_pool_keeps_original_topup_order() {
+        // Pools created before the field read zero, which tops up before buys and after sells
+        assert!(TopupOrder::default() == TopupOrder::BeforeBuyAfterSell);
+        assert_eq!(buy_output(None), buy_output(Some(TopupOrder::BeforeTrade)));
+        assert_eq!(sell_output(None), sell_output(Some(TopupOrder::AfterTrade)));
+    }
+
+    #[test]
+    fn test_set_topup_order_wrong_creator() {
+        let (mut runner, _, pool, _, _) = setup_test(Some(TopupOrder::BeforeTrade));
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 1_000_000_000);
+        assert!(runner
+            .set_topup_order(&stranger, pool.pool, TopupOrder::AfterTrade)
+            .is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetVtaFrozenArgs {
+    pub frozen: bool,
+}
+
+#[derive(Accounts)]
+pub struct SetVtaFrozen<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            virtual_token_account.owner.as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+}
+
+/// Lets the platform admin stop a single holder from trading on one of the platform's pools.
+pub fn set_vta_frozen(ctx: Context<SetVtaFrozen>, args: SetVtaFrozenArgs) -> Result<()> {
+    ctx.accounts.virtual_token_account.frozen = args.frozen;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{TestPool, TestRunner};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (
+        TestRunner,
+        Keypair,
+        Keypair,
+        TestPool,
+        Pubkey,
+        Pubkey,
+        Pubkey,
+    ) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        let user = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        runner.airdrop(&user.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let user_ata = runner.create_associated_token_account(&user, quote_mint, &user.pubkey());
+        runner.mint_to(&admin, &quote_mint, user_ata, 1_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            2000,
+            2000,
+            500,
+            1000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&admin, quote_mint, &pool.pool);
+        runner.mint_tokens(&admin, pool.pool, quote_mint, 2000);
+        let virtual_token_account =
+            runner.create_virtual_token_account_mock(user.pubkey(), pool.pool, 500);
+        (
+            runner,
+            admin,
+            user,
+            pool,
+            quote_mint,
+            user_ata,
+            virtual_token_account,
+        )
+    }
+
+    #[test]
+    fn test_set_vta_frozen_blocks_trading() {
+        let (mut runner, admin, user, pool, quote_mint, user_ata, virtual_token_account) =
+            setup_test();
+        runner
+            .set_vta_frozen(&admin, pool.pool, virtual_token_account, true)
+            .unwrap();
+
+        assert!(runner
+            .buy_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                0
+            )
+            .is_err());
+        assert!(runner
+            .sell_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0
+            )
+            .is_err());
+
+        runner.svm.expire_blockhash();
+        runner
+            .set_vta_frozen(&admin, pool.pool, virtual_token_account, false)
+            .unwrap();
+        runner
+            .buy_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                1000,
+                0,
+            )
+            .unwrap();
+        runner
+            .sell_virtual_token(
+                &user,
+                user_ata,
+                quote_mint,
+                pool.pool,
+                virtual_token_account,
+                100,
+                0,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_set_vta_frozen_wrong_admin() {
+        let (mut runner, _, user, pool, _, _, virtual_token_account) = setup_test();
+        assert!(runner
+            .set_vta_frozen(&user, pool.pool, virtual_token_account, true)
+            .is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+/// Holder balance at `slot`, captured off-chain from the logs
+#[event]
+pub struct SnapshotEvent {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub slot: u64,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotVta<'info> {
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(
+        seeds = [
+            VIRTUAL_TOKEN_ACCOUNT_SEED,
+            pool.key().as_ref(),
+            virtual_token_account.owner.as_ref(),
+        ],
+        bump = virtual_token_account.bump,
+    )]
+    pub virtual_token_account: Account<'info, VirtualTokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotVtas<'info> {
+    pub pool: Account<'info, CbmmPool>,
+}
+
+fn emit_snapshot(virtual_token_account: &VirtualTokenAccount, slot: u64) {
+    emit!(SnapshotEvent {
+        pool: virtual_token_account.pool,
+        owner: virtual_token_account.owner,
+        balance: virtual_token_account.balance,
+        slot,
+    });
+}
+
+pub fn snapshot_vta(ctx: Context<SnapshotVta>) -> Result<()> {
+    emit_snapshot(&ctx.accounts.virtual_token_account, Clock::get()?.slot);
+    Ok(())
+}
+
+/// Batched `snapshot_vta` over the virtual token accounts passed as `remaining_accounts`.
+pub fn snapshot_vtas<'info>(ctx: Context<'_, '_, 'info, 'info, SnapshotVtas<'info>>) -> Result<()> {
+    let slot = Clock::get()?.slot;
+    for account in ctx.remaining_accounts {
+        let virtual_token_account = Account::<VirtualTokenAccount>::try_from(account)?;
+        require_keys_eq!(
+            virtual_token_account.pool,
+            ctx.accounts.pool.key(),
+            CbmmError::InvalidVirtualTokenAccount
+        );
+        emit_snapshot(&virtual_token_account, slot);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::SnapshotEvent;
+    use crate::state::VirtualTokenAccount;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test() -> (TestRunner, Keypair, TestPool, Vec<Pubkey>) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            0,
+            0,
+        );
+        let virtual_token_accounts = [100, 0, 12345]
+            .into_iter()
+            .map(|balance| {
+                runner.create_virtual_token_account_mock(Pubkey::new_unique(), pool.pool, balance)
+            })
+            .collect();
+        (runner, payer, pool, virtual_token_accounts)
+    }
+
+    fn assert_matches_live_balance(
+        runner: &TestRunner,
+        event: &SnapshotEvent,
+        virtual_token_account: &Pubkey,
+    ) {
+        let account = runner.svm.get_account(virtual_token_account).unwrap();
+        let data = VirtualTokenAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(event.owner, data.owner);
+        assert_eq!(event.pool, data.pool);
+        assert_eq!(event.balance, data.balance);
+        assert_eq!(
+            event.slot,
+            runner.svm.get_sysvar::<solana_sdk::clock::Clock>().slot
+        );
+    }
+
+    #[test]
+    fn test_snapshot_vta() {
+        let (mut runner, payer, pool, virtual_token_accounts) = setup_test();
+        runner
+            .snapshot_vta(&payer, pool.pool, virtual_token_accounts[2])
+            .unwrap();
+        let events = runner.last_events::<SnapshotEvent>();
+        assert_eq!(events.len(), 1);
+        assert_matches_live_balance(&runner, &events[0], &virtual_token_accounts[2]);
+    }
+
+    #[test]
+    fn test_snapshot_vtas() {
+        let (mut runner, payer, pool, virtual_token_accounts) = setup_test();
+        runner
+            .snapshot_vtas(&payer, pool.pool, &virtual_token_accounts)
+            .unwrap();
+        let events = runner.last_events::<SnapshotEvent>();
+        assert_eq!(events.len(), virtual_token_accounts.len());
+        for (event, virtual_token_account) in events.iter().zip(&virtual_token_accounts) {
+            assert_matches_live_balance(&runner, event, virtual_token_account);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_vtas_rejects_other_pool() {
+        let (mut runner, payer, pool, _) = setup_test();
+        let other_pool_vta =
+            runner.create_virtual_token_account_mock(Pubkey::new_unique(), Pubkey::new_unique(), 1);
+        assert!(runner
+            .snapshot_vtas(&payer, pool.pool, &[other_pool_vta])
+            .is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+pub struct SweepTreasuries<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+        token::mint = quote_mint,
+        token::token_program = token_program,
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Claims platform fees from pools of several platforms into one treasury. `remaining_accounts`
+/// holds `[platform_config, pool, pool_ata]` triples, with the pool and its ATA writable. The
+/// signer must be the admin of every platform and the treasury must belong to each platform's
+/// fee recipient. Pools with no accrued platform fees are skipped.
+pub fn sweep_treasuries<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SweepTreasuries<'info>>,
+) -> Result<()> {
+    require!(
+        !ctx.remaining_accounts.is_empty() && ctx.remaining_accounts.len().is_multiple_of(3),
+        CbmmError::InvalidSweepAccounts
+    );
+
+    let admin = ctx.accounts.admin.key();
+    let quote_mint = ctx.accounts.quote_mint.key();
+    for triple in ctx.remaining_accounts.chunks(3) {
+        let platform_config = Account::<PlatformConfig>::try_from(&triple[0])?;
+        require_keys_eq!(
+            platform_config.admin,
+            admin,
+            CbmmError::InvalidPlatformAdmin
+        );
+        require_keys_eq!(
+            ctx.accounts.treasury.owner,
+            platform_config.platform_fee_recipient(),
+            CbmmError::InvalidFeeRecipient
+        );
+
+        let mut pool = Account::<CbmmPool>::try_from(&triple[1])?;
+        let pool_index_bytes = pool.pool_index.to_le_bytes();
+        let expected_pool = Pubkey::create_program_address(
+            &[
+                CBMM_POOL_SEED,
+                pool_index_bytes.as_ref(),
+                pool.seed_creator.as_ref(),
+                platform_config.key().as_ref(),
+                pool.quote_mint.as_ref(),
+                &[pool.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| CbmmError::InvalidPlatformConfig)?;
+        require_keys_eq!(pool.key(), expected_pool, CbmmError::InvalidPlatformConfig);
+        require_keys_eq!(pool.quote_mint, quote_mint, CbmmError::InvalidMint);
+
+        let amount = pool.platform_fees_balance;
+        if amount == 0 {
+            continue;
+        }
+        let pool_ata = InterfaceAccount::<TokenAccount>::try_from(&triple[2])?;
+        require_keys_eq!(pool_ata.owner, pool.key(), CbmmError::InvalidSweepAccounts);
+        require_keys_eq!(pool_ata.mint, quote_mint, CbmmError::InvalidMint);
+
+        pool.platform_fees_balance = 0;
+        let pool_account_info = pool.to_account_info();
+        pool.transfer_out(
+            amount,
+            &pool_account_info,
+            &ctx.accounts.quote_mint,
+            &pool_ata,
+            &ctx.accounts.treasury,
+            &ctx.accounts.token_program,
+        )?;
+        pool.exit(ctx.program_id)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{CbmmPool, PlatformConfig};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn token_balance(runner: &TestRunner, ata: &Pubkey) -> u64 {
+        let account = runner.svm.get_account(ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    fn pool_data(runner: &TestRunner, pool: &Pubkey) -> CbmmPool {
+        let account = runner.svm.get_account(pool).unwrap();
+        CbmmPool::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Platform created by a fresh creator but run by `admin`, with one pool holding
+    /// `platform_fees_balance`
+    fn create_platform(
+        runner: &mut TestRunner,
+        mint_authority: &Keypair,
+        quote_mint: Pubkey,
+        admin: Pubkey,
+        platform_fees_balance: u64,
+    ) -> (Pubkey, Pubkey) {
+        let creator = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        let platform_config = runner
+            .create_platform_config_mock(&creator, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let account = runner.svm.get_account(&platform_config).unwrap();
+        let mut platform_config_data =
+            PlatformConfig::try_deserialize(&mut account.data.as_slice()).unwrap();
+        platform_config_data.admin = anchor_lang::prelude::Pubkey::new_from_array(admin.to_bytes());
+        platform_config_data.fee_recipient = platform_config_data.admin;
+        runner.put_account_on_chain(&platform_config, platform_config_data);
+
+        let pool = runner
+            .create_pool_mock(
+                &creator,
+                platform_config,
+                quote_mint,
+                0,
+                1_000_000,
+                2_000_000,
+                2_000_000,
+                6,
+                200,
+                600,
+                200,
+                0,
+                0,
+                0,
+            )
+            .pool;
+        let mut data = pool_data(runner, &pool);
+        data.platform_fees_balance = platform_fees_balance;
+        runner.put_account_on_chain(&pool, data);
+        runner.create_associated_token_account(&creator, quote_mint, &pool);
+        runner.mint_tokens(mint_authority, pool, quote_mint, platform_fees_balance);
+        (platform_config, pool)
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let treasury = runner.create_associated_token_account(&admin, quote_mint, &admin.pubkey());
+        (runner, admin, quote_mint, treasury)
+    }
+
+    #[test]
+    fn test_sweep_treasuries_two_platforms() {
+        let (mut runner, admin, quote_mint, treasury) = setup_test();
+        let first = create_platform(&mut runner, &admin, quote_mint, admin.pubkey(), 500);
+        let second = create_platform(&mut runner, &admin, quote_mint, admin.pubkey(), 700);
+        let empty = create_platform(&mut runner, &admin, quote_mint, admin.pubkey(), 0);
+
+        runner
+            .sweep_treasuries(&admin, treasury, quote_mint, &[first, second, empty])
+            .unwrap();
+
+        assert_eq!(token_balance(&runner, &treasury), 1200);
+        assert_eq!(pool_data(&runner, &first.1).platform_fees_balance, 0);
+        assert_eq!(pool_data(&runner, &second.1).platform_fees_balance, 0);
+    }
+
+    #[test]
+    fn test_sweep_treasuries_foreign_platform() {
+        let (mut runner, admin, quote_mint, treasury) = setup_test();
+        let own = create_platform(&mut runner, &admin, quote_mint, admin.pubkey(), 500);
+        let foreign = create_platform(
+            &mut runner,
+            &admin,
+            quote_mint,
+            Keypair::new().pubkey(),
+            700,
+        );
+
+        assert!(runner
+            .sweep_treasuries(&admin, treasury, quote_mint, &[own, foreign])
+            .is_err());
+        assert_eq!(token_balance(&runner, &treasury), 0);
+    }
+}
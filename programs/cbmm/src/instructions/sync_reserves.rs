@@ -0,0 +1,176 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event]
+pub struct ReservesSynced {
+    pub old_quote_reserve: u64,
+    pub new_quote_reserve: u64,
+
+    pub pool: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            platform_config.key().as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Resets `quote_reserve` to whatever the pool ATA holds beyond the fee balances and the locked
+/// initial liquidity, for pools whose bookkeeping drifted from the token account, e.g. after a
+/// manual recovery. Fails if the ATA does not even cover those balances.
+pub fn sync_reserves(ctx: Context<SyncReserves>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let old_quote_reserve = pool.quote_reserve;
+    let new_quote_reserve = ctx
+        .accounts
+        .pool_ata
+        .amount
+        .checked_sub(pool.creator_fees_balance)
+        .and_then(|amount| amount.checked_sub(pool.buyback_fees_balance))
+        .and_then(|amount| amount.checked_sub(pool.platform_fees_balance))
+        .and_then(|amount| amount.checked_sub(pool.locked_quote_reserve))
+        .ok_or(CbmmError::Underflow)?;
+
+    pool.quote_reserve = new_quote_reserve;
+
+    emit!(ReservesSynced {
+        old_quote_reserve,
+        new_quote_reserve,
+        pool: pool.key(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::ReservesSynced;
+    use crate::state::CbmmPool;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn get_pool(runner: &TestRunner, pool: &Pubkey) -> CbmmPool {
+        let account = runner.svm.get_account(pool).unwrap();
+        CbmmPool::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Pool holding 1_000 A of fees next to `quote_reserve`, with `ata_amount` in its ATA
+    fn setup_test(
+        quote_reserve: u64,
+        ata_amount: u64,
+    ) -> (TestRunner, Keypair, Pubkey, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &admin,
+            platform_config,
+            quote_mint,
+            quote_reserve,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            200,
+            600,
+            0,
+        );
+        let mut pool_data = get_pool(&runner, &pool.pool);
+        pool_data.platform_fees_balance = 200;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+
+        runner.create_associated_token_account(&admin, quote_mint, &pool.pool);
+        runner.mint_tokens(&admin, pool.pool, quote_mint, ata_amount);
+
+        (runner, admin, platform_config, pool.pool, quote_mint)
+    }
+
+    #[test]
+    fn test_sync_reserves_corrects_drift() {
+        // 5_000 A reached the ATA without passing through a trade
+        let (mut runner, admin, platform_config, pool, quote_mint) = setup_test(10_000, 16_000);
+
+        runner
+            .sync_reserves(&admin, platform_config, pool, quote_mint)
+            .unwrap();
+
+        assert_eq!(get_pool(&runner, &pool).quote_reserve, 15_000);
+        let events = runner.last_events::<ReservesSynced>();
+        assert_eq!(events[0].old_quote_reserve, 10_000);
+        assert_eq!(events[0].new_quote_reserve, 15_000);
+    }
+
+    #[test]
+    fn test_sync_reserves_excludes_locked_liquidity() {
+        let (mut runner, admin, platform_config, pool, quote_mint) = setup_test(10_000, 16_000);
+        let mut pool_data = get_pool(&runner, &pool);
+        pool_data.locked_quote_reserve = 3_000;
+        runner.put_account_on_chain(&pool, pool_data);
+
+        runner
+            .sync_reserves(&admin, platform_config, pool, quote_mint)
+            .unwrap();
+
+        assert_eq!(get_pool(&runner, &pool).quote_reserve, 12_000);
+    }
+
+    #[test]
+    fn test_sync_reserves_ata_below_fees() {
+        let (mut runner, admin, platform_config, pool, quote_mint) = setup_test(10_000, 999);
+
+        assert!(runner
+            .sync_reserves(&admin, platform_config, pool, quote_mint)
+            .is_err());
+        assert_eq!(get_pool(&runner, &pool).quote_reserve, 10_000);
+    }
+
+    #[test]
+    fn test_sync_reserves_wrong_admin() {
+        let (mut runner, _, platform_config, pool, quote_mint) = setup_test(10_000, 16_000);
+        let other = Keypair::new();
+        runner.airdrop(&other.pubkey(), 10_000_000_000);
+
+        assert!(runner
+            .sync_reserves(&other, platform_config, pool, quote_mint)
+            .is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::errors::CbmmError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[event]
+pub struct TipEvent {
+    pub pool: Pubkey,
+    pub tipper: Pubkey,
+    /// A actually received by the pool ATA
+    pub amount: u64,
+    pub creator_fees_balance: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TipCreatorArgs {
+    /// Amount of Mint A to send, including decimals
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct TipCreator<'info> {
+    pub tipper: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = tipper,
+        associated_token::token_program = token_program
+    )]
+    pub tipper_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Sends A straight to the pool creator's fee balance, claimable through `claim_creator_fees`.
+/// Unlike `topup_with_deposit` the tip never reaches the reserves.
+pub fn tip_creator(ctx: Context<TipCreator>, args: TipCreatorArgs) -> Result<()> {
+    require_gt!(args.amount, 0, CbmmError::AmountTooSmall);
+
+    // As with buys, a transfer fee on the mint may deliver less than requested
+    let balance_before = ctx.accounts.pool_ata.amount;
+    let cpi_accounts = TransferChecked {
+        mint: ctx.accounts.quote_mint.to_account_info(),
+        from: ctx.accounts.tipper_ata.to_account_info(),
+        to: ctx.accounts.pool_ata.to_account_info(),
+        authority: ctx.accounts.tipper.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(cpi_context, args.amount, ctx.accounts.quote_mint.decimals)?;
+    ctx.accounts.pool_ata.reload()?;
+    let amount = ctx
+        .accounts
+        .pool_ata
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(CbmmError::Underflow)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.creator_fees_balance = pool
+        .creator_fees_balance
+        .checked_add(amount)
+        .ok_or(CbmmError::MathOverflow)?;
+    emit!(TipEvent {
+        pool: pool.key(),
+        tipper: ctx.accounts.tipper.key(),
+        amount,
+        creator_fees_balance: pool.creator_fees_balance,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::TipEvent;
+    use crate::state::CbmmPool;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn token_balance(runner: &TestRunner, ata: &Pubkey) -> u64 {
+        let account = runner.svm.get_account(ata).unwrap();
+        anchor_spl::token::spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Keypair, TestPool, Pubkey, Pubkey) {
+        let mut runner = TestRunner::new();
+        let creator = Keypair::new();
+        let tipper = Keypair::new();
+        runner.airdrop(&creator.pubkey(), 10_000_000_000);
+        runner.airdrop(&tipper.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&creator, 9);
+        let tipper_ata =
+            runner.create_associated_token_account(&tipper, quote_mint, &tipper.pubkey());
+        runner.mint_to(&creator, &quote_mint, tipper_ata, 1_000_000);
+        let platform_config = runner
+            .create_platform_config_mock(&creator, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &creator,
+            platform_config,
+            quote_mint,
+            0,
+            1_000_000,
+            2_000_000,
+            2_000_000,
+            6,
+            200,
+            600,
+            200,
+            300,
+            0,
+            0,
+        );
+        runner.create_associated_token_account(&creator, quote_mint, &pool.pool);
+        runner.mint_tokens(&creator, pool.pool, quote_mint, 300);
+        (runner, creator, tipper, pool, tipper_ata, quote_mint)
+    }
+
+    #[test]
+    fn test_tip_creator_is_claimable() {
+        let (mut runner, creator, tipper, pool, tipper_ata, quote_mint) = setup_test();
+
+        runner
+            .tip_creator(&tipper, pool.pool, tipper_ata, 5000)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.creator_fees_balance, 5300);
+        // The reserves are untouched
+        assert_eq!(pool_data.quote_reserve, 0);
+        assert_eq!(pool_data.buyback_fees_balance, 0);
+        let event = runner.last_events::<TipEvent>().pop().unwrap();
+        assert_eq!(event.amount, 5000);
+        assert_eq!(event.tipper.to_bytes(), tipper.pubkey().to_bytes());
+
+        let creator_ata =
+            runner.create_associated_token_account(&creator, quote_mint, &creator.pubkey());
+        runner
+            .claim_creator_fees(&creator, creator_ata, quote_mint, pool.pool)
+            .unwrap();
+        assert_eq!(token_balance(&runner, &creator_ata), 5300);
+    }
+
+    #[test]
+    fn test_tip_creator_zero_amount() {
+        let (mut runner, _, tipper, pool, tipper_ata, _) = setup_test();
+        assert!(runner
+            .tip_creator(&tipper, pool.pool, tipper_ata, 0)
+            .is_err());
+    }
+}
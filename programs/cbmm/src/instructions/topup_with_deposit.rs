@@ -0,0 +1,177 @@
+use crate::errors::CbmmError;
+use crate::instructions::emit_topup_event;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[event]
+pub struct TopupDepositEvent {
+    pub pool: Pubkey,
+    pub depositor: Pubkey,
+    /// A actually received by the pool ATA
+    pub amount: u64,
+    pub quote_reserve: u64,
+    pub quote_virtual_reserve: u64,
+}
+
+#[derive(Accounts)]
+pub struct TopupWithDeposit<'info> {
+    pub depositor: Signer<'info>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = depositor,
+        associated_token::token_program = token_program
+    )]
+    pub depositor_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            CBMM_POOL_SEED,
+            pool.pool_index.to_le_bytes().as_ref(),
+            pool.seed_creator.as_ref(),
+            pool.platform_config.as_ref(),
+            pool.quote_mint.as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, CbmmPool>,
+
+    #[account(mut,
+        associated_token::mint = quote_mint,
+        associated_token::authority = pool,
+        associated_token::token_program = token_program
+    )]
+    pub pool_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = pool.quote_mint @ CbmmError::InvalidMint)]
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Lets anyone donate the A the buyback fees cannot cover, so the pool returns to its optimal
+/// curve instead of running on a recomputed, lower virtual reserve. Fees are spent first and
+/// only the remaining shortfall is pulled from the depositor.
+pub fn topup_with_deposit(ctx: Context<TopupWithDeposit>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+    let real_topup_amount = pool.topup()?;
+    emit_topup_event(pool, real_topup_amount);
+    let deposit_amount = pool.topup_deposit_amount()?;
+    require_gt!(deposit_amount, 0, CbmmError::AmountTooSmall);
+
+    // As with buys, a transfer fee on the mint may deliver less than requested
+    let balance_before = ctx.accounts.pool_ata.amount;
+    let cpi_accounts = TransferChecked {
+        mint: ctx.accounts.quote_mint.to_account_info(),
+        from: ctx.accounts.depositor_ata.to_account_info(),
+        to: ctx.accounts.pool_ata.to_account_info(),
+        authority: ctx.accounts.depositor.to_account_info(),
+    };
+    let cpi_context = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+    transfer_checked(
+        cpi_context,
+        deposit_amount,
+        ctx.accounts.quote_mint.decimals,
+    )?;
+    ctx.accounts.pool_ata.reload()?;
+    let amount = ctx
+        .accounts
+        .pool_ata
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(CbmmError::Underflow)?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.deposit_topup(amount)?;
+    emit!(TopupDepositEvent {
+        pool: pool.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount,
+        quote_reserve: pool.quote_reserve,
+        quote_virtual_reserve: pool.quote_virtual_reserve,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::TopupDepositEvent;
+    use crate::state::CbmmPool;
+    use crate::test_utils::{TestPool, TestRunner};
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    fn setup_test(buyback_fees_balance: u64) -> (TestRunner, Keypair, TestPool, Pubkey) {
+        let mut runner = TestRunner::new();
+        let payer = Keypair::new();
+        runner.airdrop(&payer.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&payer, 9);
+        let payer_ata = runner.create_associated_token_account(&payer, quote_mint, &payer.pubkey());
+        runner.mint_to(&payer, &quote_mint, payer_ata, 1_000_000);
+        let platform_config =
+            runner.create_platform_config_mock(&payer, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        let pool = runner.create_pool_mock(
+            &payer,
+            platform_config,
+            quote_mint,
+            500_000,
+            500_000,
+            500_000,
+            1_000_000,
+            6,
+            200,
+            600,
+            200,
+            0,
+            buyback_fees_balance,
+            300_000,
+        );
+        // The optimal curve for the full supply needs V = 1M and A = 1M, twice the current reserves
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let mut pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        pool_data.quote_starting_virtual_reserve = 1_000_000;
+        pool_data.base_starting_total_supply = 1_000_000;
+        runner.put_account_on_chain(&pool.pool, pool_data);
+        runner.create_associated_token_account(&payer, quote_mint, &pool.pool);
+        runner.mint_tokens(
+            &payer,
+            pool.pool,
+            quote_mint,
+            500_000 + buyback_fees_balance,
+        );
+        (runner, payer, pool, payer_ata)
+    }
+
+    #[test]
+    fn test_topup_with_deposit_restores_optimal_reserves() {
+        let (mut runner, payer, pool, payer_ata) = setup_test(200_000);
+
+        runner
+            .topup_with_deposit(&payer, pool.pool, payer_ata)
+            .unwrap();
+
+        let pool_account = runner.svm.get_account(&pool.pool).unwrap();
+        let pool_data = CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        assert_eq!(pool_data.buyback_fees_balance, 0);
+        assert_eq!(pool_data.quote_reserve, 1_000_000);
+        assert_eq!(pool_data.quote_virtual_reserve, 1_000_000);
+        assert_eq!(pool_data.quote_outstanding_topup, 0);
+
+        let event = runner.last_events::<TopupDepositEvent>().pop().unwrap();
+        assert_eq!(event.amount, 300_000);
+        assert_eq!(event.quote_virtual_reserve, 1_000_000);
+    }
+
+    #[test]
+    fn test_topup_with_deposit_nothing_missing() {
+        let (mut runner, payer, pool, payer_ata) = setup_test(500_000);
+        assert!(runner
+            .topup_with_deposit(&payer, pool.pool, payer_ata)
+            .is_err());
+    }
+}
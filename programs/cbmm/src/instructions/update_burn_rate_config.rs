@@ -0,0 +1,154 @@
+use crate::errors::CbmmError;
+use crate::helpers::BurnRateConfig;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UpdateBurnRateConfigArgs {
+    pub burn_limit_bp_x100: u64,
+    pub burn_min_bp_x100: u64,
+    pub decay_rate_per_sec_bp_x100: u64,
+}
+
+#[derive(Accounts)]
+pub struct UpdateBurnRateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PLATFORM_CONFIG_SEED, platform_config.creator.as_ref()],
+        has_one = admin @ CbmmError::InvalidPlatformAdmin,
+        bump = platform_config.bump,
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Replaces the platform's burn rate limiter settings, checked against the current fees and
+/// tiers. A new decay rate changes how fast allowances refill, so it also invalidates every
+/// existing allowance the way a `burn_tiers` change does.
+pub fn update_burn_rate_config(
+    ctx: Context<UpdateBurnRateConfig>,
+    args: UpdateBurnRateConfigArgs,
+) -> Result<()> {
+    let platform_config = &mut ctx.accounts.platform_config;
+    // With a timelock, changes go through propose_config_change/apply_config_change instead
+    require_eq!(
+        platform_config.config_change_delay_seconds,
+        0,
+        CbmmError::ConfigChangeTimelocked
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+    if args.decay_rate_per_sec_bp_x100
+        != platform_config.burn_rate_config.decay_rate_per_sec_bp_x100
+    {
+        require_gt!(
+            now.saturating_sub(3600),
+            platform_config.burn_tiers_updated_at,
+            CbmmError::BurnTiersUpdatedTooRecently
+        );
+        platform_config.touch_burn_tiers_updated_at(now);
+    }
+    platform_config.burn_rate_config = BurnRateConfig::new(
+        args.burn_limit_bp_x100,
+        args.burn_min_bp_x100,
+        args.decay_rate_per_sec_bp_x100,
+    );
+
+    platform_config.validate_fees_and_burn_config()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state::PlatformConfig;
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+
+    const NOW: i64 = 1682899200;
+
+    fn get_platform_config(runner: &TestRunner, platform_config: &Pubkey) -> PlatformConfig {
+        let account = runner.svm.get_account(platform_config).unwrap();
+        PlatformConfig::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    fn setup_test() -> (TestRunner, Keypair, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config =
+            runner.create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 200, 600, 200, None);
+        runner.set_system_clock(NOW);
+        (runner, admin, platform_config)
+    }
+
+    #[test]
+    fn test_update_burn_rate_config_same_decay_keeps_allowances() {
+        let (mut runner, admin, platform_config) = setup_test();
+        runner
+            .update_burn_rate_config(&admin, platform_config, 80_000, 20, 50)
+            .unwrap();
+
+        let platform_config_data = get_platform_config(&runner, &platform_config);
+        assert_eq!(
+            platform_config_data.burn_rate_config.burn_limit_bp_x100,
+            80_000
+        );
+        assert_eq!(platform_config_data.burn_rate_config.burn_min_bp_x100, 20);
+        assert_eq!(platform_config_data.burn_tiers_updated_at, 0);
+    }
+
+    #[test]
+    fn test_update_burn_rate_config_new_decay_invalidates_allowances() {
+        let (mut runner, admin, platform_config) = setup_test();
+        runner
+            .update_burn_rate_config(&admin, platform_config, 80_000, 10, 60)
+            .unwrap();
+
+        let platform_config_data = get_platform_config(&runner, &platform_config);
+        assert_eq!(
+            platform_config_data
+                .burn_rate_config
+                .decay_rate_per_sec_bp_x100,
+            60
+        );
+        assert_eq!(platform_config_data.burn_tiers_updated_at, NOW);
+
+        // Another decay change has to wait, as with burn tiers
+        runner.svm.expire_blockhash();
+        assert!(runner
+            .update_burn_rate_config(&admin, platform_config, 80_000, 10, 70)
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_burn_rate_config_invalid() {
+        let (mut runner, admin, platform_config) = setup_test();
+        // The limit must stay below total fees (100_000)
+        assert!(runner
+            .update_burn_rate_config(&admin, platform_config, 100_000, 10, 50)
+            .is_err());
+        // Decay faster than a 15 minute recovery (80_000 / 900 = 88)
+        assert!(runner
+            .update_burn_rate_config(&admin, platform_config, 80_000, 10, 200)
+            .is_err());
+
+        let platform_config_data = get_platform_config(&runner, &platform_config);
+        assert_eq!(
+            platform_config_data.burn_rate_config.burn_limit_bp_x100,
+            90_000
+        );
+    }
+
+    #[test]
+    fn test_update_burn_rate_config_wrong_admin() {
+        let (mut runner, _, platform_config) = setup_test();
+        let stranger = Keypair::new();
+        runner.airdrop(&stranger.pubkey(), 1_000_000_000);
+        assert!(runner
+            .update_burn_rate_config(&stranger, platform_config, 80_000, 10, 50)
+            .is_err());
+    }
+}
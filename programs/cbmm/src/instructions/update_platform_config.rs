@@ -3,18 +3,6 @@ use crate::helpers::BurnRateConfig;
 use crate::state::*;
 use anchor_lang::prelude::*;
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct UpdatePlatformConfigArgs {
-    pub pool_creator_fee_bp: Option<u16>,
-    pub pool_topup_fee_bp: Option<u16>,
-    pub platform_fee_bp: Option<u16>,
-    pub burn_authority: Option<Option<Pubkey>>,
-    pub burn_limit_bp_x100: Option<u64>,
-    pub burn_min_bp_x100: Option<u64>,
-    pub burn_decay_rate_per_sec_bp_x100: Option<u64>,
-    pub burn_tiers: Option<Vec<BurnTier>>,
-}
-
 #[derive(Accounts)]
 pub struct UpdatePlatformConfig<'info> {
     #[account(mut)]
@@ -34,8 +22,20 @@ pub fn update_platform_config(
     args: UpdatePlatformConfigArgs,
 ) -> Result<()> {
     let platform_config = &mut ctx.accounts.platform_config;
-    let now = Clock::get()?.unix_timestamp;
+    // With a timelock, changes go through propose_config_change/apply_config_change instead
+    require_eq!(
+        platform_config.config_change_delay_seconds,
+        0,
+        CbmmError::ConfigChangeTimelocked
+    );
+    apply_platform_config_update(platform_config, args, Clock::get()?.unix_timestamp)
+}
 
+pub(crate) fn apply_platform_config_update(
+    platform_config: &mut PlatformConfig,
+    args: UpdatePlatformConfigArgs,
+    now: i64,
+) -> Result<()> {
     // Check if burn_tiers are being updated, and if so, verify they weren't updated in the last hour
     if args.burn_tiers.is_some() {
         let one_hour_ago = now.saturating_sub(3600);
@@ -56,12 +56,67 @@ pub fn update_platform_config(
     if let Some(platform_fee_bp) = args.platform_fee_bp {
         platform_config.platform_fee_bp = platform_fee_bp;
     }
+    if let Some(keeper_reward_bp) = args.keeper_reward_bp {
+        platform_config.keeper_reward_bp = keeper_reward_bp;
+    }
+    if let Some(referral_fee_bp) = args.referral_fee_bp {
+        platform_config.referral_fee_bp = referral_fee_bp;
+    }
+    if let Some(require_no_freeze_authority) = args.require_no_freeze_authority {
+        platform_config.require_no_freeze_authority = require_no_freeze_authority;
+    }
     if let Some(burn_authority) = args.burn_authority {
         platform_config.burn_authority = burn_authority;
     }
+    if let Some(migration_amm) = args.migration_amm {
+        platform_config.migration_amm = migration_amm;
+    }
+    if let Some(burn_reset_interval_seconds) = args.burn_reset_interval_seconds {
+        platform_config.burn_reset_interval_seconds = burn_reset_interval_seconds;
+    }
+    if let Some(config_change_delay_seconds) = args.config_change_delay_seconds {
+        platform_config.config_change_delay_seconds = config_change_delay_seconds;
+    }
+    if let Some(fee_recipient) = args.fee_recipient {
+        platform_config.fee_recipient = fee_recipient;
+    }
+    if let Some(daily_burn_envelope_bp_x100) = args.daily_burn_envelope_bp_x100 {
+        platform_config.daily_burn_envelope_bp_x100 = daily_burn_envelope_bp_x100;
+    }
+    if let Some(min_initial_lock) = args.min_initial_lock {
+        platform_config.min_initial_lock = min_initial_lock;
+    }
+    if let Some(allowed_quote_mints) = args.allowed_quote_mints {
+        platform_config.allowed_quote_mints = allowed_quote_mints;
+    }
+    if let Some(min_topup_fee_bp) = args.min_topup_fee_bp {
+        require_gte!(
+            min_topup_fee_bp,
+            PlatformConfig::MIN_TOPUP_FEE_BP,
+            CbmmError::InvalidFeeBasisPoints
+        );
+        platform_config.min_topup_fee_bp = min_topup_fee_bp;
+    }
+    if let Some(max_fee_holiday_seconds) = args.max_fee_holiday_seconds {
+        require_gte!(
+            PlatformConfig::MAX_FEE_HOLIDAY_SECONDS,
+            max_fee_holiday_seconds,
+            CbmmError::FeeHolidayTooLong
+        );
+        platform_config.max_fee_holiday_seconds = max_fee_holiday_seconds;
+    }
+    if let Some(max_event_interval_seconds) = args.max_event_interval_seconds {
+        require_gte!(
+            PlatformConfig::MAX_EVENT_INTERVAL_SECONDS,
+            max_event_interval_seconds,
+            CbmmError::EventIntervalTooLong
+        );
+        platform_config.max_event_interval_seconds = max_event_interval_seconds;
+    }
     if let Some(burn_tiers) = args.burn_tiers {
         platform_config.burn_tiers = burn_tiers;
-        platform_config.burn_tiers_updated_at = now;
+        platform_config.touch_burn_tiers_updated_at(now);
+        platform_config.validate_burn_tier_expiries(now)?;
     }
 
     // Update burn_rate_config if any of its fields are provided
@@ -95,5 +150,131 @@ pub fn update_platform_config(
         CbmmError::InvalidBurnTiers
     );
 
+    // Validate allowed_quote_mints length and uniqueness
+    let allowed_quote_mints = &platform_config.allowed_quote_mints;
+    require!(
+        allowed_quote_mints.len() <= 5,
+        CbmmError::InvalidAllowedQuoteMints
+    );
+    for (index, quote_mint) in allowed_quote_mints.iter().enumerate() {
+        require!(
+            *quote_mint != platform_config.quote_mint
+                && !allowed_quote_mints[..index].contains(quote_mint),
+            CbmmError::InvalidAllowedQuoteMints
+        );
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::state::{BurnRole, BurnTier, PlatformConfig, UpdatePlatformConfigArgs};
+    use crate::test_utils::TestRunner;
+    use anchor_lang::prelude::*;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signer};
+    use test_case::test_case;
+
+    /// Platform charging the minimum 1% topup fee, 10% fees in total
+    fn setup_test() -> (TestRunner, Keypair, Pubkey) {
+        let mut runner = TestRunner::new();
+        let admin = Keypair::new();
+        runner.airdrop(&admin.pubkey(), 10_000_000_000);
+        let quote_mint = runner.create_mint(&admin, 9);
+        let platform_config = runner
+            .create_platform_config_mock(&admin, quote_mint, 5, 5, 2, 1, 300, 100, 600, None);
+        (runner, admin, platform_config)
+    }
+
+    #[test_case(Some(200), None, false; "floor_above_topup_fee")]
+    #[test_case(Some(99), None, false; "floor_below_program_minimum")]
+    #[test_case(Some(200), Some(200), true; "topup_fee_raised_with_floor")]
+    #[test_case(None, Some(150), true; "default_floor")]
+    fn test_update_platform_config_min_topup_fee(
+        min_topup_fee_bp: Option<u16>,
+        pool_topup_fee_bp: Option<u16>,
+        success: bool,
+    ) {
+        let (mut runner, admin, platform_config) = setup_test();
+
+        let result = runner.update_platform_config(
+            &admin,
+            platform_config,
+            UpdatePlatformConfigArgs {
+                min_topup_fee_bp,
+                pool_topup_fee_bp,
+                ..Default::default()
+            },
+        );
+        assert_eq!(result.is_ok(), success);
+
+        let account = runner.svm.get_account(&platform_config).unwrap();
+        let platform_config_data =
+            PlatformConfig::try_deserialize(&mut account.data.as_slice()).unwrap();
+        let expected_floor = match min_topup_fee_bp {
+            Some(min_topup_fee_bp) if success => min_topup_fee_bp,
+            _ => PlatformConfig::MIN_TOPUP_FEE_BP,
+        };
+        assert_eq!(platform_config_data.min_topup_fee_bp, expected_floor);
+    }
+
+    #[test_case(-1, false; "already_expired")]
+    #[test_case(0, false; "expires_now")]
+    #[test_case(30 * 86400, true; "expires_in_30_days")]
+    fn test_update_platform_config_burn_tier_expiry(expires_in: i64, success: bool) {
+        let (mut runner, admin, platform_config) = setup_test();
+        let now = 1682899200;
+        runner.set_system_clock(now);
+
+        let result = runner.update_platform_config(
+            &admin,
+            platform_config,
+            UpdatePlatformConfigArgs {
+                burn_tiers: Some(vec![BurnTier {
+                    burn_bp_x100: 500,
+                    role: BurnRole::Anyone,
+                    max_daily_burns: 3,
+                    expires_at: Some(now + expires_in),
+                }]),
+                ..Default::default()
+            },
+        );
+        assert_eq!(result.is_ok(), success);
+    }
+
+    #[test_case(PlatformConfig::MAX_FEE_HOLIDAY_SECONDS, true; "at_program_max")]
+    #[test_case(PlatformConfig::MAX_FEE_HOLIDAY_SECONDS + 1, false; "above_program_max")]
+    fn test_update_platform_config_max_fee_holiday(max_fee_holiday_seconds: u32, success: bool) {
+        let (mut runner, admin, platform_config) = setup_test();
+
+        let result = runner.update_platform_config(
+            &admin,
+            platform_config,
+            UpdatePlatformConfigArgs {
+                max_fee_holiday_seconds: Some(max_fee_holiday_seconds),
+                ..Default::default()
+            },
+        );
+        assert_eq!(result.is_ok(), success);
+    }
+
+    #[test_case(PlatformConfig::MAX_EVENT_INTERVAL_SECONDS, true; "at_program_max")]
+    #[test_case(PlatformConfig::MAX_EVENT_INTERVAL_SECONDS + 1, false; "above_program_max")]
+    fn test_update_platform_config_max_event_interval(
+        max_event_interval_seconds: u32,
+        success: bool,
+    ) {
+        let (mut runner, admin, platform_config) = setup_test();
+
+        let result = runner.update_platform_config(
+            &admin,
+            platform_config,
+            UpdatePlatformConfigArgs {
+                max_event_interval_seconds: Some(max_event_interval_seconds),
+                ..Default::default()
+            },
+        );
+        assert_eq!(result.is_ok(), success);
+    }
+}
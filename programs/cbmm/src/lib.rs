@@ -1,15 +1,19 @@
 #![allow(unexpected_cfgs)]
 use anchor_lang::prelude::*;
 
+pub mod constants;
 mod errors;
 mod helpers;
 mod instructions;
+pub mod pda;
+pub mod simulate;
 mod state;
 
 #[cfg(test)]
 mod test_utils;
 
 use instructions::*;
+use state::UpdatePlatformConfigArgs;
 
 declare_id!("CBMMzs3HKfTMudbXifeNcw3NcHQhZX7izDBKoGDLRdjj");
 
@@ -41,6 +45,27 @@ pub mod cbmm {
         instructions::initialize_virtual_token_account(ctx)
     }
 
+    pub fn initialize_virtual_token_accounts_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeVirtualTokenAccountsBatch<'info>>,
+        args: InitializeVirtualTokenAccountsBatchArgs,
+    ) -> Result<()> {
+        instructions::initialize_virtual_token_accounts_batch(ctx, args)
+    }
+
+    pub fn approve_virtual(ctx: Context<ApproveVirtual>, args: ApproveVirtualArgs) -> Result<()> {
+        instructions::approve_virtual(ctx, args)
+    }
+
+    pub fn snapshot_vta(ctx: Context<SnapshotVta>) -> Result<()> {
+        instructions::snapshot_vta(ctx)
+    }
+
+    pub fn snapshot_vtas<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SnapshotVtas<'info>>,
+    ) -> Result<()> {
+        instructions::snapshot_vtas(ctx)
+    }
+
     pub fn buy_virtual_token(
         ctx: Context<BuyVirtualToken>,
         args: BuyVirtualTokenArgs,
@@ -55,26 +80,241 @@ pub mod cbmm {
         instructions::sell_virtual_token(ctx, args)
     }
 
-    pub fn burn_virtual_token(ctx: Context<BurnVirtualToken>) -> Result<()> {
+    pub fn buy_with_sol(ctx: Context<BuyWithSol>, args: BuyVirtualTokenArgs) -> Result<()> {
+        instructions::buy_with_sol(ctx, args)
+    }
+
+    pub fn sell_to_sol(ctx: Context<SellToSol>, args: SellVirtualTokenArgs) -> Result<()> {
+        instructions::sell_to_sol(ctx, args)
+    }
+
+    pub fn place_order(ctx: Context<PlaceOrder>, args: PlaceOrderArgs) -> Result<()> {
+        instructions::place_order(ctx, args)
+    }
+
+    pub fn execute_order(ctx: Context<ExecuteOrder>) -> Result<()> {
+        instructions::execute_order(ctx)
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        instructions::cancel_order(ctx)
+    }
+
+    pub fn place_limit_buy(ctx: Context<PlaceLimitBuy>, args: PlaceLimitBuyArgs) -> Result<()> {
+        instructions::place_limit_buy(ctx, args)
+    }
+
+    pub fn fill_limit_buy(ctx: Context<FillLimitBuy>, args: FillLimitBuyArgs) -> Result<()> {
+        instructions::fill_limit_buy(ctx, args)
+    }
+
+    pub fn cancel_limit_buy(ctx: Context<CancelLimitBuy>) -> Result<()> {
+        instructions::cancel_limit_buy(ctx)
+    }
+
+    pub fn burn_virtual_token(ctx: Context<BurnVirtualToken>) -> Result<BurnReceipt> {
         instructions::burn_virtual_token(ctx)
     }
 
+    pub fn burn_virtual_token_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BurnVirtualTokenBatch<'info>>,
+    ) -> Result<()> {
+        instructions::burn_virtual_token_batch(ctx)
+    }
+
+    pub fn propose_pool_creator(
+        ctx: Context<ProposePoolCreator>,
+        args: ProposePoolCreatorArgs,
+    ) -> Result<()> {
+        instructions::propose_pool_creator(ctx, args)
+    }
+
+    pub fn accept_pool_creator(ctx: Context<AcceptPoolCreator>) -> Result<()> {
+        instructions::accept_pool_creator(ctx)
+    }
+
+    pub fn set_allow_user_burns(
+        ctx: Context<SetAllowUserBurns>,
+        args: SetAllowUserBurnsArgs,
+    ) -> Result<()> {
+        instructions::set_allow_user_burns(ctx, args)
+    }
+
+    pub fn set_topup_order(ctx: Context<SetTopupOrder>, args: SetTopupOrderArgs) -> Result<()> {
+        instructions::set_topup_order(ctx, args)
+    }
+
+    pub fn set_fee_holiday(ctx: Context<SetFeeHoliday>, args: SetFeeHolidayArgs) -> Result<()> {
+        instructions::set_fee_holiday(ctx, args)
+    }
+
+    pub fn set_platform_paused(
+        ctx: Context<SetPlatformPaused>,
+        args: SetPlatformPausedArgs,
+    ) -> Result<()> {
+        instructions::set_platform_paused(ctx, args)
+    }
+
+    pub fn set_vta_frozen(ctx: Context<SetVtaFrozen>, args: SetVtaFrozenArgs) -> Result<()> {
+        instructions::set_vta_frozen(ctx, args)
+    }
+
+    pub fn rescale_virtual_reserve(ctx: Context<RescaleVirtualReserve>) -> Result<()> {
+        instructions::rescale_virtual_reserve(ctx)
+    }
+
+    pub fn graduate_pool(ctx: Context<GraduatePool>) -> Result<()> {
+        instructions::graduate_pool(ctx)
+    }
+
+    pub fn claim_graduated_tokens(ctx: Context<ClaimGraduatedTokens>) -> Result<()> {
+        instructions::claim_graduated_tokens(ctx)
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account(ctx)
+    }
+
+    pub fn migrate_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateLiquidity<'info>>,
+    ) -> Result<()> {
+        instructions::migrate_liquidity(ctx)
+    }
+
+    pub fn flush_burn_queue(ctx: Context<FlushBurnQueue>) -> Result<()> {
+        instructions::flush_burn_queue(ctx)
+    }
+
+    pub fn crank_topup(ctx: Context<CrankTopup>, args: CrankTopupArgs) -> Result<()> {
+        instructions::crank_topup(ctx, args)
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        instructions::close_pool(ctx)
+    }
+
+    pub fn claim_creator_fees_and_close(ctx: Context<ClaimCreatorFeesAndClose>) -> Result<()> {
+        instructions::claim_creator_fees_and_close(ctx)
+    }
+
+    pub fn close_platform_config(ctx: Context<ClosePlatformConfig>) -> Result<()> {
+        instructions::close_platform_config(ctx)
+    }
+
     pub fn close_virtual_token_account(ctx: Context<CloseVirtualTokenAccount>) -> Result<()> {
         instructions::close_virtual_token_account(ctx)
     }
     pub fn close_user_burn_allowance(ctx: Context<CloseUserBurnAllowance>) -> Result<()> {
         instructions::close_user_burn_allowance(ctx)
     }
+    pub fn migrate_burn_allowance(
+        ctx: Context<MigrateBurnAllowance>,
+        args: MigrateBurnAllowanceArgs,
+    ) -> Result<()> {
+        instructions::migrate_burn_allowance(ctx, args)
+    }
+    pub fn check_allowance_closable(ctx: Context<CheckAllowanceClosable>) -> Result<bool> {
+        instructions::check_allowance_closable(ctx)
+    }
+    pub fn get_burn_allowance_status(
+        ctx: Context<GetBurnAllowanceStatus>,
+    ) -> Result<BurnAllowanceStatus> {
+        instructions::get_burn_allowance_status(ctx)
+    }
+    pub fn get_effective_buy_price(ctx: Context<GetEffectivePrice>) -> Result<u128> {
+        instructions::get_effective_buy_price(ctx)
+    }
+    pub fn get_effective_sell_price(ctx: Context<GetEffectivePrice>) -> Result<u128> {
+        instructions::get_effective_sell_price(ctx)
+    }
+    pub fn quote_buy_batch(
+        ctx: Context<QuoteBuyBatch>,
+        args: QuoteBuyBatchArgs,
+    ) -> Result<Vec<BuyQuote>> {
+        instructions::quote_buy_batch(ctx, args)
+    }
+
+    pub fn get_market_cap(ctx: Context<GetMarketCap>) -> Result<u128> {
+        instructions::get_market_cap(ctx)
+    }
+
+    pub fn get_pool_state(ctx: Context<GetPoolState>) -> Result<PoolStateView> {
+        instructions::get_pool_state(ctx)
+    }
     pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
         instructions::claim_creator_fees(ctx)
     }
-    pub fn claim_platform_fees(ctx: Context<ClaimPlatformFees>) -> Result<()> {
-        instructions::claim_platform_fees(ctx)
+    pub fn claim_platform_fees(
+        ctx: Context<ClaimPlatformFees>,
+        args: ClaimPlatformFeesArgs,
+    ) -> Result<()> {
+        instructions::claim_platform_fees(ctx, args)
+    }
+    pub fn sweep_treasuries<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepTreasuries<'info>>,
+    ) -> Result<()> {
+        instructions::sweep_treasuries(ctx)
+    }
+    pub fn create_burn_schedule(
+        ctx: Context<CreateBurnSchedule>,
+        args: CreateBurnScheduleArgs,
+    ) -> Result<()> {
+        instructions::create_burn_schedule(ctx, args)
+    }
+
+    pub fn run_burn_schedule(ctx: Context<RunBurnSchedule>) -> Result<()> {
+        instructions::run_burn_schedule(ctx)
+    }
+
+    pub fn close_burn_schedule(ctx: Context<CloseBurnSchedule>) -> Result<()> {
+        instructions::close_burn_schedule(ctx)
+    }
+
+    pub fn topup_with_deposit(ctx: Context<TopupWithDeposit>) -> Result<()> {
+        instructions::topup_with_deposit(ctx)
+    }
+
+    pub fn tip_creator(ctx: Context<TipCreator>, args: TipCreatorArgs) -> Result<()> {
+        instructions::tip_creator(ctx, args)
     }
+
+    pub fn lock_initial_liquidity(
+        ctx: Context<LockInitialLiquidity>,
+        args: LockInitialLiquidityArgs,
+    ) -> Result<()> {
+        instructions::lock_initial_liquidity(ctx, args)
+    }
+
     pub fn update_platform_config(
         ctx: Context<UpdatePlatformConfig>,
         args: UpdatePlatformConfigArgs,
     ) -> Result<()> {
         instructions::update_platform_config(ctx, args)
     }
+
+    pub fn resync_burn_limiter(ctx: Context<ResyncBurnLimiter>) -> Result<()> {
+        instructions::resync_burn_limiter(ctx)
+    }
+
+    pub fn sync_reserves(ctx: Context<SyncReserves>) -> Result<()> {
+        instructions::sync_reserves(ctx)
+    }
+
+    pub fn update_burn_rate_config(
+        ctx: Context<UpdateBurnRateConfig>,
+        args: UpdateBurnRateConfigArgs,
+    ) -> Result<()> {
+        instructions::update_burn_rate_config(ctx, args)
+    }
+
+    pub fn propose_config_change(
+        ctx: Context<ProposeConfigChange>,
+        args: UpdatePlatformConfigArgs,
+    ) -> Result<()> {
+        instructions::propose_config_change(ctx, args)
+    }
+
+    pub fn apply_config_change(ctx: Context<ApplyConfigChange>) -> Result<()> {
+        instructions::apply_config_change(ctx)
+    }
 }
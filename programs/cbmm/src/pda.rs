@@ -0,0 +1,225 @@
+//! Client-side derivation of the program's PDAs. Seeds mirror the account constraints.
+use crate::state::*;
+use anchor_lang::prelude::Pubkey;
+
+pub fn platform_config_pda(creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PLATFORM_CONFIG_SEED, creator.as_ref()], &crate::ID)
+}
+
+pub fn pending_config_change_pda(platform_config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PENDING_CONFIG_CHANGE_SEED, platform_config.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn platform_registry_pda(platform_config: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PLATFORM_REGISTRY_SEED, platform_config.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn platform_pool_entry_pda(platform_config: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            PLATFORM_POOL_ENTRY_SEED,
+            platform_config.as_ref(),
+            index.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// `creator` is the pool's `seed_creator`, i.e. the wallet that created it.
+pub fn pool_pda(
+    creator: &Pubkey,
+    platform_config: &Pubkey,
+    quote_mint: &Pubkey,
+    pool_index: u32,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            CBMM_POOL_SEED,
+            pool_index.to_le_bytes().as_ref(),
+            creator.as_ref(),
+            platform_config.as_ref(),
+            quote_mint.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+pub fn vta_pda(pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VIRTUAL_TOKEN_ACCOUNT_SEED, pool.as_ref(), owner.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn user_burn_allowance_pda(
+    user: &Pubkey,
+    platform_config: &Pubkey,
+    tier_index: u8,
+    tiers_updated_at: i64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            USER_BURN_ALLOWANCE_SEED,
+            user.as_ref(),
+            platform_config.as_ref(),
+            &[tier_index],
+            tiers_updated_at.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+pub fn order_pda(pool: &Pubkey, owner: &Pubkey, order_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ORDER_SEED,
+            pool.as_ref(),
+            owner.as_ref(),
+            order_id.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+pub fn graduated_mint_pda(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GRADUATED_MINT_SEED, pool.as_ref()], &crate::ID)
+}
+
+pub fn burn_schedule_pda(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BURN_SCHEDULE_SEED, pool.as_ref()], &crate::ID)
+}
+
+pub fn limit_buy_order_pda(pool: &Pubkey, owner: &Pubkey, order_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            LIMIT_BUY_ORDER_SEED,
+            pool.as_ref(),
+            owner.as_ref(),
+            order_id.to_le_bytes().as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    // Seeds are spelled out literally so a change to the constants or their order is caught
+    #[test]
+    fn test_platform_pdas_match_seeds() {
+        let creator = key(1);
+        let platform_config = key(2);
+        assert_eq!(
+            platform_config_pda(&creator),
+            Pubkey::find_program_address(&[b"platform_config", creator.as_ref()], &crate::ID)
+        );
+        assert_eq!(
+            platform_registry_pda(&platform_config),
+            Pubkey::find_program_address(
+                &[b"platform_registry", platform_config.as_ref()],
+                &crate::ID
+            )
+        );
+        assert_eq!(
+            pending_config_change_pda(&platform_config),
+            Pubkey::find_program_address(
+                &[b"pending_config_change", platform_config.as_ref()],
+                &crate::ID
+            )
+        );
+    }
+
+    #[test]
+    fn test_pool_and_vta_pdas_match_seeds() {
+        let (creator, platform_config, quote_mint, owner) = (key(1), key(2), key(3), key(4));
+        let (pool, bump) = pool_pda(&creator, &platform_config, &quote_mint, 0);
+        assert_eq!(
+            (pool, bump),
+            Pubkey::find_program_address(
+                &[
+                    b"cbmm_pool",
+                    &0u32.to_le_bytes(),
+                    creator.as_ref(),
+                    platform_config.as_ref(),
+                    quote_mint.as_ref(),
+                ],
+                &crate::ID
+            )
+        );
+        assert_ne!(pool, pool_pda(&creator, &platform_config, &key(5), 0).0);
+        assert_eq!(
+            vta_pda(&pool, &owner),
+            Pubkey::find_program_address(
+                &[b"virtual_token_account", pool.as_ref(), owner.as_ref()],
+                &crate::ID
+            )
+        );
+    }
+
+    #[test]
+    fn test_user_burn_allowance_pda_matches_seeds() {
+        let (user, platform_config) = (key(1), key(2));
+        let tiers_updated_at: i64 = 1682899200;
+        assert_eq!(
+            user_burn_allowance_pda(&user, &platform_config, 1, tiers_updated_at),
+            Pubkey::find_program_address(
+                &[
+                    b"user_burn_allowance",
+                    user.as_ref(),
+                    platform_config.as_ref(),
+                    &[1],
+                    &tiers_updated_at.to_le_bytes(),
+                ],
+                &crate::ID
+            )
+        );
+        assert_ne!(
+            user_burn_allowance_pda(&user, &platform_config, 1, tiers_updated_at).0,
+            user_burn_allowance_pda(&user, &platform_config, 1, tiers_updated_at + 1).0
+        );
+    }
+
+    #[test]
+    fn test_order_pda_matches_seeds() {
+        let (pool, owner) = (key(1), key(2));
+        assert_eq!(
+            order_pda(&pool, &owner, 7),
+            Pubkey::find_program_address(
+                &[b"order", pool.as_ref(), owner.as_ref(), &7u64.to_le_bytes()],
+                &crate::ID
+            )
+        );
+        assert_ne!(order_pda(&pool, &owner, 7), order_pda(&pool, &owner, 8));
+        assert_eq!(
+            burn_schedule_pda(&pool),
+            Pubkey::find_program_address(&[b"burn_schedule", pool.as_ref()], &crate::ID)
+        );
+        assert_eq!(
+            graduated_mint_pda(&pool),
+            Pubkey::find_program_address(&[b"graduated_mint", pool.as_ref()], &crate::ID)
+        );
+        assert_eq!(
+            limit_buy_order_pda(&pool, &owner, 7),
+            Pubkey::find_program_address(
+                &[
+                    b"limit_buy_order",
+                    pool.as_ref(),
+                    owner.as_ref(),
+                    &7u64.to_le_bytes()
+                ],
+                &crate::ID
+            )
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Client-side quotes for trades and burns. Each function replays the steps of the matching
+//! instruction on a copy of the pool, so the result is what the instruction would produce
+//! against the same account state, topup included.
+use crate::errors::CbmmError;
+use anchor_lang::prelude::*;
+
+pub use crate::helpers::{
+    calculate_burn_amount, calculate_buy_output_amount, calculate_fees,
+    calculate_sell_output_amount, BurnRateConfig, Fees, RateLimitResult,
+};
+pub use crate::state::{BurnRole, BurnTier, CbmmPool, PlatformConfig, TopupOrder};
+
+/// Outcome of [`simulate_buy`]
+pub struct SimulatedBuy {
+    /// B credited to the buyer's virtual token account
+    pub base_output: u64,
+    pub fees: u64,
+    /// Buyback fees moved into the real reserve before the swap
+    pub topup_paid: u64,
+    /// Pool state after the buy, for chaining simulations
+    pub pool: CbmmPool,
+}
+
+/// Outcome of [`simulate_sell`]
+pub struct SimulatedSell {
+    /// A paid out to the seller, after fees
+    pub quote_output: u64,
+    pub fees: u64,
+    /// Buyback fees moved into the real reserve after the swap
+    pub topup_paid: u64,
+    /// Pool state after the sell, for chaining simulations
+    pub pool: CbmmPool,
+}
+
+/// Outcome of [`preview_burn`]
+pub struct PreviewedBurn {
+    /// B the burn removes from the reserve, 0 if the rate limiter queued it
+    pub burn_amount: u64,
+    pub rate_limit_result: RateLimitResult,
+    /// Buyback fees moved into the real reserve after the burn
+    pub topup_accrued: u64,
+    /// Pool state after the burn, including the updated rate limiter
+    pub pool: CbmmPool,
+}
+
+/// Quotes `burn_virtual_token` at `tier_index` of `platform_config` at unix time `now`, the
+/// rate limiter and the tier's expiry included. Who may use the tier and its daily limit are
+/// not checked.
+///
+/// ```
+/// use cbmm::simulate::{
+///     preview_burn, BurnRateConfig, BurnRole, BurnTier, CbmmPool, PlatformConfig,
+///     RateLimitResult,
+/// };
+///
+/// let pool = CbmmPool {
+///     quote_virtual_reserve: 1_000_000,
+///     quote_starting_virtual_reserve: 1_000_000,
+///     quote_optimal_virtual_reserve: 1_000_000,
+///     base_reserve: 2_000_000,
+///     base_total_supply: 2_000_000,
+///     base_starting_total_supply: 2_000_000,
+///     ..Default::default()
+/// };
+/// let platform_config = PlatformConfig {
+///     burn_rate_config: BurnRateConfig::new(90_000, 1, 2),
+///     burn_tiers: vec![BurnTier {
+///         burn_bp_x100: 10_000, // 1%
+///         role: BurnRole::PoolOwner,
+///         max_daily_burns: 0,
+///         expires_at: None,
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let burn = preview_burn(&pool, &platform_config, 0, 0).unwrap();
+/// assert_eq!(burn.rate_limit_result, RateLimitResult::ExecuteFull(10_000));
+/// assert_eq!(burn.burn_amount, 20_000);
+/// assert_eq!(burn.pool.base_reserve, 2_000_000 - 20_000);
+/// ```
+pub fn preview_burn(
+    pool: &CbmmPool,
+    platform_config: &PlatformConfig,
+    tier_index: u8,
+    now: i64,
+) -> Result<PreviewedBurn> {
+    let burn_tier = platform_config
+        .burn_tiers
+        .get(tier_index as usize)
+        .ok_or(CbmmError::InvalidBurnTierIndex)?;
+    require!(!burn_tier.is_expired(now), CbmmError::InvalidBurnTierIndex);
+    let mut pool = pool.clone();
+    let burn = pool.burn_at(
+        &platform_config.burn_rate_config,
+        burn_tier.burn_bp_x100,
+        now,
+    )?;
+    let topup_accrued = pool.topup()?;
+    Ok(PreviewedBurn {
+        burn_amount: burn.burn_amount,
+        rate_limit_result: burn.rate_limit_result,
+        topup_accrued,
+        pool,
+    })
+}
+
+/// Quotes `buy_virtual_token` for `quote_amount` of A arriving in the pool ATA at unix time
+/// `now`, which decides whether the pool's fee holiday applies.
+///
+/// ```
+/// use cbmm::simulate::{simulate_buy, CbmmPool};
+///
+/// let pool = CbmmPool {
+///     quote_virtual_reserve: 1_000_000,
+///     quote_starting_virtual_reserve: 1_000_000,
+///     base_reserve: 2_000_000,
+///     base_total_supply: 2_000_000,
+///     base_starting_total_supply: 2_000_000,
+///     creator_fee_bp: 200,
+///     buyback_fee_bp: 600,
+///     platform_fee_bp: 200,
+///     ..Default::default()
+/// };
+///
+/// // 10% fees leave 4500 A to swap: 2M * 4500 / (1M + 4500)
+/// let buy = simulate_buy(&pool, 5000, 0).unwrap();
+/// assert_eq!(buy.fees, 500);
+/// assert_eq!(buy.base_output, 8959);
+/// assert_eq!(buy.pool.base_reserve, 2_000_000 - 8959);
+/// ```
+pub fn simulate_buy(pool: &CbmmPool, quote_amount: u64, now: i64) -> Result<SimulatedBuy> {
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+    let mut pool = pool.clone();
+    let (amount_after_fees, _) =
+        pool.collect_fees(quote_amount, &mut PlatformConfig::default(), now)?;
+    let topup_first = pool.topup_order.topup_before_swap(true);
+    let mut topup_paid = 0;
+    if topup_first {
+        topup_paid = pool.topup()?;
+    }
+    let swap = pool.quote_to_base(amount_after_fees)?;
+    if !topup_first {
+        topup_paid = pool.topup()?;
+    }
+    require_gt!(swap.base_amount, 0, CbmmError::AmountTooSmall);
+    pool.record_volume(quote_amount, swap.base_amount)?;
+    Ok(SimulatedBuy {
+        base_output: swap.base_amount,
+        fees: quote_amount - swap.quote_amount,
+        topup_paid,
+        pool,
+    })
+}
+
+/// Quotes `sell_virtual_token` for `base_amount` of B at unix time `now`.
+///
+/// ```
+/// use cbmm::simulate::{simulate_sell, CbmmPool};
+///
+/// // Burns left the real reserve short of the optimal curve, with buyback fees waiting
+/// let pool = CbmmPool {
+///     quote_reserve: 500_000,
+///     quote_virtual_reserve: 500_000,
+///     quote_starting_virtual_reserve: 1_000_000,
+///     base_reserve: 500_000,
+///     base_total_supply: 1_000_000,
+///     base_starting_total_supply: 1_000_000,
+///     buyback_fees_balance: 100_000,
+///     creator_fee_bp: 200,
+///     buyback_fee_bp: 600,
+///     platform_fee_bp: 200,
+///     ..Default::default()
+/// };
+///
+/// // 10_000 * 1M / 510_000 = 19_607 A before the ceiling-rounded fees
+/// let sell = simulate_sell(&pool, 10_000, 0).unwrap();
+/// assert_eq!(sell.fees, 1963);
+/// assert_eq!(sell.quote_output, 19_607 - 1963);
+/// // The whole buyback balance, including this sell's share, went into the topup
+/// assert_eq!(sell.topup_paid, 100_000 + 1177);
+/// assert_eq!(sell.pool.buyback_fees_balance, 0);
+/// ```
+pub fn simulate_sell(pool: &CbmmPool, base_amount: u64, now: i64) -> Result<SimulatedSell> {
+    require!(!pool.graduated, CbmmError::AlreadyGraduated);
+    let mut pool = pool.clone();
+    let topup_first = pool.topup_order.topup_before_swap(false);
+    let mut topup_paid = 0;
+    if topup_first {
+        topup_paid = pool.topup()?;
+    }
+    let swap = pool.base_to_quote(base_amount)?;
+    let (quote_output, _) =
+        pool.collect_fees(swap.quote_amount, &mut PlatformConfig::default(), now)?;
+    require_gt!(quote_output, 0, CbmmError::AmountTooSmall);
+    pool.record_volume(swap.quote_amount, base_amount)?;
+    if !topup_first {
+        topup_paid = pool.topup()?;
+    }
+    Ok(SimulatedSell {
+        quote_output,
+        fees: swap.quote_amount - quote_output,
+        topup_paid,
+        pool,
+    })
+}
@@ -1,27 +1,67 @@
 use crate::errors::CbmmError;
 use crate::helpers::{
-    calculate_burn_amount, calculate_buy_output_amount, calculate_fees,
-    calculate_new_virtual_reserve_after_burn, calculate_new_virtual_reserve_after_topup,
-    calculate_optimal_real_quote_reserve, calculate_optimal_virtual_quote_reserve,
-    calculate_sell_output_amount,
+    calculate_burn_amount, calculate_buy_output_amount, calculate_buy_output_amount_rounded_up,
+    calculate_effective_buy_price_x64, calculate_effective_sell_price_x64, calculate_fees,
+    calculate_min_buy_input_amount, calculate_new_virtual_reserve_after_burn,
+    calculate_new_virtual_reserve_after_topup, calculate_optimal_real_quote_reserve,
+    calculate_optimal_virtual_quote_reserve, calculate_sell_output_amount,
+    calculate_sell_output_amount_rounded_up, calculate_spot_price_x64,
+    normalize_price_decimals_x64, verify_merkle_proof,
 };
-use crate::helpers::{BurnRateConfig, BurnRateLimiter, RateLimitResult};
+use crate::helpers::{BurnRateConfig, BurnRateLimiter, Fees, MigrationAmm, RateLimitResult};
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{
-    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+    close_account, mint_to, transfer_checked, CloseAccount, Mint, MintTo, TokenAccount,
+    TokenInterface, TransferChecked,
 };
 
 pub const PLATFORM_CONFIG_SEED: &[u8] = b"platform_config";
-pub const CBMM_POOL_SEED: &[u8] = b"cbmm_pool";
+pub const CBMM_POOL_SEED: &[u8] = b"cbmm_pool"; // seeds: [CBMM_POOL_SEED, pool_index, seed_creator, platform_config, quote_mint]
 pub const CBMM_POOL_INDEX_SEED: u32 = 0; // this is introduced for extensibility - if we ever need more that one pool per creator, we can use this to differentiate them
 pub const VIRTUAL_TOKEN_ACCOUNT_SEED: &[u8] = b"virtual_token_account";
 pub const USER_BURN_ALLOWANCE_SEED: &[u8] = b"user_burn_allowance";
+pub const PLATFORM_REGISTRY_SEED: &[u8] = b"platform_registry";
+pub const PLATFORM_POOL_ENTRY_SEED: &[u8] = b"platform_pool_entry"; // seeds: [PLATFORM_POOL_ENTRY_SEED, platform_config, index]
+pub const ORDER_SEED: &[u8] = b"order"; // seeds: [ORDER_SEED, pool, owner, order_id]
+pub const GRADUATED_MINT_SEED: &[u8] = b"graduated_mint"; // seeds: [GRADUATED_MINT_SEED, pool]
+pub const LIMIT_BUY_ORDER_SEED: &[u8] = b"limit_buy_order"; // seeds: [LIMIT_BUY_ORDER_SEED, pool, owner, order_id]
+pub const BURN_SCHEDULE_SEED: &[u8] = b"burn_schedule"; // seeds: [BURN_SCHEDULE_SEED, pool]
+pub const PENDING_CONFIG_CHANGE_SEED: &[u8] = b"pending_config_change"; // seeds: [PENDING_CONFIG_CHANGE_SEED, platform_config]
 
 pub const DEFAULT_BASE_MINT_DECIMALS: u8 = 6;
 pub const DEFAULT_BASE_MINT_RESERVE: u64 =
     1_000_000_000 * 10u64.pow(DEFAULT_BASE_MINT_DECIMALS as u32);
 pub const MIN_VIRTUAL_RESERVE: u64 = 1_000_000;
 
+/// Layout version of `PlatformConfig`, `CbmmPool` and `VirtualTokenAccount`. Accounts created
+/// before versioning read as 0 until upgraded by `migrate_account`. Version 2 grew `CbmmPool`
+/// by `POOL_V2_EXTRA_SPACE` and version 3 grew `PlatformConfig` by
+/// `PLATFORM_CONFIG_V3_EXTRA_SPACE`, so older accounts must be migrated before they deserialize
+/// again. Version 4 added `CbmmPool::round_in_favor_of_trader`, which older pools read as
+/// false. Version 5 added `BurnTier::expires_at`, which `migrate_account` inserts into the stored
+/// tiers of older `PlatformConfig`s.
+pub const CURRENT_ACCOUNT_VERSION: u8 = 5;
+pub const RESERVED_SPACE: usize = 64;
+/// Fresh reserved space appended to `CbmmPool` in version 2, once its first block ran out
+pub const POOL_V2_EXTRA_SPACE: usize = 64;
+/// Space appended to `PlatformConfig` in version 3 for `allowed_quote_mints`
+pub const PLATFORM_CONFIG_V3_EXTRA_SPACE: usize = 192;
+
+/// Zeroed space at the end of versioned accounts, so new fields can be added without a realloc.
+/// Fields added after versioning are placed before it and shrink it by their size.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub struct Reserved<const N: usize = RESERVED_SPACE>(pub [u8; N]);
+
+impl<const N: usize> Default for Reserved<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> Space for Reserved<N> {
+    const INIT_SPACE: usize = N;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace, PartialEq)]
 pub enum BurnRole {
     Anyone,                 // Permissionless - anyone can burn at this tier
@@ -34,6 +74,14 @@ pub struct BurnTier {
     pub burn_bp_x100: u32,    // Burn percentage in basis points * 100
     pub role: BurnRole,       // Who can use this tier
     pub max_daily_burns: u16, // Max burns per day (0 = unlimited)
+    /// Unix time from which the tier can no longer burn (None = never expires)
+    pub expires_at: Option<i64>,
+}
+
+impl BurnTier {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
 }
 
 #[account]
@@ -48,28 +96,128 @@ pub struct PlatformConfig {
     pub pool_creator_fee_bp: u16,
     pub pool_topup_fee_bp: u16,
     pub platform_fee_bp: u16,
+    /// Cut of the remaining buyback fees paid to whoever cranks `flush_burn_queue` (0 = disabled)
+    pub keeper_reward_bp: u16,
+    /// Part of the platform fee paid to the referrer of a buy, in bp of the quote amount
+    pub referral_fee_bp: u16,
+    /// Reject pools whose quote mint has a freeze authority, which could freeze the pool ATA
+    pub require_no_freeze_authority: bool,
+    /// Platform fees ever charged across all pools, including claimed ones
+    pub lifetime_platform_fees: u128,
+    /// External AMM graduated pools migrate their reserves into (None = migration disabled)
+    pub migration_amm: Option<MigrationAmm>,
 
     /// Optional global burn authority. If set, every burn instruction on this platform must be signed by this authority.
     pub burn_authority: Option<Pubkey>,
 
     pub burn_rate_config: BurnRateConfig,
+    /// Length of a user burn allowance "day" after which `burns_today` resets
+    pub burn_reset_interval_seconds: i64,
 
     pub burn_tiers_updated_at: i64, // used as a seed for the burn allowance accounts - update makes all old allowances invalid
     #[max_len(5)]
     pub burn_tiers: Vec<BurnTier>,
+
+    pub version: u8,
+    /// Emergency halt of buys, sells, burns, burn queue flushes and topup cranks on every pool of
+    /// the platform
+    pub paused: bool,
+    /// Notice users get before a proposed config change can be applied (0 = changes apply immediately)
+    pub config_change_delay_seconds: i64,
+    /// Owner of the token account platform fees are claimed to (default pubkey = admin)
+    pub fee_recipient: Pubkey,
+    /// Cap on the sum of `max_daily_burns * burn_bp_x100` over all tiers (0 = unchecked)
+    pub daily_burn_envelope_bp_x100: u64,
+    /// A a pool creator must lock with `lock_initial_liquidity` before the pool trades (0 = none)
+    pub min_initial_lock: u64,
+    /// Quote mints pools may use besides `quote_mint`, see `is_quote_mint_allowed`
+    #[max_len(5)]
+    pub allowed_quote_mints: Vec<Pubkey>,
+    /// Lowest `pool_topup_fee_bp` the platform accepts, see `topup_fee_floor_bp`
+    pub min_topup_fee_bp: u16,
+    /// Longest fee holiday a pool creator may grant, counted from now (0 = none). The admin is
+    /// not bound by it.
+    pub max_fee_holiday_seconds: u32,
+    /// Longest `CbmmPool::min_event_interval_seconds` a pool may be created with (0 = no
+    /// throttling)
+    pub max_event_interval_seconds: u32,
+    pub _reserved: Reserved<
+        {
+            RESERVED_SPACE + PLATFORM_CONFIG_V3_EXTRA_SPACE
+                - 1
+                - 8
+                - 32
+                - 8
+                - 8
+                - (4 + 5 * 32)
+                - 2
+                - 4
+                - 4
+        },
+    >,
 }
 
 impl PlatformConfig {
     /// Maximum total fees allowed (20%)
     pub const MAX_TOTAL_FEES_BP: u16 = 2_000;
-    /// Minimum topup fee required (1%)
+    /// Lowest topup fee floor a platform can configure (1%)
     pub const MIN_TOPUP_FEE_BP: u16 = 100;
     /// Maximum platform fee allowed (10%)
     pub const MAX_PLATFORM_FEE_BP: u16 = 1_000;
+    /// Maximum keeper reward allowed (1% of the buyback fees balance per crank)
+    pub const MAX_KEEPER_REWARD_BP: u16 = 100;
     /// Time window for reaching theoretical burn limit (15 minutes in seconds)
     pub const BURN_LIMIT_TIME_WINDOW_SECONDS: i64 = 900;
     // 10 bp (1000 bp_x100) hard limit for unrestricted role
     pub const MAX_DAILY_BURN_BP_X100_ANYONE: u64 = 1_000;
+    /// Default burn allowance reset interval (1 day)
+    pub const DEFAULT_BURN_RESET_INTERVAL_SECONDS: i64 = 86400;
+    /// Allowed burn allowance reset interval range (1 minute to 30 days)
+    pub const MIN_BURN_RESET_INTERVAL_SECONDS: i64 = 60;
+    pub const MAX_BURN_RESET_INTERVAL_SECONDS: i64 = 30 * 86400;
+    /// Longest allowed config change timelock (30 days)
+    pub const MAX_CONFIG_CHANGE_DELAY_SECONDS: i64 = 30 * 86400;
+    /// Highest `max_fee_holiday_seconds` a platform can configure (30 days)
+    pub const MAX_FEE_HOLIDAY_SECONDS: u32 = 30 * 86400;
+    /// Highest `max_event_interval_seconds` a platform can configure (1 day)
+    pub const MAX_EVENT_INTERVAL_SECONDS: u32 = 86400;
+
+    /// Configs created before `fee_recipient` existed read it as the default pubkey and keep
+    /// paying the admin.
+    pub fn platform_fee_recipient(&self) -> Pubkey {
+        if self.fee_recipient == Pubkey::default() {
+            self.admin
+        } else {
+            self.fee_recipient
+        }
+    }
+
+    /// The configured `min_topup_fee_bp`, never below `MIN_TOPUP_FEE_BP`. Configs created before
+    /// the field existed read it as 0 and keep the 1% floor.
+    pub fn topup_fee_floor_bp(&self) -> u16 {
+        self.min_topup_fee_bp.max(Self::MIN_TOPUP_FEE_BP)
+    }
+
+    /// Checks a creator-chosen `fee_holiday_until` against `max_fee_holiday_seconds`. Ending a
+    /// holiday, with 0 or any past timestamp, is always allowed.
+    pub fn validate_fee_holiday(&self, fee_holiday_until: i64, now: i64) -> Result<()> {
+        require_gte!(
+            now.saturating_add(self.max_fee_holiday_seconds as i64),
+            fee_holiday_until,
+            CbmmError::FeeHolidayTooLong
+        );
+        Ok(())
+    }
+
+    /// Checks a pool's `min_event_interval_seconds` against `max_event_interval_seconds`
+    pub fn validate_event_interval(&self, min_event_interval_seconds: u32) -> Result<()> {
+        require_gte!(
+            self.max_event_interval_seconds,
+            min_event_interval_seconds,
+            CbmmError::EventIntervalTooLong
+        );
+        Ok(())
+    }
 
     pub fn validate_fees_and_burn_config(&self) -> Result<()> {
         // 1. Validate fee constraints
@@ -84,13 +232,22 @@ impl PlatformConfig {
             CbmmError::InvalidFeeBasisPoints
         );
         require!(
-            self.pool_topup_fee_bp >= Self::MIN_TOPUP_FEE_BP,
+            self.pool_topup_fee_bp >= self.topup_fee_floor_bp(),
             CbmmError::InvalidFeeBasisPoints
         );
         require!(
             self.platform_fee_bp <= Self::MAX_PLATFORM_FEE_BP,
             CbmmError::InvalidFeeBasisPoints
         );
+        require!(
+            self.keeper_reward_bp <= Self::MAX_KEEPER_REWARD_BP,
+            CbmmError::InvalidFeeBasisPoints
+        );
+        // Referrals are carved out of the platform fee, so total fees are unchanged
+        require!(
+            self.referral_fee_bp <= self.platform_fee_bp,
+            CbmmError::InvalidFeeBasisPoints
+        );
 
         // 2. Validate burn tiers
         let total_fees_bp_x100 = (total_fees as u64) * 100;
@@ -98,7 +255,14 @@ impl PlatformConfig {
         // Safe max is set to 3/4 of total fees percentage
         let safe_max_bp_x100 = (total_fees_bp_x100 * 3) / 4;
 
-        for tier in &self.burn_tiers {
+        for (index, tier) in self.burn_tiers.iter().enumerate() {
+            // One tier per role, so a burner's `burn_tier_index` is never ambiguous
+            require!(
+                !self.burn_tiers[..index]
+                    .iter()
+                    .any(|other| other.role == tier.role),
+                CbmmError::InvalidBurnTiers
+            );
             match &tier.role {
                 BurnRole::Anyone => {
                     require!(
@@ -115,6 +279,24 @@ impl PlatformConfig {
             }
         }
 
+        // Bound the combined daily burn of all tiers, so burns cannot structurally outrun the fees
+        // that fund the topups. An unlimited tier has no bound.
+        if self.daily_burn_envelope_bp_x100 > 0 {
+            let mut daily_burn_bp_x100: u64 = 0;
+            for tier in &self.burn_tiers {
+                require_gt!(tier.max_daily_burns, 0, CbmmError::BurnEnvelopeExceeded);
+                daily_burn_bp_x100 = (tier.max_daily_burns as u64)
+                    .checked_mul(tier.burn_bp_x100 as u64)
+                    .and_then(|tier_bp_x100| daily_burn_bp_x100.checked_add(tier_bp_x100))
+                    .ok_or(CbmmError::MathOverflow)?;
+            }
+            require_gte!(
+                self.daily_burn_envelope_bp_x100,
+                daily_burn_bp_x100,
+                CbmmError::BurnEnvelopeExceeded
+            );
+        }
+
         // 3. Validate burn rate config
         require!(
             self.burn_rate_config.burn_limit_bp_x100 < total_fees_bp_x100,
@@ -139,9 +321,23 @@ impl PlatformConfig {
             CbmmError::InvalidBurnRate
         );
 
+        // 4. Validate burn allowance reset interval
+        require!(
+            (Self::MIN_BURN_RESET_INTERVAL_SECONDS..=Self::MAX_BURN_RESET_INTERVAL_SECONDS)
+                .contains(&self.burn_reset_interval_seconds),
+            CbmmError::InvalidBurnResetInterval
+        );
+
+        // 5. Validate config change timelock
+        require!(
+            (0..=Self::MAX_CONFIG_CHANGE_DELAY_SECONDS).contains(&self.config_change_delay_seconds),
+            CbmmError::InvalidConfigChangeDelay
+        );
+
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn try_new(
         bump: u8,
         admin: Pubkey,
@@ -152,9 +348,13 @@ impl PlatformConfig {
         pool_creator_fee_bp: u16,
         pool_topup_fee_bp: u16,
         platform_fee_bp: u16,
+        keeper_reward_bp: u16,
+        referral_fee_bp: u16,
+        require_no_freeze_authority: bool,
         burn_limit_bp_x100: u64,
         burn_min_bp_x100: u64,
         burn_decay_rate_per_sec_bp_x100: u64,
+        burn_reset_interval_seconds: i64,
     ) -> Result<Self> {
         require!(burn_tiers.len() <= 5, CbmmError::InvalidBurnTiers);
 
@@ -164,6 +364,7 @@ impl PlatformConfig {
             burn_decay_rate_per_sec_bp_x100,
         );
 
+        let now = Clock::get()?.unix_timestamp;
         let config = Self {
             bump,
             admin,
@@ -171,18 +372,52 @@ impl PlatformConfig {
             quote_mint,
             burn_authority,
             burn_tiers,
-            burn_tiers_updated_at: Clock::get()?.unix_timestamp,
+            burn_tiers_updated_at: now,
             burn_rate_config: burn_config,
+            burn_reset_interval_seconds,
             pool_creator_fee_bp,
             pool_topup_fee_bp,
             platform_fee_bp,
+            keeper_reward_bp,
+            referral_fee_bp,
+            require_no_freeze_authority,
+            lifetime_platform_fees: 0,
+            migration_amm: None,
+            version: CURRENT_ACCOUNT_VERSION,
+            paused: false,
+            config_change_delay_seconds: 0,
+            fee_recipient: admin,
+            daily_burn_envelope_bp_x100: 0,
+            min_initial_lock: 0,
+            allowed_quote_mints: Vec::new(),
+            min_topup_fee_bp: Self::MIN_TOPUP_FEE_BP,
+            max_fee_holiday_seconds: 0,
+            max_event_interval_seconds: 0,
+            _reserved: Reserved::default(),
         };
 
         config.validate_fees_and_burn_config()?;
+        config.validate_burn_tier_expiries(now)?;
 
         Ok(config)
     }
 
+    /// New burn tiers may only expire in the future. Checked only when the tiers are written,
+    /// so a tier that expired since does not block unrelated config updates.
+    pub fn validate_burn_tier_expiries(&self, now: i64) -> Result<()> {
+        for tier in &self.burn_tiers {
+            if let Some(expires_at) = tier.expires_at {
+                require_gt!(expires_at, now, CbmmError::InvalidBurnTiers);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether pools on this platform may be created with `quote_mint` as Mint A
+    pub fn is_quote_mint_allowed(&self, quote_mint: &Pubkey) -> bool {
+        *quote_mint == self.quote_mint || self.allowed_quote_mints.contains(quote_mint)
+    }
+
     /// If a global burn authority is configured, require the provided authority
     /// to be present and to match. If no authority is configured, then no
     /// authority must be provided.
@@ -192,6 +427,109 @@ impl PlatformConfig {
         }
         Ok(())
     }
+
+    /// Marks the burn tiers as updated at `now`, invalidating every existing allowance. The
+    /// timestamp only ever moves forward, so a clock running backwards cannot bring stale
+    /// allowances back to life.
+    pub fn touch_burn_tiers_updated_at(&mut self, now: i64) {
+        self.burn_tiers_updated_at = now.max(self.burn_tiers_updated_at.saturating_add(1));
+    }
+}
+
+/// Optional overrides for `PlatformConfig`, applied by `update_platform_config` or staged in a
+/// `PendingConfigChange`. `None` leaves the field unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct UpdatePlatformConfigArgs {
+    pub pool_creator_fee_bp: Option<u16>,
+    pub pool_topup_fee_bp: Option<u16>,
+    pub platform_fee_bp: Option<u16>,
+    pub keeper_reward_bp: Option<u16>,
+    pub referral_fee_bp: Option<u16>,
+    pub require_no_freeze_authority: Option<bool>,
+    pub burn_authority: Option<Option<Pubkey>>,
+    pub migration_amm: Option<Option<MigrationAmm>>,
+    pub burn_limit_bp_x100: Option<u64>,
+    pub burn_min_bp_x100: Option<u64>,
+    pub burn_decay_rate_per_sec_bp_x100: Option<u64>,
+    pub burn_reset_interval_seconds: Option<i64>,
+    #[max_len(5)]
+    pub burn_tiers: Option<Vec<BurnTier>>,
+    pub config_change_delay_seconds: Option<i64>,
+    pub fee_recipient: Option<Pubkey>,
+    pub daily_burn_envelope_bp_x100: Option<u64>,
+    pub min_initial_lock: Option<u64>,
+    #[max_len(5)]
+    pub allowed_quote_mints: Option<Vec<Pubkey>>,
+    pub min_topup_fee_bp: Option<u16>,
+    pub max_fee_holiday_seconds: Option<u32>,
+    pub max_event_interval_seconds: Option<u32>,
+}
+
+/// Platform parameter change staged by `propose_config_change`, written to the config by
+/// `apply_config_change` once `effective_at` has passed.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PendingConfigChange {
+    pub bump: u8,
+    pub platform_config: Pubkey,
+
+    pub effective_at: i64,
+    pub args: UpdatePlatformConfigArgs,
+}
+
+/// Per-platform pool counters, so clients can discover pools without a `getProgramAccounts` scan.
+/// Each pool gets a `PlatformPoolEntry` at the index `total_pools_created` had when it was created.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlatformRegistry {
+    pub bump: u8,
+    pub platform_config: Pubkey,
+
+    /// Number of currently open pools
+    pub pool_count: u64,
+    /// Monotonic counter of all pools ever created, never decremented
+    pub total_pools_created: u64,
+}
+
+impl PlatformRegistry {
+    /// Registry is created lazily by the first `create_pool` on a platform.
+    pub fn initialize_if_needed(&mut self, bump: u8, platform_config: Pubkey) {
+        if self.platform_config == Pubkey::default() {
+            self.bump = bump;
+            self.platform_config = platform_config;
+        }
+    }
+
+    /// Returns the running count of open pools.
+    pub fn register_pool(&mut self) -> Result<u64> {
+        self.pool_count = self
+            .pool_count
+            .checked_add(1)
+            .ok_or(CbmmError::MathOverflow)?;
+        self.total_pools_created = self
+            .total_pools_created
+            .checked_add(1)
+            .ok_or(CbmmError::MathOverflow)?;
+        Ok(self.pool_count)
+    }
+
+    pub fn unregister_pool(&mut self) -> Result<u64> {
+        self.pool_count = self.pool_count.checked_sub(1).ok_or(CbmmError::Underflow)?;
+        Ok(self.pool_count)
+    }
+}
+
+/// Registry slot pointing at the pool created as number `index` on a platform. Clients list the
+/// pools by walking indices `0..total_pools_created`, slots of closed pools are closed with them.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlatformPoolEntry {
+    pub bump: u8,
+    // seeds
+    pub platform_config: Pubkey,
+    pub index: u64,
+
+    pub pool: Pubkey,
 }
 
 // A is the real SPL token
@@ -201,8 +539,13 @@ impl PlatformConfig {
 pub struct CbmmPool {
     /// Bump seed
     pub bump: u8,
-    /// Pool creator address
+    /// Pool creator address, gates creator burns and fee claims. Can be handed over.
     pub creator: Pubkey,
+    /// Creator the pool PDA was derived with. Never changes, so the pool address and every
+    /// account keyed by it (pool ATA, virtual token accounts) survive a creator handover.
+    pub seed_creator: Pubkey,
+    /// Creator proposed by `propose_pool_creator`, awaiting `accept_pool_creator`
+    pub pending_creator: Option<Pubkey>,
     /// Pool index per creator
     pub pool_index: u32,
     /// Platform config used by this pool
@@ -227,6 +570,8 @@ pub struct CbmmPool {
     pub base_starting_total_supply: u64,
     /// B total supply including decimals
     pub base_total_supply: u64,
+    /// Floor burns can never take the B reserve below, so the price stays defined
+    pub min_base_reserve: u64,
 
     /// Creator fees balance denominated in Mint A including decimals
     pub creator_fees_balance: u64,
@@ -242,13 +587,78 @@ pub struct CbmmPool {
     /// Platform fee basis points
     pub platform_fee_bp: u16,
 
+    /// Whether permissionless (`BurnRole::Anyone`) burn tiers may burn on this pool
+    pub allow_user_burns: bool,
+    /// Minimum time between a user's last buy and their next sell (0 = no lockup)
+    pub sell_lockup_seconds: u32,
+
+    /// Set by `graduate_pool`. Freezes trading and burns; holders claim real B tokens instead.
+    pub graduated: bool,
+    /// Real B mint provisioned on graduation, default until then
+    pub base_mint: Pubkey,
+    /// Owner of the external LP position, set once reserves are migrated by `migrate_liquidity`
+    pub migrated_lp_owner: Option<Pubkey>,
+
     /// Burn rate limiter
     pub burn_limiter: BurnRateLimiter,
+
+    pub version: u8,
+    /// Quote owed to the real reserve by burns (`ΔV - ΔA`) and not yet paid back by topups
+    pub quote_outstanding_topup: u64,
+    /// Merkle root of the buyers allowed to buy (None = open to everyone)
+    pub allowlist_root: Option<[u8; 32]>,
+    /// Most B a single virtual token account may hold after a buy (0 = unlimited)
+    pub max_vta_balance: u64,
+    /// Whether buys and sells top up before or after the swap, see `TopupOrder`
+    pub topup_order: TopupOrder,
+    /// A locked by the creator through `lock_initial_liquidity`. It sits in the pool ATA outside
+    /// the reserves and fee balances, so nothing can withdraw it.
+    pub locked_quote_reserve: u64,
+    /// Running total of A traded, buys before fees and sells before fees
+    pub cumulative_quote_volume: u128,
+    /// Running total of B bought and sold
+    pub cumulative_base_volume: u128,
+    /// Whether swap outputs round up, handing sub-unit remainders to the trader, or down, keeping
+    /// them in the pool. Pools from before version 4 read false and keep rounding down.
+    pub round_in_favor_of_trader: bool,
+    /// Trades before this timestamp pay no fees (0 = no holiday)
+    pub fee_holiday_until: i64,
+    /// Burns by anyone but the creator also need the creator's signature
+    pub require_creator_cosign_on_burn: bool,
+    /// Only the first trade in each such window emits its full trade event, later ones emit a
+    /// compact `ThrottledTradeEvent` (0 = every trade emits in full). At most the platform's
+    /// `max_event_interval_seconds` at creation.
+    pub min_event_interval_seconds: u32,
+    /// Timestamp of the last trade that emitted its event
+    pub last_trade_event_timestamp: i64,
+    /// Index of the pool's `PlatformPoolEntry` (None = created before entries existed)
+    pub registry_index: Option<u64>,
+    pub _reserved: Reserved<
+        {
+            RESERVED_SPACE + POOL_V2_EXTRA_SPACE
+                - 8
+                - 33
+                - 8
+                - 1
+                - 8
+                - 16
+                - 16
+                - 1
+                - 8
+                - 1
+                - 4
+                - 8
+                - 9
+        },
+    >,
 }
 
 pub struct BurnResult {
     pub rate_limit_result: RateLimitResult,
+    /// Amount actually burned, after clamping to `min_base_reserve`
     pub burn_amount: u64,
+    /// The limiter executed while earlier burns were still waiting in its queue
+    pub flushed_queue: bool,
 }
 
 pub struct SwapResult {
@@ -256,6 +666,25 @@ pub struct SwapResult {
     pub base_amount: u64,
 }
 
+/// Settings of a pool being created, the fees copied from the platform and the rest chosen by
+/// the creator. See `CbmmPool` for what each field does.
+#[derive(Clone, Default)]
+pub struct NewPoolParams {
+    pub quote_virtual_reserve: u64,
+    pub creator_fee_bp: u16,
+    pub buyback_fee_bp: u16,
+    pub platform_fee_bp: u16,
+    pub sell_lockup_seconds: u32,
+    pub min_base_reserve: u64,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub max_vta_balance: u64,
+    pub topup_order: TopupOrder,
+    pub round_in_favor_of_trader: bool,
+    pub fee_holiday_until: i64,
+    pub require_creator_cosign_on_burn: bool,
+    pub min_event_interval_seconds: u32,
+}
+
 impl CbmmPool {
     pub fn try_new(
         bump: u8,
@@ -263,23 +692,45 @@ impl CbmmPool {
         pool_index: u32,
         platform_config: Pubkey,
         quote_mint: Pubkey,
-        quote_virtual_reserve: u64,
-        creator_fee_bp: u16,
-        buyback_fee_bp: u16,
-        platform_fee_bp: u16,
+        params: NewPoolParams,
     ) -> Result<Self> {
-        require!(quote_virtual_reserve > 0, CbmmError::InvalidVirtualReserve);
+        let NewPoolParams {
+            quote_virtual_reserve,
+            creator_fee_bp,
+            buyback_fee_bp,
+            platform_fee_bp,
+            sell_lockup_seconds,
+            min_base_reserve,
+            allowlist_root,
+            max_vta_balance,
+            topup_order,
+            round_in_favor_of_trader,
+            fee_holiday_until,
+            require_creator_cosign_on_burn,
+            min_event_interval_seconds,
+        } = params;
+        require_gte!(
+            quote_virtual_reserve,
+            MIN_VIRTUAL_RESERVE,
+            CbmmError::InvalidVirtualReserve
+        );
+        require!(
+            min_base_reserve > 0 && min_base_reserve < DEFAULT_BASE_MINT_RESERVE,
+            CbmmError::InvalidMinBaseReserve
+        );
         require!(buyback_fee_bp > 0, CbmmError::InvalidBuybackFeeBasisPoints);
 
-        // Initial stress is 3/4 of total fees - to ensure the pool is not exploitable after creation
-        let total_fees_bp_x100 = (creator_fee_bp + buyback_fee_bp + platform_fee_bp) as u64 * 100;
-        let initial_stress_bp_x10k = total_fees_bp_x100 * 3 / 4;
+        let initial_stress_bp_x10k = BurnRateLimiter::initial_stress_bp_x10k(
+            creator_fee_bp + buyback_fee_bp + platform_fee_bp,
+        );
         let burn_limiter =
             BurnRateLimiter::new(Clock::get()?.unix_timestamp, initial_stress_bp_x10k);
 
         Ok(Self {
             bump,
             creator,
+            seed_creator: creator,
+            pending_creator: None,
             pool_index,
             platform_config,
             quote_mint,
@@ -291,31 +742,248 @@ impl CbmmPool {
             base_reserve: DEFAULT_BASE_MINT_RESERVE,
             base_starting_total_supply: DEFAULT_BASE_MINT_RESERVE,
             base_total_supply: DEFAULT_BASE_MINT_RESERVE,
+            min_base_reserve,
             creator_fees_balance: 0,
             buyback_fees_balance: 0,
             platform_fees_balance: 0,
             creator_fee_bp,
             buyback_fee_bp,
             platform_fee_bp,
+            allow_user_burns: true,
+            sell_lockup_seconds,
+            graduated: false,
+            base_mint: Pubkey::default(),
+            migrated_lp_owner: None,
             burn_limiter,
+            version: CURRENT_ACCOUNT_VERSION,
+            quote_outstanding_topup: 0,
+            allowlist_root,
+            max_vta_balance,
+            topup_order,
+            locked_quote_reserve: 0,
+            cumulative_quote_volume: 0,
+            cumulative_base_volume: 0,
+            round_in_favor_of_trader,
+            fee_holiday_until,
+            require_creator_cosign_on_burn,
+            min_event_interval_seconds,
+            last_trade_event_timestamp: 0,
+            registry_index: None,
+            _reserved: Reserved::default(),
         })
     }
 
-    pub fn collect_fees(&mut self, quote_amount: u64) -> anchor_lang::prelude::Result<u64> {
+    /// Adds a trade to the running volume totals
+    pub fn record_volume(&mut self, quote_amount: u64, base_amount: u64) -> Result<()> {
+        self.cumulative_quote_volume = self
+            .cumulative_quote_volume
+            .checked_add(quote_amount as u128)
+            .ok_or(CbmmError::MathOverflow)?;
+        self.cumulative_base_volume = self
+            .cumulative_base_volume
+            .checked_add(base_amount as u128)
+            .ok_or(CbmmError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Trading stays closed until the creator has locked the platform's minimum.
+    pub fn check_initial_lock(&self, min_initial_lock: u64) -> Result<()> {
+        require_gte!(
+            self.locked_quote_reserve,
+            min_initial_lock,
+            CbmmError::InitialLockMissing
+        );
+        Ok(())
+    }
+
+    /// Checks the creator's co-sign on a burn by `burner` when the pool requires one. The
+    /// creator burning on their own pool needs no second signature.
+    pub fn check_creator_cosign(&self, burner: Pubkey, cosigner: Option<Pubkey>) -> Result<()> {
+        if !self.require_creator_cosign_on_burn || burner == self.creator {
+            return Ok(());
+        }
+        require!(
+            cosigner == Some(self.creator),
+            CbmmError::CreatorCosignRequired
+        );
+        Ok(())
+    }
+
+    /// Whether a trade at `now` gets to emit its event, claiming the current throttle window if
+    /// so. See `min_event_interval_seconds`.
+    pub fn take_trade_event_slot(&mut self, now: i64) -> bool {
+        if self.min_event_interval_seconds > 0
+            && now
+                < self
+                    .last_trade_event_timestamp
+                    .saturating_add(self.min_event_interval_seconds as i64)
+        {
+            return false;
+        }
+        self.last_trade_event_timestamp = now;
+        true
+    }
+
+    /// Whether trades at `now` fall inside the creator's fee holiday
+    pub fn fee_holiday_active(&self, now: i64) -> bool {
+        now < self.fee_holiday_until
+    }
+
+    /// Books the fees on `quote_amount` and returns what is left of it, along with the fees
+    /// booked. Nothing is charged during a fee holiday.
+    pub fn collect_fees(
+        &mut self,
+        quote_amount: u64,
+        platform_config: &mut PlatformConfig,
+        now: i64,
+    ) -> anchor_lang::prelude::Result<(u64, Fees)> {
+        if self.fee_holiday_active(now) {
+            return Ok((quote_amount, Fees::default()));
+        }
         let fees = calculate_fees(
             quote_amount,
             self.creator_fee_bp,
             self.buyback_fee_bp,
             self.platform_fee_bp,
         )?;
-        self.creator_fees_balance += fees.creator_fees_amount;
-        self.buyback_fees_balance += fees.buyback_fees_amount;
-        self.platform_fees_balance += fees.platform_fees_amount;
-        Ok(quote_amount - fees.total_fees_amount())
+        // Ceiling-rounded fees can exceed dust amounts
+        let amount_after_fees = quote_amount
+            .checked_sub(fees.total_fees_amount())
+            .ok_or(CbmmError::AmountTooSmall)?;
+        self.creator_fees_balance = self
+            .creator_fees_balance
+            .checked_add(fees.creator_fees_amount)
+            .ok_or(CbmmError::MathOverflow)?;
+        self.buyback_fees_balance = self
+            .buyback_fees_balance
+            .checked_add(fees.buyback_fees_amount)
+            .ok_or(CbmmError::MathOverflow)?;
+        self.platform_fees_balance = self
+            .platform_fees_balance
+            .checked_add(fees.platform_fees_amount)
+            .ok_or(CbmmError::MathOverflow)?;
+        platform_config.lifetime_platform_fees = platform_config
+            .lifetime_platform_fees
+            .checked_add(fees.platform_fees_amount as u128)
+            .ok_or(CbmmError::MathOverflow)?;
+        Ok((amount_after_fees, fees))
+    }
+
+    /// Open pools accept any buyer; allowlisted ones need a proof of the buyer's leaf
+    pub fn check_allowlist(&self, buyer: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+        if let Some(root) = self.allowlist_root {
+            require!(
+                verify_merkle_proof(&root, buyer.as_ref(), proof),
+                CbmmError::NotAllowlisted
+            );
+        }
+        Ok(())
+    }
+
+    /// Position caps only limit growth, a holder above the cap can always sell down
+    pub fn check_position_cap(&self, vta_balance: u64) -> Result<()> {
+        if self.max_vta_balance > 0 {
+            require_gte!(
+                self.max_vta_balance,
+                vta_balance,
+                CbmmError::PositionCapExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// Spot price of B in A as Q64.64
+    pub fn spot_price_x64(&self) -> Result<u128> {
+        calculate_spot_price_x64(
+            self.quote_reserve,
+            self.quote_virtual_reserve,
+            self.base_reserve,
+        )
+    }
+
+    /// Spot price of one whole B in whole A as Q64.64, for a quote mint with `quote_decimals`
+    pub fn normalized_spot_price_x64(&self, quote_decimals: u8) -> Result<u128> {
+        normalize_price_decimals_x64(
+            self.spot_price_x64()?,
+            self.base_mint_decimals,
+            quote_decimals,
+        )
+    }
+
+    /// Fully diluted market cap in A: the spot price times `base_total_supply`, so
+    /// `(A + V) * supply / B`. A pool without B reserve, as after graduation, quotes 0.
+    pub fn market_cap_quote(&self) -> Result<u128> {
+        if self.base_reserve == 0 {
+            return Ok(0);
+        }
+        (self.quote_reserve as u128 + self.quote_virtual_reserve as u128)
+            .checked_mul(self.base_total_supply as u128)
+            .map(|numerator| numerator / self.base_reserve as u128)
+            .ok_or(CbmmError::MathOverflow.into())
+    }
+
+    /// Creator, buyback and platform fees combined
+    pub fn total_fee_bp(&self) -> u32 {
+        self.creator_fee_bp as u32 + self.buyback_fee_bp as u32 + self.platform_fee_bp as u32
+    }
+
+    /// Smallest `quote_amount` a buy needs, fees included, to receive at least one B at the
+    /// current reserves. `None` when no amount can.
+    pub fn min_buy_quote_amount(&self) -> Option<u64> {
+        let min_swap_amount = calculate_min_buy_input_amount(
+            self.quote_reserve,
+            self.base_reserve,
+            self.quote_virtual_reserve,
+        )?;
+        let fee_free_bp = 10_000u128.checked_sub(self.total_fee_bp() as u128)?;
+        let mut quote_amount =
+            u64::try_from((min_swap_amount as u128 * 10_000).div_ceil(fee_free_bp)).ok()?;
+        // Each fee rounds up on its own, which can cost a few more units
+        loop {
+            let fees = calculate_fees(
+                quote_amount,
+                self.creator_fee_bp,
+                self.buyback_fee_bp,
+                self.platform_fee_bp,
+            )
+            .ok()?;
+            if quote_amount.saturating_sub(fees.total_fees_amount()) >= min_swap_amount {
+                return Some(quote_amount);
+            }
+            quote_amount = quote_amount.checked_add(1)?;
+        }
+    }
+
+    /// Marginal price of buying B, fees included, as Q64.64
+    pub fn effective_buy_price_x64(&self) -> Result<u128> {
+        calculate_effective_buy_price_x64(self.spot_price_x64()?, self.total_fee_bp())
+    }
+
+    /// Marginal price of selling B, fees included, as Q64.64
+    pub fn effective_sell_price_x64(&self) -> Result<u128> {
+        calculate_effective_sell_price_x64(self.spot_price_x64()?, self.total_fee_bp())
+    }
+
+    /// Moves the referral share of `quote_amount` out of the platform fee `fees` booked for the
+    /// same trade. Returns the amount owed to the referrer.
+    pub fn take_referral_fee(
+        &mut self,
+        quote_amount: u64,
+        fees: &Fees,
+        platform_config: &mut PlatformConfig,
+    ) -> Result<u64> {
+        let referral_amount = ((quote_amount as u128 * platform_config.referral_fee_bp as u128)
+            / 10_000)
+            .min(fees.platform_fees_amount as u128) as u64;
+        self.platform_fees_balance -= referral_amount;
+        platform_config.lifetime_platform_fees = platform_config
+            .lifetime_platform_fees
+            .saturating_sub(referral_amount as u128);
+        Ok(referral_amount)
     }
 
     pub fn quote_to_base(&mut self, quote_amount: u64) -> anchor_lang::prelude::Result<SwapResult> {
-        let base_amount = self.calculate_base_output_amount(quote_amount);
+        let base_amount = self.calculate_base_output_amount(quote_amount)?;
         self.base_reserve = self
             .base_reserve
             .checked_sub(base_amount)
@@ -331,7 +999,7 @@ impl CbmmPool {
     }
 
     pub fn base_to_quote(&mut self, base_amount: u64) -> anchor_lang::prelude::Result<SwapResult> {
-        let quote_amount = self.calculate_quote_output_amount(base_amount);
+        let quote_amount = self.calculate_quote_output_amount(base_amount)?;
         self.quote_reserve = self
             .quote_reserve
             .checked_sub(quote_amount)
@@ -346,30 +1014,61 @@ impl CbmmPool {
         })
     }
 
-    fn calculate_quote_output_amount(&self, base_amount: u64) -> u64 {
-        calculate_sell_output_amount(
+    fn calculate_quote_output_amount(&self, base_amount: u64) -> Result<u64> {
+        let output = calculate_sell_output_amount(
             base_amount,
             self.base_reserve,
             self.quote_reserve,
             self.quote_virtual_reserve,
-        )
-    }
-
-    fn calculate_base_output_amount(&self, quote_amount: u64) -> u64 {
-        calculate_buy_output_amount(
-            quote_amount,
-            self.quote_reserve,
+        )?;
+        if !self.round_in_favor_of_trader {
+            return Ok(output);
+        }
+        // The remainder is only handed out while the real reserve can pay it
+        Ok(calculate_sell_output_amount_rounded_up(
+            base_amount,
             self.base_reserve,
+            self.quote_reserve,
             self.quote_virtual_reserve,
-        )
+        )?
+        .min(self.quote_reserve)
+        .max(output))
+    }
+
+    fn calculate_base_output_amount(&self, quote_amount: u64) -> Result<u64> {
+        if !self.round_in_favor_of_trader {
+            calculate_buy_output_amount(
+                quote_amount,
+                self.quote_reserve,
+                self.base_reserve,
+                self.quote_virtual_reserve,
+            )
+        } else {
+            calculate_buy_output_amount_rounded_up(
+                quote_amount,
+                self.quote_reserve,
+                self.base_reserve,
+                self.quote_virtual_reserve,
+            )
+        }
     }
 
     pub fn burn(&mut self, config: &BurnRateConfig, requested_bp_x100: u32) -> Result<BurnResult> {
-        let allowed_burn = self.burn_limiter.calculate_required_bp_x100(
-            requested_bp_x100,
-            &config,
-            Clock::get()?.unix_timestamp,
-        )?;
+        self.burn_at(config, requested_bp_x100, Clock::get()?.unix_timestamp)
+    }
+
+    /// [`Self::burn`] with the rate limiter evaluated at unix time `now`
+    pub fn burn_at(
+        &mut self,
+        config: &BurnRateConfig,
+        requested_bp_x100: u32,
+        now: i64,
+    ) -> Result<BurnResult> {
+        require!(!self.graduated, CbmmError::AlreadyGraduated);
+        let pending_before = self.burn_limiter.pending_queue_shares_bp_x10k;
+        let allowed_burn =
+            self.burn_limiter
+                .calculate_required_bp_x100(requested_bp_x100, config, now)?;
 
         let allowed_burn_bp_x100;
         match allowed_burn {
@@ -379,31 +1078,64 @@ impl CbmmPool {
                 return Ok(BurnResult {
                     rate_limit_result: RateLimitResult::Queued,
                     burn_amount: 0,
+                    flushed_queue: false,
                 })
             }
         }
 
-        let burn_amount = calculate_burn_amount(allowed_burn_bp_x100, self.base_reserve);
+        let burnable = self.base_reserve.saturating_sub(self.min_base_reserve);
+        let burn_amount =
+            calculate_burn_amount(allowed_burn_bp_x100, self.base_reserve).min(burnable);
+        self.apply_burn(burn_amount)?;
+        Ok(BurnResult {
+            rate_limit_result: allowed_burn,
+            burn_amount,
+            flushed_queue: pending_before > 0,
+        })
+    }
 
+    /// Removes `burn_amount` of B from the pool. The virtual reserve scales with the base reserve
+    /// to keep the spot price, while the optimal virtual reserve scales with the total supply to
+    /// keep the worst-case exit price `quote_optimal_virtual_reserve / base_total_supply`.
+    fn apply_burn(&mut self, burn_amount: u64) -> Result<()> {
+        let quote_virtual_reserve_before = self.quote_virtual_reserve;
+        let quote_reserve_before = self.quote_reserve;
         self.quote_virtual_reserve = calculate_new_virtual_reserve_after_burn(
             self.quote_virtual_reserve,
             self.base_reserve,
             burn_amount,
         );
+        // Scaled from its own pre-burn value, as the virtual reserve may sit below the optimal
+        // curve and was already reduced above
         self.quote_optimal_virtual_reserve = calculate_new_virtual_reserve_after_burn(
-            self.quote_virtual_reserve,
+            self.quote_optimal_virtual_reserve,
             self.base_total_supply,
             burn_amount,
         );
         self.base_reserve -= burn_amount;
         self.base_total_supply -= burn_amount;
-        Ok(BurnResult {
-            rate_limit_result: allowed_burn,
-            burn_amount,
-        })
+        self.record_burn_liability(quote_virtual_reserve_before, quote_reserve_before)
     }
 
-    pub fn topup(&mut self) -> Result<u64> {
+    /// Adds the liability `ΔV - ΔA` left by a burn to `quote_outstanding_topup`
+    fn record_burn_liability(
+        &mut self,
+        quote_virtual_reserve_before: u64,
+        quote_reserve_before: u64,
+    ) -> Result<()> {
+        let virtual_delta = quote_virtual_reserve_before
+            .checked_sub(self.quote_virtual_reserve)
+            .ok_or(CbmmError::Underflow)?;
+        let real_delta = quote_reserve_before.saturating_sub(self.quote_reserve);
+        self.quote_outstanding_topup = self
+            .quote_outstanding_topup
+            .checked_add(virtual_delta.saturating_sub(real_delta))
+            .ok_or(CbmmError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Returns the real A reserve missing to reach the optimal curve and that curve's virtual reserve.
+    fn topup_target(&self) -> Result<(u64, u64)> {
         let quote_optimal_virtual_reserve = calculate_optimal_virtual_quote_reserve(
             self.quote_starting_virtual_reserve,
             self.base_starting_total_supply,
@@ -419,14 +1151,23 @@ impl CbmmPool {
         let needed_topup_amount = quote_optimal_real_reserve
             .checked_sub(self.quote_reserve)
             .ok_or(CbmmError::MathOverflow)?;
-        if needed_topup_amount == 0 {
-            return Ok(0);
-        }
+        Ok((needed_topup_amount, quote_optimal_virtual_reserve))
+    }
 
-        let real_topup_amount = needed_topup_amount.min(self.buyback_fees_balance);
-        self.buyback_fees_balance -= real_topup_amount;
-        self.quote_reserve += real_topup_amount;
-        self.quote_virtual_reserve = if real_topup_amount < needed_topup_amount {
+    /// Adds `amount` to the real reserve. Short of `needed_topup_amount` the virtual reserve is
+    /// recomputed to keep the pool solvent, otherwise the optimal curve is restored.
+    fn apply_topup(
+        &mut self,
+        amount: u64,
+        needed_topup_amount: u64,
+        quote_optimal_virtual_reserve: u64,
+    ) -> Result<()> {
+        self.quote_outstanding_topup = self.quote_outstanding_topup.saturating_sub(amount);
+        self.quote_reserve = self
+            .quote_reserve
+            .checked_add(amount)
+            .ok_or(CbmmError::MathOverflow)?;
+        self.quote_virtual_reserve = if amount < needed_topup_amount {
             calculate_new_virtual_reserve_after_topup(
                 self.quote_reserve,
                 self.base_reserve,
@@ -435,9 +1176,66 @@ impl CbmmPool {
         } else {
             quote_optimal_virtual_reserve
         };
+        Ok(())
+    }
+
+    pub fn topup(&mut self) -> Result<u64> {
+        let (needed_topup_amount, quote_optimal_virtual_reserve) = self.topup_target()?;
+        if needed_topup_amount == 0 {
+            return Ok(0);
+        }
+
+        let real_topup_amount = needed_topup_amount.min(self.buyback_fees_balance);
+        self.buyback_fees_balance -= real_topup_amount;
+        self.apply_topup(
+            real_topup_amount,
+            needed_topup_amount,
+            quote_optimal_virtual_reserve,
+        )?;
         Ok(real_topup_amount)
     }
 
+    /// Real A reserve an external deposit must add, after the buyback fees are spent, to put the
+    /// pool back on its optimal curve.
+    pub fn topup_deposit_amount(&self) -> Result<u64> {
+        Ok(self.topup_target()?.0)
+    }
+
+    /// Credits a donated `amount` of A to the real reserve like a fee-funded topup.
+    pub fn deposit_topup(&mut self, amount: u64) -> Result<()> {
+        let (needed_topup_amount, quote_optimal_virtual_reserve) = self.topup_target()?;
+        self.apply_topup(amount, needed_topup_amount, quote_optimal_virtual_reserve)
+    }
+
+    /// Empties the real quote reserve for migration and computes the B amount to pair it
+    /// with so the external pool opens at the curve's final spot price.
+    pub fn take_migration_amounts(&mut self) -> Result<SwapResult> {
+        require!(self.graduated, CbmmError::NotGraduated);
+        require!(self.migrated_lp_owner.is_none(), CbmmError::AlreadyMigrated);
+        require_gt!(self.quote_reserve, 0, CbmmError::AmountTooSmall);
+
+        let quote_amount = self.quote_reserve;
+        let base_amount = (quote_amount as u128 * self.base_reserve as u128
+            / (quote_amount as u128 + self.quote_virtual_reserve as u128))
+            as u64;
+        require_gt!(base_amount, 0, CbmmError::AmountTooSmall);
+        self.quote_reserve = 0;
+        Ok(SwapResult {
+            quote_amount,
+            base_amount,
+        })
+    }
+
+    /// Takes the keeper's cut out of the buyback fees balance. The caller transfers it out.
+    pub fn take_keeper_reward(&mut self, keeper_reward_bp: u16) -> Result<u64> {
+        let reward = (self.buyback_fees_balance as u128 * keeper_reward_bp as u128 / 10_000) as u64;
+        self.buyback_fees_balance = self
+            .buyback_fees_balance
+            .checked_sub(reward)
+            .ok_or(CbmmError::Underflow)?;
+        Ok(reward)
+    }
+
     pub fn transfer_out<'info>(
         &mut self,
         amount: u64,
@@ -459,8 +1257,9 @@ impl CbmmPool {
         let signer_seeds: &[&[&[u8]]] = &[&[
             CBMM_POOL_SEED,
             pool_index_bytes.as_slice(),
-            self.creator.as_ref(),
+            self.seed_creator.as_ref(),
             self.platform_config.as_ref(),
+            self.quote_mint.as_ref(),
             &[bump_seed],
         ]];
         let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
@@ -469,6 +1268,80 @@ impl CbmmPool {
         transfer_checked(cpi_context, amount, decimals)?;
         Ok(())
     }
+
+    pub fn mint_base_to<'info>(
+        &self,
+        amount: u64,
+        pool_account_info: &AccountInfo<'info>,
+        base_mint: &InterfaceAccount<'info, Mint>,
+        to: &InterfaceAccount<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        let cpi_accounts = MintTo {
+            mint: base_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: pool_account_info.clone(),
+        };
+        let bump_seed = self.bump;
+        let pool_index_bytes = self.pool_index.to_le_bytes().to_vec();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            CBMM_POOL_SEED,
+            pool_index_bytes.as_slice(),
+            self.seed_creator.as_ref(),
+            self.platform_config.as_ref(),
+            self.quote_mint.as_ref(),
+            &[bump_seed],
+        ]];
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        mint_to(cpi_context, amount)
+    }
+
+    pub fn close_pool_ata<'info>(
+        &self,
+        pool_account_info: &AccountInfo<'info>,
+        pool_ata: &InterfaceAccount<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        let cpi_accounts = CloseAccount {
+            account: pool_ata.to_account_info(),
+            destination: destination.clone(),
+            authority: pool_account_info.clone(),
+        };
+        let bump_seed = self.bump;
+        let pool_index_bytes = self.pool_index.to_le_bytes().to_vec();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            CBMM_POOL_SEED,
+            pool_index_bytes.as_slice(),
+            self.seed_creator.as_ref(),
+            self.platform_config.as_ref(),
+            self.quote_mint.as_ref(),
+            &[bump_seed],
+        ]];
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        close_account(cpi_context)
+    }
+
+    /// A pool can be closed once no virtual tokens are held by users and the
+    /// claimable fee balances have been withdrawn.
+    pub fn is_closable(&self) -> bool {
+        self.base_reserve == self.base_total_supply
+            && self.creator_fees_balance == 0
+            && self.platform_fees_balance == 0
+    }
+
+    /// A held in the pool ATA that does not belong to the creator and stays behind when the
+    /// pool is closed: buyback fees, unclaimed platform fees and the locked initial liquidity.
+    pub fn non_creator_balance(&self) -> Result<u64> {
+        let balance = self
+            .buyback_fees_balance
+            .checked_add(self.platform_fees_balance)
+            .and_then(|balance| balance.checked_add(self.locked_quote_reserve))
+            .ok_or(CbmmError::MathOverflow)?;
+        Ok(balance)
+    }
 }
 
 #[account]
@@ -482,6 +1355,22 @@ pub struct VirtualTokenAccount {
     pub owner: Pubkey,
     /// Balance of Mint B including decimals
     pub balance: u64,
+    /// Timestamp of the last buy, used for the pool's sell lockup
+    pub last_buy_ts: i64,
+    /// Quote spent on buys including fees, for cost basis
+    pub quote_spent: u64,
+    /// Quote received from sells after fees
+    pub quote_received: u64,
+    /// Account allowed to sell up to `delegated_amount` on the owner's behalf
+    pub delegate: Option<Pubkey>,
+    pub delegated_amount: u64,
+
+    pub version: u8,
+    /// Set by the platform admin via `set_vta_frozen`; frozen accounts cannot trade
+    pub frozen: bool,
+    /// Timestamp of the last sell
+    pub last_sell_ts: i64,
+    pub _reserved: Reserved<{ RESERVED_SPACE - 1 - 8 }>,
 }
 
 impl VirtualTokenAccount {
@@ -491,6 +1380,15 @@ impl VirtualTokenAccount {
             pool,
             owner,
             balance: 0,
+            last_buy_ts: 0,
+            quote_spent: 0,
+            quote_received: 0,
+            delegate: None,
+            delegated_amount: 0,
+            version: CURRENT_ACCOUNT_VERSION,
+            frozen: false,
+            last_sell_ts: 0,
+            _reserved: Reserved::default(),
         }
     }
 
@@ -509,6 +1407,152 @@ impl VirtualTokenAccount {
             .ok_or(CbmmError::MathOverflow)?;
         Ok(())
     }
+
+    /// Checks that `spender` may sell `base_amount` and, for a delegate, consumes the allowance.
+    pub fn spend(&mut self, spender: Pubkey, base_amount: u64) -> Result<()> {
+        if spender == self.owner {
+            return Ok(());
+        }
+        require!(self.delegate == Some(spender), CbmmError::InvalidDelegate);
+        self.delegated_amount = self
+            .delegated_amount
+            .checked_sub(base_amount)
+            .ok_or(CbmmError::InsufficientDelegatedAmount)?;
+        Ok(())
+    }
+
+    /// Net quote taken out minus quote put in. Ignores the value of the remaining balance.
+    pub fn realized_pnl(&self) -> i128 {
+        self.quote_received as i128 - self.quote_spent as i128
+    }
+}
+
+/// When a trade pays the outstanding topup relative to its swap. Topping up first moves the
+/// price bump to the trader's disadvantage on buys and advantage on sells.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace, PartialEq)]
+pub enum TopupOrder {
+    #[default]
+    BeforeBuyAfterSell, // Original ordering, the bump works against the trader on both sides
+    BeforeTrade,
+    AfterTrade,
+}
+
+impl TopupOrder {
+    pub fn topup_before_swap(&self, is_buy: bool) -> bool {
+        match self {
+            TopupOrder::BeforeBuyAfterSell => is_buy,
+            TopupOrder::BeforeTrade => true,
+            TopupOrder::AfterTrade => false,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace, PartialEq)]
+pub enum OrderDirection {
+    #[default]
+    StopLoss, // Sell once the price falls to or below the trigger
+    TakeProfit, // Sell once the price rises to or above the trigger
+}
+
+/// Conditional sell placed by `owner`. The base amount is escrowed out of the owner's
+/// virtual token account until the order is executed or cancelled.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Order {
+    pub bump: u8,
+    // seeds
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+
+    /// Escrowed B amount including decimals
+    pub base_amount: u64,
+    /// Spot price of B in A as Q64.64 at which the order becomes executable
+    pub trigger_price_x64: u128,
+    pub direction: OrderDirection,
+    /// Least A the owner accepts for the escrowed B, after fees
+    pub min_quote_amount: u64,
+}
+
+impl Order {
+    pub fn is_triggered(&self, price_x64: u128) -> bool {
+        match self.direction {
+            OrderDirection::StopLoss => price_x64 <= self.trigger_price_x64,
+            OrderDirection::TakeProfit => price_x64 >= self.trigger_price_x64,
+        }
+    }
+}
+
+/// Resting buy placed by `owner`. The quote amount is escrowed in an ATA owned by the
+/// order and spent by keepers whenever the pool price is at or below `max_price_x64`.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct LimitBuyOrder {
+    pub bump: u8,
+    // seeds
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+
+    /// Remaining escrowed A amount including decimals
+    pub quote_amount: u64,
+    /// Highest price of B in A as Q64.64 the owner is willing to pay, fees included
+    pub max_price_x64: u128,
+}
+
+impl LimitBuyOrder {
+    pub fn transfer_out<'info>(
+        &self,
+        amount: u64,
+        order_account_info: &AccountInfo<'info>,
+        mint: &InterfaceAccount<'info, Mint>,
+        escrow_ata: &InterfaceAccount<'info, TokenAccount>,
+        to: &InterfaceAccount<'info, TokenAccount>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            mint: mint.to_account_info(),
+            from: escrow_ata.to_account_info(),
+            to: to.to_account_info(),
+            authority: order_account_info.clone(),
+        };
+        let order_id_bytes = self.order_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            LIMIT_BUY_ORDER_SEED,
+            self.pool.as_ref(),
+            self.owner.as_ref(),
+            order_id_bytes.as_ref(),
+            &[self.bump],
+        ]];
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        transfer_checked(cpi_context, amount, mint.decimals)
+    }
+
+    pub fn close_escrow_ata<'info>(
+        &self,
+        order_account_info: &AccountInfo<'info>,
+        escrow_ata: &InterfaceAccount<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Interface<'info, TokenInterface>,
+    ) -> Result<()> {
+        let cpi_accounts = CloseAccount {
+            account: escrow_ata.to_account_info(),
+            destination: destination.clone(),
+            authority: order_account_info.clone(),
+        };
+        let order_id_bytes = self.order_id.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            LIMIT_BUY_ORDER_SEED,
+            self.pool.as_ref(),
+            self.owner.as_ref(),
+            order_id_bytes.as_ref(),
+            &[self.bump],
+        ]];
+        let cpi_context = CpiContext::new(token_program.to_account_info(), cpi_accounts)
+            .with_signer(signer_seeds);
+        close_account(cpi_context)
+    }
 }
 
 #[account]
@@ -527,10 +1571,12 @@ pub struct UserBurnAllowance {
     pub last_burn_timestamp: i64,
 
     pub created_at: i64,
+    /// Burns the rate limiter queued in the current day. They are not counted in `burns_today`
+    /// but still count towards the tier's `max_daily_burns`.
+    pub queued_today: u16,
 }
 
 impl UserBurnAllowance {
-    const RESET_INTERVAL_SECONDS: i64 = 86400;
     pub fn new(
         bump: u8,
         user: Pubkey,
@@ -550,34 +1596,147 @@ impl UserBurnAllowance {
             created_at: now,
             burn_tier_index,
             burn_tier_update_timestamp,
+            queued_today: 0,
         }
     }
 
-    pub fn pop(&mut self) -> Result<u16> {
-        let now = Clock::get()?.unix_timestamp;
-        if self.should_reset(now) {
-            self.burns_today = 0;
+    /// Burns counted in the day containing `now`. The stored count is only reset lazily by the
+    /// next burn, so it can still hold a previous day's burns.
+    pub fn effective_burns_today(&self, now: i64, reset_interval_seconds: i64) -> u16 {
+        if self.should_reset(now, reset_interval_seconds) {
+            0
+        } else {
+            self.burns_today
+        }
+    }
+
+    /// Burns queued in the day containing `now`, see `effective_burns_today`
+    pub fn effective_queued_today(&self, now: i64, reset_interval_seconds: i64) -> u16 {
+        if self.should_reset(now, reset_interval_seconds) {
+            0
+        } else {
+            self.queued_today
         }
-        self.burns_today += 1;
+    }
+
+    /// Index the next burn would take in the day containing `now`, queued burns included,
+    /// without counting it yet
+    pub fn peek_at(&self, reset_interval_seconds: i64, now: i64) -> Result<u16> {
+        self.effective_burns_today(now, reset_interval_seconds)
+            .checked_add(self.effective_queued_today(now, reset_interval_seconds))
+            .and_then(|burns| burns.checked_add(1))
+            .ok_or(CbmmError::MathOverflow.into())
+    }
+
+    /// Counts an executed burn at unix time `now`
+    pub fn pop_at(&mut self, reset_interval_seconds: i64, now: i64) -> Result<u16> {
+        self.reset_at(reset_interval_seconds, now);
+        self.burns_today = self
+            .burns_today
+            .checked_add(1)
+            .ok_or(CbmmError::MathOverflow)?;
         self.last_burn_timestamp = now;
         Ok(self.burns_today)
     }
 
-    pub fn is_closable(&self, platform_burn_tiers_updated_at: i64, now: i64) -> bool {
-        self.burns_today == 0
+    /// Counts a burn the rate limiter queued at unix time `now`
+    pub fn queue_at(&mut self, reset_interval_seconds: i64, now: i64) -> Result<u16> {
+        self.reset_at(reset_interval_seconds, now);
+        self.queued_today = self
+            .queued_today
+            .checked_add(1)
+            .ok_or(CbmmError::MathOverflow)?;
+        self.last_burn_timestamp = now;
+        Ok(self.queued_today)
+    }
+
+    fn reset_at(&mut self, reset_interval_seconds: i64, now: i64) {
+        if self.should_reset(now, reset_interval_seconds) {
+            self.burns_today = 0;
+            self.queued_today = 0;
+        }
+    }
+
+    pub fn is_closable(
+        &self,
+        platform_burn_tiers_updated_at: i64,
+        reset_interval_seconds: i64,
+        now: i64,
+    ) -> bool {
+        (self.burns_today == 0 && self.queued_today == 0)
             || platform_burn_tiers_updated_at > self.burn_tier_update_timestamp
-            || now - self.last_burn_timestamp >= Self::RESET_INTERVAL_SECONDS
+            || now - self.last_burn_timestamp >= reset_interval_seconds
+    }
+
+    /// Successor of this allowance under the current burn tiers. The burn counts, and the day
+    /// they are counted on, carry over if the last burn still falls in the current day.
+    pub fn migrate(
+        &self,
+        bump: u8,
+        payer: Pubkey,
+        burn_tier_index: u8,
+        burn_tier_update_timestamp: i64,
+        reset_interval_seconds: i64,
+        now: i64,
+    ) -> Self {
+        let mut migrated = Self::new(
+            bump,
+            self.user,
+            self.platform_config,
+            payer,
+            burn_tier_index,
+            burn_tier_update_timestamp,
+            now,
+        );
+        let counted = self.burns_today > 0 || self.queued_today > 0;
+        if counted && !self.should_reset(now, reset_interval_seconds) {
+            migrated.burns_today = self.burns_today;
+            migrated.queued_today = self.queued_today;
+            migrated.last_burn_timestamp = self.last_burn_timestamp;
+            migrated.created_at = self.created_at;
+        }
+        migrated
     }
 
-    fn should_reset(&self, now: i64) -> bool {
-        let reset_offset = self.created_at % Self::RESET_INTERVAL_SECONDS;
+    /// Whether `now` falls in a later day than the last burn, so the next burn restarts the count
+    pub fn should_reset(&self, now: i64, reset_interval_seconds: i64) -> bool {
+        let reset_offset = self.created_at % reset_interval_seconds;
         let day_last =
-            (self.last_burn_timestamp.saturating_sub(reset_offset)) / Self::RESET_INTERVAL_SECONDS;
-        let day_now = (now.saturating_sub(reset_offset)) / Self::RESET_INTERVAL_SECONDS;
+            (self.last_burn_timestamp.saturating_sub(reset_offset)) / reset_interval_seconds;
+        let day_now = (now.saturating_sub(reset_offset)) / reset_interval_seconds;
         day_last < day_now
     }
 }
 
+/// Recurring burn from `authority`'s allowance on `tier_index`, cranked by keepers.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct BurnSchedule {
+    pub bump: u8,
+    // seeds
+    pub pool: Pubkey,
+
+    /// Wallet whose burn allowance the schedule spends, the pool creator at creation
+    pub authority: Pubkey,
+    pub tier_index: u8,
+    pub interval_seconds: i64,
+    pub next_run_ts: i64,
+}
+
+impl BurnSchedule {
+    /// Moves `next_run_ts` to the first slot after `now`. Slots missed by a late keeper are
+    /// skipped rather than replayed, so the cadence never bunches up.
+    pub fn advance(&mut self, now: i64) -> Result<()> {
+        require_gte!(now, self.next_run_ts, CbmmError::BurnScheduleNotDue);
+        let elapsed_intervals = (now - self.next_run_ts) / self.interval_seconds + 1;
+        self.next_run_ts = elapsed_intervals
+            .checked_mul(self.interval_seconds)
+            .and_then(|delta| self.next_run_ts.checked_add(delta))
+            .ok_or(CbmmError::MathOverflow)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -585,6 +1744,9 @@ mod tests {
 
     const CREATED_AT: i64 = 1761177600;
     const DAY: i64 = 86400;
+    const OWNER: Pubkey = Pubkey::new_from_array([1; 32]);
+    const DELEGATE: Pubkey = Pubkey::new_from_array([2; 32]);
+    const STRANGER: Pubkey = Pubkey::new_from_array([3; 32]);
 
     #[test_case(CREATED_AT, 0, CREATED_AT, true; "reset_on_creation")]
     #[test_case(CREATED_AT, CREATED_AT, CREATED_AT + 1, false; "reset_on_creation_and_immediately_after_creation")]
@@ -604,6 +1766,930 @@ mod tests {
             created_at,
         );
         user_burn_allowance.last_burn_timestamp = last_burn_timestamp;
-        assert_eq!(user_burn_allowance.should_reset(now), should_reset);
+        assert_eq!(user_burn_allowance.should_reset(now, DAY), should_reset);
+    }
+
+    const MINUTE: i64 = 60;
+
+    #[test_case(CREATED_AT, CREATED_AT, CREATED_AT + MINUTE - 1, false; "same_interval")]
+    #[test_case(CREATED_AT, CREATED_AT + MINUTE - 1, CREATED_AT + MINUTE, true; "next_interval")]
+    #[test_case(CREATED_AT + 30, CREATED_AT + 30, CREATED_AT + MINUTE + 29, false; "offset_same_interval")]
+    #[test_case(CREATED_AT + 30, CREATED_AT + 30, CREATED_AT + MINUTE + 30, true; "offset_next_interval")]
+    fn test_should_reset_short_interval(
+        created_at: i64,
+        last_burn_timestamp: i64,
+        now: i64,
+        should_reset: bool,
+    ) {
+        let mut user_burn_allowance = UserBurnAllowance::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            0,
+            0,
+            created_at,
+        );
+        user_burn_allowance.last_burn_timestamp = last_burn_timestamp;
+        assert_eq!(user_burn_allowance.should_reset(now, MINUTE), should_reset);
+    }
+
+    #[test_case(CREATED_AT + DAY - 1, 2; "same_day")]
+    #[test_case(CREATED_AT + DAY, 0; "next_day")]
+    fn test_effective_burns_today(now: i64, expected: u16) {
+        let mut user_burn_allowance = UserBurnAllowance::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            0,
+            0,
+            CREATED_AT,
+        );
+        user_burn_allowance.burns_today = 2;
+        user_burn_allowance.last_burn_timestamp = CREATED_AT + 1;
+        assert_eq!(
+            user_burn_allowance.effective_burns_today(now, DAY),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pop_burns_today_overflow() {
+        let mut user_burn_allowance = UserBurnAllowance::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            0,
+            0,
+            CREATED_AT,
+        );
+        user_burn_allowance.burns_today = u16::MAX - 1;
+        user_burn_allowance.last_burn_timestamp = CREATED_AT + 1;
+        assert_eq!(
+            user_burn_allowance.pop_at(DAY, CREATED_AT + 2).unwrap(),
+            u16::MAX
+        );
+        assert_eq!(
+            user_burn_allowance.pop_at(DAY, CREATED_AT + 3).unwrap_err(),
+            CbmmError::MathOverflow.into()
+        );
+        // A new day starts the count over
+        assert_eq!(
+            user_burn_allowance
+                .pop_at(DAY, CREATED_AT + DAY + 1)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_queue_counts_towards_peek_only() {
+        let mut user_burn_allowance = UserBurnAllowance::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            0,
+            0,
+            CREATED_AT,
+        );
+        user_burn_allowance.pop_at(DAY, CREATED_AT + 1).unwrap();
+        assert_eq!(
+            user_burn_allowance.queue_at(DAY, CREATED_AT + 2).unwrap(),
+            1
+        );
+        assert_eq!(user_burn_allowance.burns_today, 1);
+        assert_eq!(user_burn_allowance.peek_at(DAY, CREATED_AT + 3).unwrap(), 3);
+        assert!(!user_burn_allowance.is_closable(0, DAY, CREATED_AT + 3));
+        // Both counts restart with the day
+        assert_eq!(
+            user_burn_allowance
+                .peek_at(DAY, CREATED_AT + DAY + 1)
+                .unwrap(),
+            1
+        );
+        user_burn_allowance
+            .pop_at(DAY, CREATED_AT + DAY + 1)
+            .unwrap();
+        assert_eq!(user_burn_allowance.queued_today, 0);
+    }
+
+    #[test_case(MINUTE, CREATED_AT + MINUTE - 1, false; "short_interval_active")]
+    #[test_case(MINUTE, CREATED_AT + MINUTE, true; "short_interval_elapsed")]
+    #[test_case(DAY, CREATED_AT + MINUTE, false; "day_interval_active")]
+    fn test_is_closable_reset_interval(reset_interval_seconds: i64, now: i64, expected: bool) {
+        let mut user_burn_allowance = UserBurnAllowance::new(
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            0,
+            0,
+            CREATED_AT,
+        );
+        user_burn_allowance.burns_today = 1;
+        user_burn_allowance.last_burn_timestamp = CREATED_AT;
+        assert_eq!(
+            user_burn_allowance.is_closable(0, reset_interval_seconds, now),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_collect_fees_dust_amount() {
+        let mut pool = CbmmPool {
+            creator_fee_bp: 200,
+            buyback_fee_bp: 600,
+            platform_fee_bp: 200,
+            ..Default::default()
+        };
+        // 1 unit would owe 3 units of rounded-up fees
+        assert_eq!(
+            pool.collect_fees(1, &mut PlatformConfig::default(), 0)
+                .unwrap_err(),
+            CbmmError::AmountTooSmall.into()
+        );
+        assert_eq!(pool.creator_fees_balance, 0);
+    }
+
+    #[test]
+    fn test_collect_fees_balance_overflow() {
+        let mut pool = CbmmPool {
+            creator_fee_bp: 200,
+            buyback_fee_bp: 600,
+            platform_fee_bp: 200,
+            buyback_fees_balance: u64::MAX - 10,
+            ..Default::default()
+        };
+        // 6% of 1000 doesn't fit in the remaining 10 units
+        assert_eq!(
+            pool.collect_fees(1000, &mut PlatformConfig::default(), 0)
+                .unwrap_err(),
+            CbmmError::MathOverflow.into()
+        );
+    }
+
+    #[test_case(0, 0, 0; "no_trades")]
+    #[test_case(1000, 1200, 200; "profit")]
+    #[test_case(1000, 0, -1000; "open_position")]
+    #[test_case(u64::MAX, 0, -(u64::MAX as i128); "max_spent")]
+    fn test_realized_pnl(quote_spent: u64, quote_received: u64, expected: i128) {
+        let virtual_token_account = VirtualTokenAccount {
+            quote_spent,
+            quote_received,
+            ..Default::default()
+        };
+        assert_eq!(virtual_token_account.realized_pnl(), expected);
+    }
+
+    #[test_case(TopupOrder::BeforeBuyAfterSell, true, true; "original_buy")]
+    #[test_case(TopupOrder::BeforeBuyAfterSell, false, false; "original_sell")]
+    #[test_case(TopupOrder::BeforeTrade, true, true; "before_buy")]
+    #[test_case(TopupOrder::BeforeTrade, false, true; "before_sell")]
+    #[test_case(TopupOrder::AfterTrade, true, false; "after_buy")]
+    #[test_case(TopupOrder::AfterTrade, false, false; "after_sell")]
+    fn test_topup_before_swap(topup_order: TopupOrder, is_buy: bool, expected: bool) {
+        assert_eq!(topup_order.topup_before_swap(is_buy), expected);
+    }
+
+    #[test_case(OrderDirection::StopLoss, 99, true; "stop_loss_below")]
+    #[test_case(OrderDirection::StopLoss, 100, true; "stop_loss_at")]
+    #[test_case(OrderDirection::StopLoss, 101, false; "stop_loss_above")]
+    #[test_case(OrderDirection::TakeProfit, 99, false; "take_profit_below")]
+    #[test_case(OrderDirection::TakeProfit, 100, true; "take_profit_at")]
+    #[test_case(OrderDirection::TakeProfit, 101, true; "take_profit_above")]
+    fn test_order_is_triggered(direction: OrderDirection, price_x64: u128, expected: bool) {
+        let order = Order {
+            trigger_price_x64: 100,
+            direction,
+            ..Default::default()
+        };
+        assert_eq!(order.is_triggered(price_x64), expected);
+    }
+
+    #[test]
+    fn test_collect_fees_tracks_lifetime_platform_fees() {
+        let mut pool = CbmmPool {
+            creator_fee_bp: 200,
+            buyback_fee_bp: 600,
+            platform_fee_bp: 200,
+            ..Default::default()
+        };
+        let mut platform_config = PlatformConfig::default();
+        pool.collect_fees(5000, &mut platform_config, 0).unwrap();
+        pool.collect_fees(10_000, &mut platform_config, 0).unwrap();
+        assert_eq!(platform_config.lifetime_platform_fees, 300);
+
+        // Claiming resets the pool balance but not the lifetime counter
+        pool.platform_fees_balance = 0;
+        pool.collect_fees(5000, &mut platform_config, 0).unwrap();
+        assert_eq!(platform_config.lifetime_platform_fees, 400);
+    }
+
+    #[test_case(1000, 999, 10_000, 0; "during_holiday")]
+    #[test_case(1000, 1000, 9000, 1000; "holiday_over")]
+    #[test_case(0, 0, 9000, 1000; "no_holiday")]
+    fn test_collect_fees_fee_holiday(
+        fee_holiday_until: i64,
+        now: i64,
+        amount_after_fees: u64,
+        total_fees: u64,
+    ) {
+        let mut pool = CbmmPool {
+            creator_fee_bp: 200,
+            buyback_fee_bp: 600,
+            platform_fee_bp: 200,
+            fee_holiday_until,
+            ..Default::default()
+        };
+        let mut platform_config = PlatformConfig::default();
+        assert_eq!(
+            pool.collect_fees(10_000, &mut platform_config, now)
+                .unwrap()
+                .0,
+            amount_after_fees
+        );
+        assert_eq!(
+            pool.creator_fees_balance + pool.buyback_fees_balance + pool.platform_fees_balance,
+            total_fees
+        );
+        assert_eq!(
+            platform_config.lifetime_platform_fees,
+            pool.platform_fees_balance as u128
+        );
+    }
+
+    #[test_case(0, &[0, 0, 1], &[true, true, true]; "unthrottled")]
+    #[test_case(60, &[CREATED_AT, CREATED_AT + 59, CREATED_AT + 60], &[true, false, true]; "window")]
+    #[test_case(60, &[CREATED_AT, CREATED_AT + 30, CREATED_AT + 90], &[true, false, true]; "window_from_last_event")]
+    fn test_take_trade_event_slot(interval: u32, trades: &[i64], emitted: &[bool]) {
+        let mut pool = CbmmPool {
+            min_event_interval_seconds: interval,
+            ..Default::default()
+        };
+        let actual: Vec<bool> = trades
+            .iter()
+            .map(|&now| pool.take_trade_event_slot(now))
+            .collect();
+        assert_eq!(actual, emitted);
+    }
+
+    #[test_case(false, STRANGER, None, true; "not_required")]
+    #[test_case(true, OWNER, None, true; "creator_burns")]
+    #[test_case(true, STRANGER, Some(OWNER), true; "creator_cosigned")]
+    #[test_case(true, STRANGER, None, false; "cosign_missing")]
+    #[test_case(true, STRANGER, Some(DELEGATE), false; "wrong_cosigner")]
+    fn test_check_creator_cosign(
+        require_creator_cosign_on_burn: bool,
+        burner: Pubkey,
+        cosigner: Option<Pubkey>,
+        ok: bool,
+    ) {
+        let pool = CbmmPool {
+            creator: OWNER,
+            require_creator_cosign_on_burn,
+            ..Default::default()
+        };
+        let result = pool.check_creator_cosign(burner, cosigner);
+        if ok {
+            assert!(result.is_ok());
+        } else {
+            assert_eq!(result.unwrap_err(), CbmmError::CreatorCosignRequired.into());
+        }
+    }
+
+    #[test_case(0, 10_000, 0; "disabled")]
+    #[test_case(100, 10_000, 100; "half_of_platform_fee")]
+    #[test_case(200, 10_000, 200; "whole_platform_fee")]
+    #[test_case(100, 99, 0; "rounds_down")]
+    fn test_take_referral_fee(referral_fee_bp: u16, quote_amount: u64, expected: u64) {
+        let mut pool = CbmmPool {
+            creator_fee_bp: 200,
+            buyback_fee_bp: 600,
+            platform_fee_bp: 200,
+            ..Default::default()
+        };
+        let mut platform_config = PlatformConfig {
+            platform_fee_bp: 200,
+            referral_fee_bp,
+            ..Default::default()
+        };
+        let (_, fees) = pool
+            .collect_fees(quote_amount, &mut platform_config, 0)
+            .unwrap();
+        let platform_fees = pool.platform_fees_balance;
+
+        let referral_amount = pool
+            .take_referral_fee(quote_amount, &fees, &mut platform_config)
+            .unwrap();
+        assert_eq!(referral_amount, expected);
+        assert_eq!(pool.platform_fees_balance, platform_fees - expected);
+        assert_eq!(
+            platform_config.lifetime_platform_fees,
+            (platform_fees - expected) as u128
+        );
+    }
+
+    #[test_case(i64::MAX, 200, 0; "fee_holiday")]
+    #[test_case(0, 50, 25; "pool_platform_fee_below_referral_fee")]
+    fn test_take_referral_fee_capped_by_trade_platform_fee(
+        fee_holiday_until: i64,
+        platform_fee_bp: u16,
+        expected: u64,
+    ) {
+        let mut pool = CbmmPool {
+            platform_fee_bp,
+            fee_holiday_until,
+            platform_fees_balance: 1_000,
+            ..Default::default()
+        };
+        let mut platform_config = PlatformConfig {
+            referral_fee_bp: 100,
+            ..Default::default()
+        };
+        let (_, fees) = pool.collect_fees(5000, &mut platform_config, 0).unwrap();
+        assert_eq!(
+            pool.take_referral_fee(5000, &fees, &mut platform_config)
+                .unwrap(),
+            expected
+        );
+        // Earlier trades' platform fees stay with the platform
+        assert_eq!(pool.platform_fees_balance, 1_000);
+    }
+
+    #[test_case(1, 1_000_000; "single_unit_left")]
+    #[test_case(1_000, u64::MAX / 2; "huge_buy")]
+    fn test_quote_to_base_near_depleted_base_reserve(base_reserve: u64, quote_amount: u64) {
+        let mut pool = CbmmPool {
+            quote_virtual_reserve: MIN_VIRTUAL_RESERVE,
+            base_reserve,
+            ..Default::default()
+        };
+        // The constant product curve never hands out the full reserve, so no partial fill is needed
+        let result = pool.quote_to_base(quote_amount).unwrap();
+        assert!(result.base_amount < base_reserve);
+        assert_eq!(pool.base_reserve, base_reserve - result.base_amount);
+        assert_eq!(pool.quote_reserve, quote_amount);
+    }
+
+    #[test_case(1000, 1000, 1600; "on_time")]
+    #[test_case(1000, 1599, 1600; "late_within_interval")]
+    #[test_case(1000, 2300, 2800; "skips_missed_runs")]
+    fn test_burn_schedule_advance(next_run_ts: i64, now: i64, expected: i64) {
+        let mut schedule = BurnSchedule {
+            interval_seconds: 600,
+            next_run_ts,
+            ..Default::default()
+        };
+        schedule.advance(now).unwrap();
+        assert_eq!(schedule.next_run_ts, expected);
+    }
+
+    #[test]
+    fn test_burn_schedule_advance_rejects_early_run() {
+        let mut schedule = BurnSchedule {
+            interval_seconds: 600,
+            next_run_ts: 1000,
+            ..Default::default()
+        };
+        assert!(schedule.advance(999).is_err());
+        assert_eq!(schedule.next_run_ts, 1000);
+    }
+
+    fn envelope_config(envelope: u64, tiers: &[(u32, u16)]) -> PlatformConfig {
+        PlatformConfig {
+            pool_creator_fee_bp: 200,
+            pool_topup_fee_bp: 600,
+            platform_fee_bp: 200,
+            burn_rate_config: BurnRateConfig::new(90_000, 10, 50),
+            burn_reset_interval_seconds: PlatformConfig::DEFAULT_BURN_RESET_INTERVAL_SECONDS,
+            daily_burn_envelope_bp_x100: envelope,
+            burn_tiers: tiers
+                .iter()
+                .map(|&(burn_bp_x100, max_daily_burns)| BurnTier {
+                    burn_bp_x100,
+                    role: BurnRole::SpecificPubkey(Pubkey::new_unique()),
+                    max_daily_burns,
+                    expires_at: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test_case(0, &[(20_000, 0)], true; "envelope_disabled")]
+    #[test_case(50_000, &[(1_000, 5), (20_000, 2)], true; "within_envelope")]
+    #[test_case(45_000, &[(1_000, 5), (20_000, 2)], true; "exactly_at_envelope")]
+    #[test_case(44_999, &[(1_000, 5), (20_000, 2)], false; "over_committed")]
+    #[test_case(50_000, &[(1_000, 5), (20_000, 0)], false; "unlimited_tier")]
+    fn test_validate_daily_burn_envelope(envelope: u64, tiers: &[(u32, u16)], valid: bool) {
+        let config = envelope_config(envelope, tiers);
+        assert_eq!(config.validate_fees_and_burn_config().is_ok(), valid);
+    }
+
+    fn tier(role: BurnRole) -> BurnTier {
+        BurnTier {
+            burn_bp_x100: 1_000,
+            role,
+            max_daily_burns: 1,
+            expires_at: None,
+        }
+    }
+
+    #[test_case(vec![BurnRole::Anyone, BurnRole::PoolOwner], true; "distinct_roles")]
+    #[test_case(vec![BurnRole::PoolOwner, BurnRole::PoolOwner], false; "two_pool_owner_tiers")]
+    #[test_case(vec![BurnRole::Anyone, BurnRole::Anyone], false; "two_anyone_tiers")]
+    fn test_validate_burn_tier_roles(roles: Vec<BurnRole>, valid: bool) {
+        let mut config = envelope_config(0, &[]);
+        config.burn_tiers = roles.into_iter().map(tier).collect();
+        assert_eq!(config.validate_fees_and_burn_config().is_ok(), valid);
+    }
+
+    #[test_case(None, true; "no_expiry")]
+    #[test_case(Some(1_001), true; "expires_in_the_future")]
+    #[test_case(Some(1_000), false; "expires_now")]
+    #[test_case(Some(999), false; "already_expired")]
+    fn test_validate_burn_tier_expiries(expires_at: Option<i64>, valid: bool) {
+        let mut config = envelope_config(0, &[]);
+        config.burn_tiers = vec![BurnTier {
+            expires_at,
+            ..tier(BurnRole::PoolOwner)
+        }];
+        assert_eq!(config.validate_burn_tier_expiries(1_000).is_ok(), valid);
+        assert_eq!(
+            config.burn_tiers[0].is_expired(1_000),
+            !valid && expires_at.is_some()
+        );
+    }
+
+    #[test]
+    fn test_validate_burn_tier_pubkeys() {
+        let burner = Pubkey::new_unique();
+        let mut config = envelope_config(0, &[]);
+        config.burn_tiers = vec![
+            tier(BurnRole::SpecificPubkey(burner)),
+            tier(BurnRole::SpecificPubkey(Pubkey::new_unique())),
+            tier(BurnRole::PoolOwner),
+        ];
+        assert!(config.validate_fees_and_burn_config().is_ok());
+
+        config
+            .burn_tiers
+            .push(tier(BurnRole::SpecificPubkey(burner)));
+        assert!(config.validate_fees_and_burn_config().is_err());
+    }
+
+    #[test_case(0, 600, true; "legacy_config_keeps_default_floor")]
+    #[test_case(700, 600, false; "platform_floor_rejects_lower_fee")]
+    #[test_case(700, 700, true; "platform_floor_met")]
+    fn test_validate_topup_fee_floor(min_topup_fee_bp: u16, pool_topup_fee_bp: u16, valid: bool) {
+        let mut config = envelope_config(0, &[]);
+        config.min_topup_fee_bp = min_topup_fee_bp;
+        config.pool_topup_fee_bp = pool_topup_fee_bp;
+        assert_eq!(config.validate_fees_and_burn_config().is_ok(), valid);
+    }
+
+    #[test_case(0, 0, true; "ending_allowed_without_max")]
+    #[test_case(0, 1001, false; "legacy_config_rejects_holiday")]
+    #[test_case(3600, 1000 + 3600, true; "at_max")]
+    #[test_case(3600, 1000 + 3601, false; "above_max")]
+    fn test_validate_fee_holiday(
+        max_fee_holiday_seconds: u32,
+        fee_holiday_until: i64,
+        valid: bool,
+    ) {
+        let config = PlatformConfig {
+            max_fee_holiday_seconds,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.validate_fee_holiday(fee_holiday_until, 1000).is_ok(),
+            valid
+        );
+    }
+
+    #[test_case(0, 0, true; "unthrottled_without_max")]
+    #[test_case(0, 1, false; "legacy_config_rejects_throttle")]
+    #[test_case(60, 60, true; "at_max")]
+    #[test_case(60, 61, false; "above_max")]
+    fn test_validate_event_interval(
+        max_event_interval_seconds: u32,
+        min_event_interval_seconds: u32,
+        valid: bool,
+    ) {
+        let config = PlatformConfig {
+            max_event_interval_seconds,
+            ..Default::default()
+        };
+        assert_eq!(
+            config
+                .validate_event_interval(min_event_interval_seconds)
+                .is_ok(),
+            valid
+        );
+    }
+
+    #[test]
+    fn test_platform_fee_recipient_defaults_to_admin() {
+        let admin = Pubkey::new_unique();
+        let mut platform_config = PlatformConfig {
+            admin,
+            ..Default::default()
+        };
+        assert_eq!(platform_config.platform_fee_recipient(), admin);
+
+        let treasury = Pubkey::new_unique();
+        platform_config.fee_recipient = treasury;
+        assert_eq!(platform_config.platform_fee_recipient(), treasury);
+    }
+
+    #[test_case(CREATED_AT + DAY, CREATED_AT + DAY; "clock_forward")]
+    #[test_case(CREATED_AT, CREATED_AT + 1; "clock_unchanged")]
+    #[test_case(CREATED_AT - DAY, CREATED_AT + 1; "clock_backwards")]
+    fn test_touch_burn_tiers_updated_at(now: i64, expected: i64) {
+        let mut platform_config = PlatformConfig {
+            burn_tiers_updated_at: CREATED_AT,
+            ..Default::default()
+        };
+        platform_config.touch_burn_tiers_updated_at(now);
+        assert_eq!(platform_config.burn_tiers_updated_at, expected);
+
+        // Allowances from before the update stay stale
+        let allowance = UserBurnAllowance {
+            burn_tier_update_timestamp: CREATED_AT,
+            burns_today: 1,
+            last_burn_timestamp: now,
+            ..Default::default()
+        };
+        assert!(allowance.is_closable(platform_config.burn_tiers_updated_at, DAY, now));
+    }
+
+    #[test]
+    fn test_try_new_rejects_virtual_reserve_below_min() {
+        let result = CbmmPool::try_new(
+            0,
+            Pubkey::default(),
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            NewPoolParams {
+                quote_virtual_reserve: MIN_VIRTUAL_RESERVE - 1,
+                creator_fee_bp: 200,
+                buyback_fee_bp: 600,
+                platform_fee_bp: 200,
+                min_base_reserve: 1,
+                round_in_favor_of_trader: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            CbmmError::InvalidVirtualReserve.into()
+        );
+    }
+
+    #[test_case(OWNER, 500, 100, Ok(100); "owner_ignores_allowance")]
+    #[test_case(DELEGATE, 100, 100, Ok(0); "delegate_within_allowance")]
+    #[test_case(DELEGATE, 101, 100, Err(CbmmError::InsufficientDelegatedAmount); "delegate_beyond_allowance")]
+    #[test_case(STRANGER, 100, 1, Err(CbmmError::InvalidDelegate); "not_delegate")]
+    fn test_vta_spend(
+        spender: Pubkey,
+        base_amount: u64,
+        allowance: u64,
+        expected: std::result::Result<u64, CbmmError>,
+    ) {
+        let mut virtual_token_account = VirtualTokenAccount {
+            owner: OWNER,
+            delegate: Some(DELEGATE),
+            delegated_amount: allowance,
+            ..Default::default()
+        };
+        let result = virtual_token_account.spend(spender, base_amount);
+        match expected {
+            Ok(remaining) => {
+                assert!(result.is_ok());
+                assert_eq!(virtual_token_account.delegated_amount, remaining);
+            }
+            Err(err) => assert_eq!(result.err().unwrap(), err.into()),
+        }
+    }
+
+    #[test]
+    fn test_new_vta_has_current_version() {
+        let virtual_token_account = VirtualTokenAccount::try_new(0, OWNER, OWNER);
+        assert_eq!(virtual_token_account.version, CURRENT_ACCOUNT_VERSION);
+        assert!(virtual_token_account._reserved == Reserved::default());
+    }
+
+    #[test_case(1_000_000, 100_000; "on_optimal_curve")]
+    #[test_case(800_000, 100_000; "below_optimal_curve")]
+    #[test_case(800_000, 1; "dust_burn")]
+    fn test_burn_preserves_worst_case_exit_price(quote_virtual_reserve: u64, burn_amount: u64) {
+        let mut pool = CbmmPool {
+            quote_starting_virtual_reserve: 1_000_000,
+            quote_optimal_virtual_reserve: 1_000_000,
+            quote_virtual_reserve,
+            quote_reserve: 400_000,
+            base_starting_total_supply: 2_000_000,
+            base_total_supply: 2_000_000,
+            base_reserve: 1_000_000,
+            ..Default::default()
+        };
+        pool.apply_burn(burn_amount).unwrap();
+
+        assert_eq!(pool.base_total_supply, 2_000_000 - burn_amount);
+        // Exit price V_opt / S stays at 1M / 2M, rounded down by at most one unit of A
+        let ratio_before = 1_000_000u128 * pool.base_total_supply as u128;
+        let ratio_after = pool.quote_optimal_virtual_reserve as u128 * 2_000_000;
+        assert!(ratio_after <= ratio_before);
+        assert!(ratio_before - ratio_after < 2_000_000);
+        // And stays on the curve the topup targets
+        let expected = calculate_optimal_virtual_quote_reserve(
+            pool.quote_starting_virtual_reserve,
+            pool.base_starting_total_supply,
+            pool.base_total_supply,
+        );
+        assert!(expected - pool.quote_optimal_virtual_reserve <= 1);
+    }
+
+    #[test_case(500_000, 1000, 10_005; "virtual_reserve_only")]
+    #[test_case(500_000, 1500, 9505; "virtual_and_real_reserve")]
+    fn test_record_burn_liability(
+        quote_virtual_reserve_before: u64,
+        quote_reserve_before: u64,
+        expected: u64,
+    ) {
+        let mut pool = CbmmPool {
+            quote_virtual_reserve: 490_000,
+            quote_reserve: 1000,
+            quote_outstanding_topup: 5,
+            ..Default::default()
+        };
+        pool.record_burn_liability(quote_virtual_reserve_before, quote_reserve_before)
+            .unwrap();
+        // L = ΔV - ΔA
+        assert_eq!(pool.quote_outstanding_topup, expected);
+    }
+
+    #[test_case(200_000, 200_000, 100_000; "partial_topup")]
+    #[test_case(1_000_000, 500_000, 0; "full_topup")]
+    fn test_topup_pays_down_outstanding_topup(
+        buyback_fees_balance: u64,
+        expected_topup: u64,
+        expected_outstanding: u64,
+    ) {
+        let mut pool = CbmmPool {
+            quote_starting_virtual_reserve: 1_000_000,
+            base_starting_total_supply: 1_000_000,
+            base_total_supply: 1_000_000,
+            base_reserve: 500_000,
+            quote_reserve: 500_000,
+            quote_virtual_reserve: 500_000,
+            buyback_fees_balance,
+            quote_outstanding_topup: 300_000,
+            ..Default::default()
+        };
+        assert_eq!(pool.topup().unwrap(), expected_topup);
+        assert_eq!(pool.quote_outstanding_topup, expected_outstanding);
+    }
+
+    #[test]
+    fn test_deposit_topup_restores_optimal_reserves() {
+        let mut pool = CbmmPool {
+            quote_starting_virtual_reserve: 1_000_000,
+            base_starting_total_supply: 1_000_000,
+            base_total_supply: 1_000_000,
+            base_reserve: 500_000,
+            quote_reserve: 500_000,
+            quote_virtual_reserve: 500_000,
+            buyback_fees_balance: 200_000,
+            quote_outstanding_topup: 300_000,
+            ..Default::default()
+        };
+        // Fees only cover part of the 500k shortfall, leaving a suboptimal virtual reserve
+        assert_eq!(pool.topup().unwrap(), 200_000);
+        assert!(pool.quote_virtual_reserve < 1_000_000);
+
+        let deposit_amount = pool.topup_deposit_amount().unwrap();
+        assert_eq!(deposit_amount, 300_000);
+        pool.deposit_topup(deposit_amount).unwrap();
+        assert_eq!(pool.quote_reserve, 1_000_000);
+        assert_eq!(pool.quote_virtual_reserve, 1_000_000);
+        assert_eq!(pool.quote_outstanding_topup, 0);
+        assert_eq!(pool.topup_deposit_amount().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_min_buy_quote_amount() {
+        let pool = CbmmPool {
+            quote_virtual_reserve: 1_000_000_000_000,
+            base_reserve: 2_000_000,
+            creator_fee_bp: 200,
+            buyback_fee_bp: 600,
+            platform_fee_bp: 200,
+            ..Default::default()
+        };
+        // 500_001 A has to reach the swap, and the rounded-up fees take 55_558 of it
+        let min_amount = pool.min_buy_quote_amount().unwrap();
+        assert_eq!(min_amount, 555_559);
+        for (quote_amount, base_output) in [(min_amount, 1), (min_amount - 1, 0)] {
+            let mut pool = pool.clone();
+            let (amount_after_fees, _) = pool
+                .collect_fees(quote_amount, &mut PlatformConfig::default(), 0)
+                .unwrap();
+            assert_eq!(
+                pool.quote_to_base(amount_after_fees).unwrap().base_amount,
+                base_output
+            );
+        }
+    }
+
+    #[test_case(false, 8959, 684_500 ; "favor pool")]
+    #[test_case(true, 8960, -320_000 ; "favor trader")]
+    fn test_buy_rounding_policy(round_in_favor_of_trader: bool, base_output: u64, k_delta: i128) {
+        let mut pool = CbmmPool {
+            quote_virtual_reserve: 1_000_000,
+            base_reserve: 2_000_000,
+            round_in_favor_of_trader,
+            ..Default::default()
+        };
+        // 2M * 4500 / (1M + 4500) = 8959.98
+        assert_eq!(pool.quote_to_base(4500).unwrap().base_amount, base_output);
+        let k_after =
+            (pool.quote_reserve + pool.quote_virtual_reserve) as i128 * pool.base_reserve as i128;
+        assert_eq!(k_after - 2_000_000_000_000, k_delta);
+        // Rounding up costs the invariant less than one B at the new A + V
+        assert!(-k_delta < (pool.quote_reserve + pool.quote_virtual_reserve) as i128);
+    }
+
+    #[test_case(false, 19_607, 430_000 ; "favor pool")]
+    #[test_case(true, 19_608, -80_000 ; "favor trader")]
+    fn test_sell_rounding_policy(round_in_favor_of_trader: bool, quote_output: u64, k_delta: i128) {
+        let mut pool = CbmmPool {
+            quote_reserve: 500_000,
+            quote_virtual_reserve: 500_000,
+            base_reserve: 500_000,
+            round_in_favor_of_trader,
+            ..Default::default()
+        };
+        // 10_000 * 1M / 510_000 = 19_607.84
+        assert_eq!(
+            pool.base_to_quote(10_000).unwrap().quote_amount,
+            quote_output
+        );
+        let k_after =
+            (pool.quote_reserve + pool.quote_virtual_reserve) as i128 * pool.base_reserve as i128;
+        assert_eq!(k_after - 500_000_000_000, k_delta);
+        assert!(-k_delta < pool.base_reserve as i128);
+    }
+
+    #[test]
+    fn test_sell_rounding_up_capped_by_real_reserve() {
+        let mut pool = CbmmPool {
+            quote_reserve: 1,
+            quote_virtual_reserve: 1_000_000,
+            base_reserve: 1_000_000,
+            ..Default::default()
+        };
+        // 2 * 1_000_001 / 1_000_002 = 1.99 only rounds up to what the real reserve holds
+        assert_eq!(pool.base_to_quote(2).unwrap().quote_amount, 1);
+        assert_eq!(pool.quote_reserve, 0);
+    }
+
+    #[test]
+    fn test_market_cap_quote() {
+        let mut pool = CbmmPool {
+            quote_reserve: 1_000_000,
+            quote_virtual_reserve: 3_000_000,
+            base_reserve: 8_000_000,
+            base_total_supply: 10_000_000,
+            ..Default::default()
+        };
+        // (1M + 3M) * 10M / 8M
+        assert_eq!(pool.market_cap_quote().unwrap(), 5_000_000);
+
+        pool.base_reserve = 0;
+        assert_eq!(pool.market_cap_quote().unwrap(), 0);
+
+        // (A + V) * supply no longer fits in u128
+        pool.base_reserve = 1;
+        pool.quote_reserve = u64::MAX;
+        pool.quote_virtual_reserve = u64::MAX;
+        pool.base_total_supply = u64::MAX;
+        assert!(pool.market_cap_quote().is_err());
+    }
+
+    #[test]
+    fn test_take_migration_amounts_matches_spot_price() {
+        let mut pool = CbmmPool {
+            quote_reserve: 1_000_000,
+            quote_virtual_reserve: 3_000_000,
+            base_reserve: 8_000_000,
+            graduated: true,
+            ..Default::default()
+        };
+        let spot_price_x64 = pool.spot_price_x64().unwrap();
+
+        let amounts = pool.take_migration_amounts().unwrap();
+        assert_eq!(amounts.quote_amount, 1_000_000);
+        // 1M * 8M / (1M + 3M)
+        assert_eq!(amounts.base_amount, 2_000_000);
+        assert_eq!(
+            calculate_spot_price_x64(amounts.quote_amount, 0, amounts.base_amount).unwrap(),
+            spot_price_x64
+        );
+        assert_eq!(pool.quote_reserve, 0);
+    }
+
+    #[test]
+    fn test_take_migration_amounts_rejects_invalid_state() {
+        let mut pool = CbmmPool {
+            quote_reserve: 1_000_000,
+            quote_virtual_reserve: 3_000_000,
+            base_reserve: 8_000_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            pool.take_migration_amounts().err().unwrap(),
+            CbmmError::NotGraduated.into()
+        );
+
+        pool.graduated = true;
+        pool.migrated_lp_owner = Some(Pubkey::new_unique());
+        assert_eq!(
+            pool.take_migration_amounts().err().unwrap(),
+            CbmmError::AlreadyMigrated.into()
+        );
+
+        pool.migrated_lp_owner = None;
+        pool.quote_reserve = 0;
+        assert_eq!(
+            pool.take_migration_amounts().err().unwrap(),
+            CbmmError::AmountTooSmall.into()
+        );
+    }
+
+    mod proptests {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn prop_collect_fees_conserves_amount(
+                quote_amount in 0u64..1_000_000_000_000_000,
+                creator_fee_bp in 0u16..=1000,
+                buyback_fee_bp in 0u16..=1000,
+                platform_fee_bp in 0u16..=1000,
+            ) {
+                let mut pool = CbmmPool {
+                    creator_fee_bp,
+                    buyback_fee_bp,
+                    platform_fee_bp,
+                    ..Default::default()
+                };
+                if let Ok((amount_after_fees, _)) = pool.collect_fees(quote_amount, &mut PlatformConfig::default(), 0) {
+                    prop_assert_eq!(
+                        amount_after_fees
+                            + pool.creator_fees_balance
+                            + pool.buyback_fees_balance
+                            + pool.platform_fees_balance,
+                        quote_amount
+                    );
+                }
+            }
+
+            #[test]
+            fn prop_buy_then_sell_never_profits(
+                quote_reserve in 0u64..1_000_000_000_000_000,
+                quote_virtual_reserve in 1u64..1_000_000_000_000_000,
+                base_reserve in 1_000u64..1_000_000_000_000_000_000,
+                quote_amount in 1u64..1_000_000_000_000_000,
+                creator_fee_bp in 0u16..=1000,
+                buyback_fee_bp in 0u16..=1000,
+                platform_fee_bp in 0u16..=1000,
+            ) {
+                let mut pool = CbmmPool {
+                    quote_reserve,
+                    quote_virtual_reserve,
+                    base_reserve,
+                    creator_fee_bp,
+                    buyback_fee_bp,
+                    platform_fee_bp,
+                    ..Default::default()
+                };
+                let Ok((amount_after_fees, _)) = pool.collect_fees(quote_amount, &mut PlatformConfig::default(), 0) else {
+                    return Ok(());
+                };
+                let bought = pool.quote_to_base(amount_after_fees).unwrap().base_amount;
+                let sold = pool.base_to_quote(bought).unwrap().quote_amount;
+                prop_assert!(sold <= amount_after_fees);
+                prop_assert!(pool.quote_reserve >= quote_reserve);
+                if let Ok((net_output, _)) = pool.collect_fees(sold, &mut PlatformConfig::default(), 0) {
+                    prop_assert!(net_output <= quote_amount);
+                }
+            }
+        }
     }
 }
@@ -3,6 +3,15 @@ use std::collections::HashMap;
 use litesvm::LiteSVM;
 use solana_sdk::transaction::Transaction;
 
+// Expected compute unit ceilings per instruction. The run fails if any call exceeds its
+// ceiling by more than CU_CEILING_TOLERANCE_PCT, so raise these deliberately, not silently.
+const CU_CEILINGS: &[(&str, u64)] = &[
+    ("buy_virtual_token", 40_000),
+    ("sell_virtual_token", 40_000),
+    ("burn_virtual_token", 25_000),
+];
+const CU_CEILING_TOLERANCE_PCT: u64 = 10;
+
 // Global collector for compute unit metrics
 struct ComputeMetrics {
     calls: HashMap<String, Vec<u64>>,
@@ -18,7 +27,7 @@ impl ComputeMetrics {
     fn record(&mut self, instruction_name: &str, compute_units: u64) {
         self.calls
             .entry(instruction_name.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(compute_units);
     }
 
@@ -47,6 +56,24 @@ impl ComputeMetrics {
 
         println!("\n{}", "=".repeat(80));
     }
+
+    fn ceiling_violations(&self, ceilings: &[(&str, u64)], tolerance_pct: u64) -> Vec<String> {
+        let mut violations = Vec::new();
+        for (name, ceiling) in ceilings {
+            let Some(values) = self.calls.get(*name) else {
+                continue;
+            };
+            let max = *values.iter().max().unwrap();
+            let limit = ceiling * (100 + tolerance_pct) / 100;
+            if max > limit {
+                violations.push(format!(
+                    "{}: {} CU exceeds ceiling {} CU (+{}% tolerance = {} CU)",
+                    name, max, ceiling, tolerance_pct, limit
+                ));
+            }
+        }
+        violations
+    }
 }
 
 static METRICS: Mutex<Option<ComputeMetrics>> = Mutex::new(None);
@@ -65,15 +92,26 @@ pub fn record_compute_units(instruction_name: &str, compute_units: u64) {
     }
 }
 
-// Print the final report
+// Print the final report and fail the run on compute unit regressions
 pub fn print_metrics_report() {
     let metrics = METRICS.lock().unwrap();
     if let Some(m) = metrics.as_ref() {
         m.print_report();
+
+        let violations = m.ceiling_violations(CU_CEILINGS, CU_CEILING_TOLERANCE_PCT);
+        if !violations.is_empty() {
+            eprintln!("\n\x1b[31;1mCOMPUTE UNIT CEILINGS EXCEEDED\x1b[0m");
+            for violation in violations {
+                eprintln!("  {}", violation);
+            }
+            // Runs after all tests in a destructor, where a panic would not be reported
+            std::process::exit(1);
+        }
     }
 }
 
 // Helper function to send transaction and auto-record metrics
+#[allow(clippy::result_large_err)]
 pub fn send_and_record(
     svm: &mut LiteSVM,
     tx: Transaction,
@@ -91,3 +129,23 @@ pub fn send_and_record(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ceiling_violations() {
+        let mut metrics = ComputeMetrics::new();
+        metrics.record("buy_virtual_token", 30_000);
+        metrics.record("buy_virtual_token", 43_000);
+        metrics.record("sell_virtual_token", 45_000);
+        metrics.record("untracked", 1_000_000);
+
+        let ceilings = [("buy_virtual_token", 40_000), ("sell_virtual_token", 40_000)];
+        // 43k is within the 10% tolerance, 45k is not
+        let violations = metrics.ceiling_violations(&ceilings, 10);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("sell_virtual_token"));
+    }
+}
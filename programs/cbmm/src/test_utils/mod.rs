@@ -7,4 +7,4 @@ mod compute_metrics;
 #[cfg(test)]
 pub use compute_metrics::{init_metrics, print_metrics_report};
 #[cfg(test)]
-pub use test_runner::{TestPool, TestRunner};
+pub use test_runner::{TestPool, TestRunner, TransactionError};
@@ -1,9 +1,9 @@
 use super::compute_metrics::send_and_record;
 use crate::helpers::BurnRateLimiter;
-use crate::instructions::BuyVirtualTokenArgs;
+use crate::instructions::{BuyVirtualTokenArgs, ClaimPlatformFeesArgs};
+use crate::pda;
 use crate::state::{self as cpmm_state, CBMM_POOL_INDEX_SEED};
 use anchor_lang::prelude::*;
-use anchor_lang::system_program;
 use litesvm::LiteSVM;
 use litesvm_token::{CreateAssociatedTokenAccount, CreateMint, MintTo};
 use solana_sdk::clock::Clock;
@@ -16,6 +16,14 @@ use solana_sdk::{
 
 const POOL_INDEX: u32 = CBMM_POOL_INDEX_SEED;
 
+fn to_anchor(key: &Pubkey) -> anchor_lang::prelude::Pubkey {
+    anchor_lang::prelude::Pubkey::new_from_array(key.to_bytes())
+}
+
+fn to_sdk((key, bump): (anchor_lang::prelude::Pubkey, u8)) -> (Pubkey, u8) {
+    (Pubkey::from(key.to_bytes()), bump)
+}
+
 #[derive(Debug)]
 pub struct TransactionError {
     pub message: String,
@@ -38,6 +46,12 @@ impl From<TransactionError> for anchor_lang::error::Error {
 pub struct TestRunner {
     pub svm: LiteSVM,
     pub program_id: Pubkey,
+    /// Logs of the last successful transaction sent through `send_instruction`
+    pub last_logs: Vec<String>,
+    /// Event data of `emit_cpi!` self-invocations in the last successful transaction
+    pub last_event_cpi_data: Vec<Vec<u8>>,
+    /// Return data of the last successful transaction
+    pub last_return_data: Vec<u8>,
 }
 
 pub struct TestPool {
@@ -53,21 +67,154 @@ impl TestRunner {
         let program_bytes = include_bytes!("../../../../target/deploy/cbmm.so");
         svm.add_program(program_id, program_bytes).unwrap();
 
-        Self { svm, program_id }
+        Self {
+            svm,
+            program_id,
+            last_logs: Vec::new(),
+            last_event_cpi_data: Vec::new(),
+            last_return_data: Vec::new(),
+        }
     }
 
     pub fn create_mint(&mut self, payer: &Keypair, quote_mint_decimals: u8) -> Pubkey {
-        let quote_mint = CreateMint::new(&mut self.svm, &payer)
+        let quote_mint = CreateMint::new(&mut self.svm, payer)
+            .authority(&payer.pubkey())
+            .decimals(quote_mint_decimals)
+            .send()
+            .unwrap();
+        quote_mint
+    }
+
+    pub fn create_mint_with_freeze_authority(
+        &mut self,
+        payer: &Keypair,
+        quote_mint_decimals: u8,
+        freeze_authority: &Pubkey,
+    ) -> Pubkey {
+        CreateMint::new(&mut self.svm, payer)
             .authority(&payer.pubkey())
+            .freeze_authority(freeze_authority)
             .decimals(quote_mint_decimals)
             .send()
+            .unwrap()
+    }
+
+    /// LiteSVM does not ship the wSOL mint account, so put it on chain manually.
+    pub fn create_native_mint(&mut self) -> Pubkey {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token::spl_token;
+
+        let native_mint = Pubkey::from(spl_token::native_mint::ID.to_bytes());
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint {
+            mint_authority: anchor_lang::solana_program::program_option::COption::None,
+            supply: 0,
+            decimals: spl_token::native_mint::DECIMALS,
+            is_initialized: true,
+            freeze_authority: anchor_lang::solana_program::program_option::COption::None,
+        }
+        .pack_into_slice(&mut data);
+
+        let rent = self.svm.get_sysvar::<solana_sdk::rent::Rent>();
+        self.svm
+            .set_account(
+                native_mint,
+                solana_sdk::account::Account {
+                    lamports: rent.minimum_balance(data.len()),
+                    data,
+                    owner: Pubkey::from(spl_token::ID.to_bytes()),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )
             .unwrap();
-        return quote_mint;
+        native_mint
+    }
+
+    /// Creates a Token-2022 mint with the transfer fee extension, withholding
+    /// `transfer_fee_bp` of every transfer (up to `maximum_fee`) at the destination.
+    pub fn create_transfer_fee_mint(
+        &mut self,
+        payer: &Keypair,
+        decimals: u8,
+        transfer_fee_bp: u16,
+        maximum_fee: u64,
+    ) -> Pubkey {
+        use anchor_lang::solana_program::system_instruction;
+        use anchor_spl::token_2022::spl_token_2022::{
+            self,
+            extension::{transfer_fee, ExtensionType},
+            state::Mint,
+        };
+
+        let mint = Keypair::new();
+        let mint_address = anchor_lang::prelude::Pubkey::from(mint.pubkey().to_bytes());
+        let authority = anchor_lang::prelude::Pubkey::from(payer.pubkey().to_bytes());
+        let space =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let rent = self.svm.get_sysvar::<solana_sdk::rent::Rent>();
+
+        let instructions = [
+            system_instruction::create_account(
+                &authority,
+                &mint_address,
+                rent.minimum_balance(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint_address,
+                Some(&authority),
+                Some(&authority),
+                transfer_fee_bp,
+                maximum_fee,
+            )
+            .unwrap(),
+            spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                &mint_address,
+                &authority,
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ]
+        .map(|ix| Instruction {
+            program_id: Pubkey::from(ix.program_id.to_bytes()),
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: Pubkey::from(meta.pubkey.to_bytes()),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data,
+        });
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[payer, &mint],
+            self.svm.latest_blockhash(),
+        );
+        self.svm.send_transaction(tx).unwrap();
+        mint.pubkey()
+    }
+
+    /// The token program owning `mint`, so helpers work for both SPL Token and Token-2022.
+    pub fn token_program_of(&self, mint: &Pubkey) -> Pubkey {
+        self.svm.get_account(mint).unwrap().owner
     }
 
     pub fn mint_to(&mut self, payer: &Keypair, mint: &Pubkey, payer_ata: Pubkey, amount: u64) {
-        MintTo::new(&mut self.svm, &payer, &mint, &payer_ata, amount)
-            .owner(&payer)
+        let token_program = self.token_program_of(mint);
+        MintTo::new(&mut self.svm, payer, mint, &payer_ata, amount)
+            .owner(payer)
+            .token_program_id(&token_program)
             .send()
             .unwrap();
     }
@@ -78,11 +225,13 @@ impl TestRunner {
         mint: Pubkey,
         owner: &Pubkey,
     ) -> Pubkey {
-        let ata = CreateAssociatedTokenAccount::new(&mut self.svm, &payer, &mint)
+        let token_program = self.token_program_of(&mint);
+        let ata = CreateAssociatedTokenAccount::new(&mut self.svm, payer, &mint)
             .owner(owner)
+            .token_program_id(&token_program)
             .send()
             .unwrap();
-        return ata;
+        ata
     }
 
     pub fn put_account_on_chain<T>(&mut self, account_address: &Pubkey, account_data: T) -> Pubkey
@@ -91,7 +240,7 @@ impl TestRunner {
     {
         let mut serialized_data = Vec::new();
         // Add the 8-byte discriminator first (required by Anchor)
-        serialized_data.extend_from_slice(&T::DISCRIMINATOR);
+        serialized_data.extend_from_slice(T::DISCRIMINATOR);
         // Then serialize the account data using AnchorSerialize
         anchor_lang::AnchorSerialize::serialize(&account_data, &mut serialized_data).unwrap();
 
@@ -114,6 +263,7 @@ impl TestRunner {
         *account_address
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_platform_config_mock(
         &mut self,
         creator: &Keypair,
@@ -127,20 +277,20 @@ impl TestRunner {
         platform_fee_bp: u16,
         burn_authority: Option<anchor_lang::prelude::Pubkey>,
     ) -> Pubkey {
-        let (platform_config_pda, platform_config_bump) = Pubkey::find_program_address(
-            &[cpmm_state::PLATFORM_CONFIG_SEED, creator.pubkey().as_ref()],
-            &self.program_id,
-        );
+        let (platform_config_pda, platform_config_bump) =
+            to_sdk(pda::platform_config_pda(&to_anchor(&creator.pubkey())));
         let burn_tiers = vec![
             cpmm_state::BurnTier {
                 burn_bp_x100: user_burn_bp_x100,
                 role: cpmm_state::BurnRole::Anyone,
                 max_daily_burns: daily_burn_allowance,
+                expires_at: None,
             },
             cpmm_state::BurnTier {
                 burn_bp_x100: creator_burn_bp_x100,
                 role: cpmm_state::BurnRole::PoolOwner,
                 max_daily_burns: creator_daily_burn_allowance,
+                expires_at: None,
             },
         ];
 
@@ -166,14 +316,32 @@ impl TestRunner {
             pool_creator_fee_bp: creator_fee_bp,
             pool_topup_fee_bp: buyback_fee_bp,
             platform_fee_bp,
+            keeper_reward_bp: 0,
+            referral_fee_bp: 0,
+            require_no_freeze_authority: false,
+            lifetime_platform_fees: 0,
+            migration_amm: None,
             burn_rate_config: burn_config,
+            burn_reset_interval_seconds: cpmm_state::PlatformConfig::DEFAULT_BURN_RESET_INTERVAL_SECONDS,
             burn_tiers_updated_at: 0,
             burn_tiers,
+            version: cpmm_state::CURRENT_ACCOUNT_VERSION,
+            paused: false,
+            config_change_delay_seconds: 0,
+            fee_recipient: anchor_lang::prelude::Pubkey::new_from_array(creator.pubkey().to_bytes()),
+            daily_burn_envelope_bp_x100: 0,
+            min_initial_lock: 0,
+            allowed_quote_mints: Vec::new(),
+            min_topup_fee_bp: cpmm_state::PlatformConfig::MIN_TOPUP_FEE_BP,
+            max_fee_holiday_seconds: 7 * 86400,
+            max_event_interval_seconds: 3600,
+            _reserved: cpmm_state::Reserved::default(),
         };
 
         self.put_account_on_chain(&platform_config_pda, platform_config)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_user_burn_allowance_mock(
         &mut self,
         user: Pubkey,
@@ -193,19 +361,12 @@ impl TestRunner {
 
         let burn_tier_index = if is_pool_owner { 1u8 } else { 0u8 };
 
-        let (user_burn_allowance_pda, bump) = Pubkey::find_program_address(
-            &[
-                cpmm_state::USER_BURN_ALLOWANCE_SEED,
-                user.as_ref(),
-                platform_config.as_ref(),
-                &[burn_tier_index],
-                platform_config_data
-                    .burn_tiers_updated_at
-                    .to_le_bytes()
-                    .as_ref(),
-            ],
-            &self.program_id,
-        );
+        let (user_burn_allowance_pda, bump) = to_sdk(pda::user_burn_allowance_pda(
+            &to_anchor(&user),
+            &to_anchor(&platform_config),
+            burn_tier_index,
+            platform_config_data.burn_tiers_updated_at,
+        ));
         let user_burn_allowance = cpmm_state::UserBurnAllowance {
             bump,
             platform_config: anchor_lang::prelude::Pubkey::new_from_array(
@@ -218,6 +379,7 @@ impl TestRunner {
             created_at,
             burn_tier_index,
             burn_tier_update_timestamp: platform_config_data.burn_tiers_updated_at,
+            queued_today: 0,
         };
         self.put_account_on_chain(
             &Pubkey::from(user_burn_allowance_pda.to_bytes()),
@@ -229,13 +391,13 @@ impl TestRunner {
         self.svm.airdrop(receiver, amount).unwrap();
     }
 
-    pub fn send_instruction<T>(
-        &mut self,
+    fn build_transaction<T>(
+        &self,
         instruction_name: &str,
         accounts: Vec<AccountMeta>,
         args: T,
         signers: &[&Keypair],
-    ) -> std::result::Result<(), TransactionError>
+    ) -> Transaction
     where
         T: anchor_lang::AnchorSerialize,
     {
@@ -252,7 +414,7 @@ impl TestRunner {
 
         let instruction = Instruction {
             program_id: self.program_id,
-            accounts: accounts,
+            accounts,
             data: {
                 let mut data = Vec::new();
                 data.extend_from_slice(&get_discriminator(instruction_name));
@@ -261,19 +423,105 @@ impl TestRunner {
             },
         };
 
-        let tx = Transaction::new_signed_with_payer(
+        Transaction::new_signed_with_payer(
             &[instruction],
             Some(&signers[0].pubkey()),
             signers,
             self.svm.latest_blockhash(),
-        );
+        )
+    }
 
-        send_and_record(&mut self.svm, tx, instruction_name).map_err(|err| TransactionError {
-            message: format!("{:?}", err),
-        })?;
+    pub fn send_instruction<T>(
+        &mut self,
+        instruction_name: &str,
+        accounts: Vec<AccountMeta>,
+        args: T,
+        signers: &[&Keypair],
+    ) -> std::result::Result<(), TransactionError>
+    where
+        T: anchor_lang::AnchorSerialize,
+    {
+        let tx = self.build_transaction(instruction_name, accounts, args, signers);
+        let account_keys = tx.message.account_keys.clone();
+
+        let result =
+            send_and_record(&mut self.svm, tx, instruction_name).map_err(|err| TransactionError {
+                message: format!("{:?}", err),
+            })?;
+        self.last_logs = result.logs;
+        self.last_return_data = result.return_data.data;
+        self.last_event_cpi_data = result
+            .inner_instructions
+            .iter()
+            .flatten()
+            .filter(|inner| {
+                account_keys[inner.instruction.program_id_index as usize] == self.program_id
+            })
+            .filter_map(|inner| {
+                inner
+                    .instruction
+                    .data
+                    .strip_prefix(anchor_lang::event::EVENT_IX_TAG_LE)
+                    .map(<[u8]>::to_vec)
+            })
+            .collect();
         Ok(())
     }
 
+    /// Decodes all events of type `E` emitted by the last successful transaction,
+    /// whether logged with `emit!` or self-invoked with `emit_cpi!`.
+    pub fn last_events<E>(&self) -> Vec<E>
+    where
+        E: anchor_lang::Discriminator + anchor_lang::AnchorDeserialize,
+    {
+        use anchor_lang::__private::base64::{engine::general_purpose::STANDARD, Engine};
+
+        self.last_logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| STANDARD.decode(data).ok())
+            .chain(self.last_event_cpi_data.iter().cloned())
+            .filter(|data| data.starts_with(E::DISCRIMINATOR))
+            .map(|data| E::deserialize(&mut &data[E::DISCRIMINATOR.len()..]).unwrap())
+            .collect()
+    }
+
+    /// Decodes the value returned by the last successful transaction.
+    pub fn last_return_data<R>(&self) -> R
+    where
+        R: anchor_lang::AnchorDeserialize,
+    {
+        R::deserialize(&mut self.last_return_data.as_slice()).unwrap()
+    }
+
+    /// Simulates a view instruction and decodes the value it returned via return data.
+    pub fn simulate_instruction<T, R>(
+        &self,
+        instruction_name: &str,
+        accounts: Vec<AccountMeta>,
+        args: T,
+        signers: &[&Keypair],
+    ) -> std::result::Result<R, TransactionError>
+    where
+        T: anchor_lang::AnchorSerialize,
+        R: anchor_lang::AnchorDeserialize,
+    {
+        let tx = self.build_transaction(instruction_name, accounts, args, signers);
+
+        let result = self
+            .svm
+            .simulate_transaction(tx)
+            .map_err(|err| TransactionError {
+                message: format!("{:?}", err),
+            })?;
+        R::deserialize(&mut result.meta.return_data.data.as_slice()).map_err(|err| {
+            TransactionError {
+                message: format!("Failed to decode return data: {:?}", err),
+            }
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn create_pool_mock(
         &mut self,
         payer: &Keypair,
@@ -289,18 +537,15 @@ impl TestRunner {
         platform_fee_bp: u16,
         creator_fees_balance: u64,
         buyback_fees_balance: u64,
-        _quote_outstanding_topup: u64,
+        quote_outstanding_topup: u64,
     ) -> TestPool {
         // Setup PDAs consistent with on-chain seeds
-        let (pool_pda, pool_bump) = Pubkey::find_program_address(
-            &[
-                cpmm_state::CBMM_POOL_SEED,
-                POOL_INDEX.to_le_bytes().as_ref(),
-                payer.pubkey().as_ref(),
-                platform_config_pda.as_ref(),
-            ],
-            &self.program_id,
-        );
+        let (pool_pda, pool_bump) = to_sdk(pda::pool_pda(
+            &to_anchor(&payer.pubkey()),
+            &to_anchor(&platform_config_pda),
+            &to_anchor(&quote_mint),
+            POOL_INDEX,
+        ));
 
         let total_fees_bp_x100 = (creator_fee_bp + buyback_fee_bp + platform_fee_bp) as u64 * 100;
 
@@ -314,27 +559,49 @@ impl TestRunner {
         let pool_data = cpmm_state::CbmmPool {
             bump: pool_bump,
             creator: anchor_lang::prelude::Pubkey::new_from_array(payer.pubkey().to_bytes()),
+            seed_creator: anchor_lang::prelude::Pubkey::new_from_array(payer.pubkey().to_bytes()),
+            pending_creator: None,
             pool_index: POOL_INDEX,
             platform_config: anchor_lang::prelude::Pubkey::new_from_array(
                 platform_config_pda.to_bytes(),
             ),
             quote_mint: anchor_lang::prelude::Pubkey::new_from_array(quote_mint.to_bytes()),
-            quote_reserve: quote_reserve,
-            quote_virtual_reserve: quote_virtual_reserve,
-            // quote_outstanding_topup removed from state
-            base_mint_decimals: base_mint_decimals,
-            base_reserve: base_reserve,
+            quote_reserve,
+            quote_virtual_reserve,
+            base_mint_decimals,
+            base_reserve,
             base_total_supply,
+            min_base_reserve: 1,
             creator_fees_balance,
             buyback_fees_balance,
             creator_fee_bp,
             buyback_fee_bp,
             platform_fee_bp,
+            allow_user_burns: true,
+            sell_lockup_seconds: 0,
+            graduated: false,
+            base_mint: anchor_lang::prelude::Pubkey::default(),
+            migrated_lp_owner: None,
             burn_limiter: BurnRateLimiter::new(current_timestamp, total_fees_bp_x100 * 3 / 4),
             quote_optimal_virtual_reserve: quote_virtual_reserve, // defaulting
             quote_starting_virtual_reserve: quote_virtual_reserve, // defaulting
             base_starting_total_supply: base_reserve,             // defaulting
             platform_fees_balance: 0,
+            version: cpmm_state::CURRENT_ACCOUNT_VERSION,
+            quote_outstanding_topup,
+            allowlist_root: None,
+            max_vta_balance: 0,
+            topup_order: cpmm_state::TopupOrder::default(),
+            locked_quote_reserve: 0,
+            cumulative_quote_volume: 0,
+            cumulative_base_volume: 0,
+            round_in_favor_of_trader: false,
+            fee_holiday_until: 0,
+            require_creator_cosign_on_burn: false,
+            min_event_interval_seconds: 0,
+            last_trade_event_timestamp: 0,
+            registry_index: None,
+            _reserved: cpmm_state::Reserved::default(),
         };
 
         self.put_account_on_chain(&pool_pda, pool_data);
@@ -342,195 +609,1061 @@ impl TestRunner {
         TestPool { pool: pool_pda }
     }
 
-    pub fn create_virtual_token_account_mock(
+    pub fn create_pool(
         &mut self,
-        owner: Pubkey,
-        pool: Pubkey,
-        balance: u64,
-    ) -> Pubkey {
-        // Derive the VirtualTokenAccount PDA using pool + owner seeds
-        let (vta_pda, vta_bump) = Pubkey::find_program_address(
-            &[
-                cpmm_state::VIRTUAL_TOKEN_ACCOUNT_SEED,
-                pool.as_ref(),
-                owner.as_ref(),
-            ],
-            &self.program_id,
-        );
-        self.put_account_on_chain(
-            &vta_pda,
-            cpmm_state::VirtualTokenAccount {
-                bump: vta_bump,
-                pool: anchor_lang::prelude::Pubkey::new_from_array(pool.to_bytes()),
-                owner: anchor_lang::prelude::Pubkey::new_from_array(owner.to_bytes()),
-                balance,
-            },
+        payer: &Keypair,
+        platform_config: Pubkey,
+        quote_mint: Pubkey,
+        quote_virtual_reserve: u64,
+    ) -> std::result::Result<TestPool, TransactionError> {
+        let (pool_pda, _) = to_sdk(pda::pool_pda(
+            &to_anchor(&payer.pubkey()),
+            &to_anchor(&platform_config),
+            &to_anchor(&quote_mint),
+            POOL_INDEX,
+        ));
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool_pda.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(quote_mint.to_bytes()),
         );
+        let platform_registry = self.get_platform_registry_pda(platform_config);
+        let registry_index = self
+            .get_platform_registry(platform_config)
+            .map_or(0, |registry| registry.total_pools_created);
+        let pool_entry = self.get_pool_entry_pda(platform_config, registry_index);
 
-        vta_pda
+        let accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(quote_mint, false),
+            AccountMeta::new(pool_pda, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new(platform_config, false),
+            AccountMeta::new(platform_registry, false),
+            AccountMeta::new(pool_entry, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        let args = crate::instructions::CreatePoolArgs {
+            quote_virtual_reserve,
+            sell_lockup_seconds: 0,
+            min_base_reserve: 1,
+            allowlist_root: None,
+            max_vta_balance: 0,
+            topup_order: cpmm_state::TopupOrder::default(),
+            round_in_favor_of_trader: false,
+            fee_holiday_until: 0,
+            require_creator_cosign_on_burn: false,
+            min_event_interval_seconds: 0,
+        };
+
+        self.send_instruction("create_pool", accounts, args, &[payer])?;
+        Ok(TestPool { pool: pool_pda })
     }
 
-    pub fn buy_virtual_token(
+    pub fn close_pool(
         &mut self,
-        payer: &Keypair,
-        payer_ata: Pubkey,
+        owner: &Keypair,
+        owner_ata: Pubkey,
         mint: Pubkey,
         pool: Pubkey,
-        virtual_token_account: Pubkey,
-        quote_amount: u64,
-        base_amount_min: u64,
     ) -> std::result::Result<(), TransactionError> {
         let pool_ata = anchor_spl::associated_token::get_associated_token_address(
             &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
             &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
         );
 
-        // Get platform_config from pool account
         let pool_account = self.svm.get_account(&pool).unwrap();
         let pool_data =
             cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
-        let platform_config_pda = pool_data.platform_config;
+        let platform_config = Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_registry = self.get_platform_registry_pda(platform_config);
+        // Pools from before registry entries pass the program id for the missing entry
+        let pool_entry = pool_data.registry_index.map_or(self.program_id, |index| {
+            self.get_pool_entry_pda(platform_config, index)
+        });
 
         let accounts = vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(payer_ata, false),
-            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(owner_ata, false),
             AccountMeta::new(pool, false),
             AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
-            AccountMeta::new(Pubkey::from(platform_config_pda.to_bytes()), false),
-            AccountMeta::new(mint, false),
+            AccountMeta::new(platform_registry, false),
+            AccountMeta::new(pool_entry, false),
+            AccountMeta::new_readonly(mint, false),
             AccountMeta::new_readonly(
                 Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
                 false,
             ),
-            AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
         ];
 
-        let args = BuyVirtualTokenArgs {
-            quote_amount,
-            base_amount_min,
-        };
-
-        self.send_instruction("buy_virtual_token", accounts, args, &[payer])
+        self.send_instruction("close_pool", accounts, (), &[owner])
     }
 
-    pub fn sell_virtual_token(
+    pub fn claim_creator_fees_and_close(
         &mut self,
-        payer: &Keypair,
-        payer_ata: Pubkey,
+        owner: &Keypair,
+        owner_ata: Pubkey,
         mint: Pubkey,
         pool: Pubkey,
-        virtual_token_account: Pubkey,
-        base_amount: u64,
-        min_quote_amount: u64,
     ) -> std::result::Result<(), TransactionError> {
         let pool_ata = anchor_spl::associated_token::get_associated_token_address(
             &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
             &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
         );
 
-        // Get platform_config from pool account
         let pool_account = self.svm.get_account(&pool).unwrap();
         let pool_data =
             cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
-        let platform_config_pda = pool_data.platform_config;
+        let platform_config = Pubkey::from(pool_data.platform_config.to_bytes());
+        let platform_registry = self.get_platform_registry_pda(platform_config);
+        // Pools from before registry entries pass the program id for the missing entry
+        let pool_entry = pool_data.registry_index.map_or(self.program_id, |index| {
+            self.get_pool_entry_pda(platform_config, index)
+        });
 
         let accounts = vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(payer_ata, false),
-            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(owner_ata, false),
             AccountMeta::new(pool, false),
             AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
-            AccountMeta::new(Pubkey::from(platform_config_pda.to_bytes()), false),
-            AccountMeta::new(mint, false),
+            AccountMeta::new(platform_registry, false),
+            AccountMeta::new(pool_entry, false),
+            AccountMeta::new_readonly(mint, false),
             AccountMeta::new_readonly(
                 Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
                 false,
             ),
-            AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
         ];
 
-        let args = crate::instructions::SellVirtualTokenArgs {
-            base_amount,
-            min_quote_amount,
-        };
-
-        self.send_instruction("sell_virtual_token", accounts, args, &[payer])
+        self.send_instruction("claim_creator_fees_and_close", accounts, (), &[owner])
     }
 
-    pub fn initialize_user_burn_allowance(
+    pub fn close_platform_config(
         &mut self,
-        payer: &Keypair,
-        owner: Pubkey,
+        admin: &Keypair,
         platform_config: Pubkey,
-        is_pool_owner: bool,
-    ) -> std::result::Result<Pubkey, TransactionError> {
-        use crate::instructions::InitializeUserBurnAllowanceArgs;
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(platform_config, false),
+            AccountMeta::new(self.get_platform_registry_pda(platform_config), false),
+        ];
+        self.send_instruction("close_platform_config", accounts, (), &[admin])
+    }
 
-        // Get platform config to read burn_tiers_updated_at
-        let platform_config_account = self.svm.get_account(&platform_config).unwrap();
-        let platform_config_data = cpmm_state::PlatformConfig::try_deserialize(
-            &mut platform_config_account.data.as_slice(),
-        )
-        .unwrap();
+    pub fn get_platform_registry_pda(&self, platform_config: Pubkey) -> Pubkey {
+        to_sdk(pda::platform_registry_pda(&to_anchor(&platform_config))).0
+    }
 
-        let burn_tier_index = if is_pool_owner { 1u8 } else { 0u8 };
+    pub fn get_pool_entry_pda(&self, platform_config: Pubkey, index: u64) -> Pubkey {
+        to_sdk(pda::platform_pool_entry_pda(&to_anchor(&platform_config), index)).0
+    }
 
-        // Derive the UserBurnAllowance PDA with correct seeds
-        let (user_burn_allowance_pda, _bump) = Pubkey::find_program_address(
-            &[
-                cpmm_state::USER_BURN_ALLOWANCE_SEED,
-                owner.as_ref(),
-                platform_config.as_ref(),
-                &[burn_tier_index],
-                platform_config_data
-                    .burn_tiers_updated_at
-                    .to_le_bytes()
-                    .as_ref(),
-            ],
-            &self.program_id,
+    pub fn get_pool_entry(
+        &self,
+        platform_config: Pubkey,
+        index: u64,
+    ) -> Option<cpmm_state::PlatformPoolEntry> {
+        let account = self
+            .svm
+            .get_account(&self.get_pool_entry_pda(platform_config, index))?;
+        cpmm_state::PlatformPoolEntry::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn get_platform_registry(
+        &self,
+        platform_config: Pubkey,
+    ) -> Option<cpmm_state::PlatformRegistry> {
+        let account = self
+            .svm
+            .get_account(&self.get_platform_registry_pda(platform_config))?;
+        cpmm_state::PlatformRegistry::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn get_pending_config_change_pda(&self, platform_config: Pubkey) -> Pubkey {
+        to_sdk(pda::pending_config_change_pda(&to_anchor(&platform_config))).0
+    }
+
+    pub fn get_pending_config_change(
+        &self,
+        platform_config: Pubkey,
+    ) -> Option<cpmm_state::PendingConfigChange> {
+        let account = self
+            .svm
+            .get_account(&self.get_pending_config_change_pda(platform_config))?;
+        cpmm_state::PendingConfigChange::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn create_virtual_token_account_mock(
+        &mut self,
+        owner: Pubkey,
+        pool: Pubkey,
+        balance: u64,
+    ) -> Pubkey {
+        // Derive the VirtualTokenAccount PDA using pool + owner seeds
+        let (vta_pda, vta_bump) = to_sdk(pda::vta_pda(&to_anchor(&pool), &to_anchor(&owner)));
+        self.put_account_on_chain(
+            &vta_pda,
+            cpmm_state::VirtualTokenAccount {
+                bump: vta_bump,
+                pool: anchor_lang::prelude::Pubkey::new_from_array(pool.to_bytes()),
+                owner: anchor_lang::prelude::Pubkey::new_from_array(owner.to_bytes()),
+                balance,
+                last_buy_ts: 0,
+                quote_spent: 0,
+                quote_received: 0,
+                delegate: None,
+                delegated_amount: 0,
+                version: cpmm_state::CURRENT_ACCOUNT_VERSION,
+                frozen: false,
+                last_sell_ts: 0,
+                _reserved: cpmm_state::Reserved::default(),
+            },
         );
 
-        // Find the pool if needed
-        let pool_pda = if is_pool_owner {
-            let (pool, _) = Pubkey::find_program_address(
-                &[
-                    cpmm_state::CBMM_POOL_SEED,
-                    cpmm_state::CBMM_POOL_INDEX_SEED.to_le_bytes().as_ref(),
-                    owner.as_ref(),
-                    platform_config.as_ref(),
-                ],
-                &self.program_id,
-            );
-            pool
-        } else {
-            self.program_id // Use program_id as dummy when pool is not needed
-        };
+        vta_pda
+    }
 
+    pub fn approve_virtual(
+        &mut self,
+        owner: &Keypair,
+        pool: Pubkey,
+        delegate: Option<Pubkey>,
+        amount: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let (virtual_token_account, _) =
+            to_sdk(pda::vta_pda(&to_anchor(&pool), &to_anchor(&owner.pubkey())));
         let accounts = vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new_readonly(owner, false),
-            AccountMeta::new(user_burn_allowance_pda, false),
-            AccountMeta::new_readonly(platform_config, false),
-            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
-            AccountMeta::new_readonly(pool_pda, false),
+            AccountMeta::new_readonly(owner.pubkey(), true),
+            AccountMeta::new(virtual_token_account, false),
         ];
+        let args = crate::instructions::ApproveVirtualArgs {
+            delegate: delegate.map(|delegate| to_anchor(&delegate)),
+            amount,
+        };
 
-        let args = InitializeUserBurnAllowanceArgs { burn_tier_index };
-
-        self.send_instruction("initialize_user_burn_allowance", accounts, args, &[payer])?;
-
-        Ok(user_burn_allowance_pda)
+        self.send_instruction("approve_virtual", accounts, args, &[owner])
     }
 
-    pub fn burn_virtual_token(
+    pub fn snapshot_vta(
         &mut self,
         payer: &Keypair,
         pool: Pubkey,
-        user_burn_allowance: Pubkey,
-        burn_authority: Option<&Keypair>,
+        virtual_token_account: Pubkey,
     ) -> std::result::Result<(), TransactionError> {
-        // Get platform_config from pool account
+        let accounts = vec![
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(virtual_token_account, false),
+        ];
+        self.send_instruction("snapshot_vta", accounts, (), &[payer])
+    }
+
+    pub fn snapshot_vtas(
+        &mut self,
+        payer: &Keypair,
+        pool: Pubkey,
+        virtual_token_accounts: &[Pubkey],
+    ) -> std::result::Result<(), TransactionError> {
+        let mut accounts = vec![AccountMeta::new_readonly(pool, false)];
+        accounts.extend(
+            virtual_token_accounts
+                .iter()
+                .map(|address| AccountMeta::new_readonly(*address, false)),
+        );
+        self.send_instruction("snapshot_vtas", accounts, (), &[payer])
+    }
+
+    pub fn initialize_virtual_token_account(
+        &mut self,
+        payer: &Keypair,
+        owner: Pubkey,
+        pool: Pubkey,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let (virtual_token_account, _) =
+            to_sdk(pda::vta_pda(&to_anchor(&pool), &to_anchor(&owner)));
+        let accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(owner, false),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        self.send_instruction("initialize_virtual_token_account", accounts, (), &[payer])?;
+        Ok(virtual_token_account)
+    }
+
+    pub fn initialize_virtual_token_accounts_batch(
+        &mut self,
+        payer: &Keypair,
+        pool: Pubkey,
+        owners: &[Pubkey],
+        skip_existing: bool,
+    ) -> std::result::Result<Vec<Pubkey>, TransactionError> {
+        let mut accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        let mut virtual_token_accounts = vec![];
+        for owner in owners {
+            let (virtual_token_account, _) =
+                to_sdk(pda::vta_pda(&to_anchor(&pool), &to_anchor(owner)));
+            accounts.push(AccountMeta::new_readonly(*owner, false));
+            accounts.push(AccountMeta::new(virtual_token_account, false));
+            virtual_token_accounts.push(virtual_token_account);
+        }
+
+        self.send_instruction(
+            "initialize_virtual_token_accounts_batch",
+            accounts,
+            crate::instructions::InitializeVirtualTokenAccountsBatchArgs { skip_existing },
+            &[payer],
+        )?;
+        Ok(virtual_token_accounts)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn buy_virtual_token(
+        &mut self,
+        payer: &Keypair,
+        payer_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        quote_amount: u64,
+        base_amount_min: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let args = BuyVirtualTokenArgs {
+            quote_amount,
+            base_amount_min,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+
+        self.buy_virtual_token_with_args(payer, payer_ata, mint, pool, virtual_token_account, args)
+    }
+
+    pub fn buy_virtual_token_with_args(
+        &mut self,
+        payer: &Keypair,
+        payer_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        args: BuyVirtualTokenArgs,
+    ) -> std::result::Result<(), TransactionError> {
+        self.buy_virtual_token_with_referrer(
+            payer,
+            payer_ata,
+            mint,
+            pool,
+            virtual_token_account,
+            args,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn buy_virtual_token_with_referrer(
+        &mut self,
+        payer: &Keypair,
+        payer_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        args: BuyVirtualTokenArgs,
+        referrer_ata: Option<Pubkey>,
+    ) -> std::result::Result<(), TransactionError> {
+        self.relay_buy_virtual_token(
+            payer,
+            payer,
+            payer_ata,
+            mint,
+            pool,
+            virtual_token_account,
+            args,
+            referrer_ata,
+        )
+    }
+
+    /// Buys for `trader` while `fee_payer` covers the transaction fees
+    #[allow(clippy::too_many_arguments)]
+    pub fn relay_buy_virtual_token(
+        &mut self,
+        fee_payer: &Keypair,
+        trader: &Keypair,
+        trader_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        args: BuyVirtualTokenArgs,
+        referrer_ata: Option<Pubkey>,
+    ) -> std::result::Result<(), TransactionError> {
+        let token_program = self.token_program_of(&mint);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address_with_program_id(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(token_program.to_bytes()),
+        );
+
+        // Get platform_config from pool account
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_pda = pool_data.platform_config;
+
+        let mut accounts = vec![
+            AccountMeta::new(fee_payer.pubkey(), true),
+            AccountMeta::new_readonly(trader.pubkey(), true),
+            AccountMeta::new(trader_ata, false),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new(Pubkey::from(platform_config_pda.to_bytes()), false),
+            // Absent optional accounts are passed as the program id
+            match referrer_ata {
+                Some(referrer_ata) => AccountMeta::new(referrer_ata, false),
+                None => AccountMeta::new_readonly(self.program_id, false),
+            },
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        accounts.extend(self.event_cpi_accounts());
+
+        // The transaction fee is charged to the first signer
+        let signers: Vec<&Keypair> = if fee_payer.pubkey() == trader.pubkey() {
+            vec![fee_payer]
+        } else {
+            vec![fee_payer, trader]
+        };
+        self.send_instruction("buy_virtual_token", accounts, args, &signers)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn sell_virtual_token(
+        &mut self,
+        payer: &Keypair,
+        payer_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        base_amount: u64,
+        min_quote_amount: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let args = crate::instructions::SellVirtualTokenArgs {
+            base_amount,
+            min_quote_amount,
+            memo: None,
+        };
+        self.sell_virtual_token_with_args(
+            payer,
+            payer_ata,
+            mint,
+            pool,
+            virtual_token_account,
+            args,
+        )
+    }
+
+    pub fn sell_virtual_token_with_args(
+        &mut self,
+        payer: &Keypair,
+        payer_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        args: crate::instructions::SellVirtualTokenArgs,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
+        );
+
+        // Get platform_config from pool account
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let platform_config_pda = pool_data.platform_config;
+
+        let mut accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(payer_ata, false),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new(Pubkey::from(platform_config_pda.to_bytes()), false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+        ];
+        accounts.extend(self.event_cpi_accounts());
+
+        self.send_instruction("sell_virtual_token", accounts, args, &[payer])
+    }
+
+    pub fn place_order(
+        &mut self,
+        owner: &Keypair,
+        pool: Pubkey,
+        order_id: u64,
+        base_amount: u64,
+        trigger_price_x64: u128,
+        direction: cpmm_state::OrderDirection,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        self.place_order_with_min_output(
+            owner,
+            pool,
+            order_id,
+            base_amount,
+            trigger_price_x64,
+            direction,
+            0,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_order_with_min_output(
+        &mut self,
+        owner: &Keypair,
+        pool: Pubkey,
+        order_id: u64,
+        base_amount: u64,
+        trigger_price_x64: u128,
+        direction: cpmm_state::OrderDirection,
+        min_quote_amount: u64,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let (virtual_token_account, _) =
+            to_sdk(pda::vta_pda(&to_anchor(&pool), &to_anchor(&owner.pubkey())));
+        let (order, _) = to_sdk(pda::order_pda(
+            &to_anchor(&pool),
+            &to_anchor(&owner.pubkey()),
+            order_id,
+        ));
+
+        let accounts = vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        let args = crate::instructions::PlaceOrderArgs {
+            order_id,
+            base_amount,
+            trigger_price_x64,
+            direction,
+            min_quote_amount,
+        };
+
+        self.send_instruction("place_order", accounts, args, &[owner])?;
+        Ok(order)
+    }
+
+    pub fn get_order(&self, order: &Pubkey) -> Option<cpmm_state::Order> {
+        let account = self.svm.get_account(order)?;
+        cpmm_state::Order::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn execute_order(
+        &mut self,
+        keeper: &Keypair,
+        order: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let order_data = self.get_order(&order).unwrap();
+        let pool = Pubkey::from(order_data.pool.to_bytes());
+        let owner = Pubkey::from(order_data.owner.to_bytes());
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let owner_ata = anchor_spl::associated_token::get_associated_token_address(
+            &order_data.owner,
+            &pool_data.quote_mint,
+        );
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &order_data.pool,
+            &pool_data.quote_mint,
+        );
+        let (virtual_token_account, _) = to_sdk(pda::vta_pda(&order_data.pool, &order_data.owner));
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(keeper.pubkey(), true),
+            AccountMeta::new(owner, false),
+            AccountMeta::new(Pubkey::from(owner_ata.to_bytes()), false),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.quote_mint.to_bytes()), false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        accounts.extend(self.event_cpi_accounts());
+
+        self.send_instruction("execute_order", accounts, (), &[keeper])
+    }
+
+    pub fn cancel_order(
+        &mut self,
+        owner: &Keypair,
+        order: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let order_data = self.get_order(&order).unwrap();
+        let (virtual_token_account, _) =
+            to_sdk(pda::vta_pda(&order_data.pool, &to_anchor(&owner.pubkey())));
+
+        let accounts = vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(order, false),
+        ];
+
+        self.send_instruction("cancel_order", accounts, (), &[owner])
+    }
+
+    fn limit_buy_escrow_ata(&self, order: Pubkey, quote_mint: Pubkey) -> Pubkey {
+        Pubkey::from(
+            anchor_spl::associated_token::get_associated_token_address(
+                &to_anchor(&order),
+                &to_anchor(&quote_mint),
+            )
+            .to_bytes(),
+        )
+    }
+
+    pub fn place_limit_buy(
+        &mut self,
+        owner: &Keypair,
+        owner_ata: Pubkey,
+        pool: Pubkey,
+        order_id: u64,
+        quote_amount: u64,
+        max_price_x64: u128,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+        let (order, _) = to_sdk(pda::limit_buy_order_pda(
+            &to_anchor(&pool),
+            &to_anchor(&owner.pubkey()),
+            order_id,
+        ));
+
+        let accounts = vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(owner_ata, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(self.limit_buy_escrow_ata(order, quote_mint), false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        let args = crate::instructions::PlaceLimitBuyArgs {
+            order_id,
+            quote_amount,
+            max_price_x64,
+        };
+
+        self.send_instruction("place_limit_buy", accounts, args, &[owner])?;
+        Ok(order)
+    }
+
+    pub fn get_limit_buy_order(&self, order: &Pubkey) -> Option<cpmm_state::LimitBuyOrder> {
+        let account = self.svm.get_account(order)?;
+        cpmm_state::LimitBuyOrder::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn fill_limit_buy(
+        &mut self,
+        keeper: &Keypair,
+        order: Pubkey,
+        quote_amount: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let order_data = self.get_limit_buy_order(&order).unwrap();
+        let pool = Pubkey::from(order_data.pool.to_bytes());
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &order_data.pool,
+            &pool_data.quote_mint,
+        );
+        let (virtual_token_account, _) = to_sdk(pda::vta_pda(&order_data.pool, &order_data.owner));
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(keeper.pubkey(), true),
+            AccountMeta::new(Pubkey::from(order_data.owner.to_bytes()), false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(self.limit_buy_escrow_ata(order, quote_mint), false),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        accounts.extend(self.event_cpi_accounts());
+
+        let args = crate::instructions::FillLimitBuyArgs { quote_amount };
+        self.send_instruction("fill_limit_buy", accounts, args, &[keeper])
+    }
+
+    pub fn cancel_limit_buy(
+        &mut self,
+        owner: &Keypair,
+        owner_ata: Pubkey,
+        order: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let order_data = self.get_limit_buy_order(&order).unwrap();
+        let pool_account = self
+            .svm
+            .get_account(&Pubkey::from(order_data.pool.to_bytes()))
+            .unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+
+        let accounts = vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(owner_ata, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(self.limit_buy_escrow_ata(order, quote_mint), false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+        ];
+
+        self.send_instruction("cancel_limit_buy", accounts, (), &[owner])
+    }
+
+    pub fn graduate_pool(
+        &mut self,
+        admin: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let (base_mint, _) = to_sdk(pda::graduated_mint_pda(&to_anchor(&pool)));
+
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(base_mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        self.send_instruction("graduate_pool", accounts, (), &[admin])?;
+        Ok(base_mint)
+    }
+
+    pub fn claim_graduated_tokens(
+        &mut self,
+        owner: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        let (virtual_token_account, _) =
+            to_sdk(pda::vta_pda(&to_anchor(&pool), &to_anchor(&owner.pubkey())));
+        let (base_mint, _) = to_sdk(pda::graduated_mint_pda(&to_anchor(&pool)));
+        let owner_base_ata = Pubkey::from(
+            anchor_spl::associated_token::get_associated_token_address(
+                &to_anchor(&owner.pubkey()),
+                &to_anchor(&base_mint),
+            )
+            .to_bytes(),
+        );
+
+        let accounts = vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(base_mint, false),
+            AccountMeta::new(owner_base_ata, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        self.send_instruction("claim_graduated_tokens", accounts, (), &[owner])?;
+        Ok(owner_base_ata)
+    }
+
+    pub fn migrate_account(
+        &mut self,
+        payer: &Keypair,
+        account: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        self.send_instruction("migrate_account", accounts, (), &[payer])
+    }
+
+    pub fn migrate_liquidity(
+        &mut self,
+        admin: &Keypair,
+        pool: Pubkey,
+        lp_owner: Pubkey,
+        amm_program: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+        let (base_mint, _) = to_sdk(pda::graduated_mint_pda(&to_anchor(&pool)));
+        let pool_ata = Pubkey::from(
+            anchor_spl::associated_token::get_associated_token_address(
+                &to_anchor(&pool),
+                &to_anchor(&quote_mint),
+            )
+            .to_bytes(),
+        );
+        let pool_base_ata = Pubkey::from(
+            anchor_spl::associated_token::get_associated_token_address(
+                &to_anchor(&pool),
+                &to_anchor(&base_mint),
+            )
+            .to_bytes(),
+        );
+
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(pool_ata, false),
+            AccountMeta::new(pool_base_ata, false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new(base_mint, false),
+            AccountMeta::new_readonly(lp_owner, false),
+            AccountMeta::new_readonly(amm_program, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        self.send_instruction("migrate_liquidity", accounts, (), &[admin])
+    }
+
+    /// Trailing accounts required by instructions that emit events via `emit_cpi!`.
+    fn event_cpi_accounts(&self) -> [AccountMeta; 2] {
+        let (event_authority, _) =
+            Pubkey::find_program_address(&[b"__event_authority"], &self.program_id);
+        [
+            AccountMeta::new_readonly(event_authority, false),
+            AccountMeta::new_readonly(self.program_id, false),
+        ]
+    }
+
+    fn sol_swap_accounts(
+        &self,
+        payer: &Keypair,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+    ) -> Vec<AccountMeta> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let payer_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(payer.pubkey().to_bytes()),
+            &pool_data.quote_mint,
+        );
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &pool_data.quote_mint,
+        );
+
+        let [event_authority, program] = self.event_cpi_accounts();
+
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(Pubkey::from(payer_ata.to_bytes()), false),
+            AccountMeta::new(virtual_token_account, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.quote_mint.to_bytes()), false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            event_authority,
+            program,
+        ]
+    }
+
+    pub fn buy_with_sol(
+        &mut self,
+        payer: &Keypair,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        quote_amount: u64,
+        base_amount_min: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = self.sol_swap_accounts(payer, pool, virtual_token_account);
+        let args = BuyVirtualTokenArgs {
+            quote_amount,
+            base_amount_min,
+            slippage_bps: None,
+            allowlist_proof: vec![],
+            memo: None,
+        };
+
+        self.send_instruction("buy_with_sol", accounts, args, &[payer])
+    }
+
+    pub fn sell_to_sol(
+        &mut self,
+        payer: &Keypair,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        base_amount: u64,
+        min_quote_amount: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = self.sol_swap_accounts(payer, pool, virtual_token_account);
+        let args = crate::instructions::SellVirtualTokenArgs {
+            base_amount,
+            min_quote_amount,
+            memo: None,
+        };
+
+        self.send_instruction("sell_to_sol", accounts, args, &[payer])
+    }
+
+    pub fn initialize_user_burn_allowance(
+        &mut self,
+        payer: &Keypair,
+        owner: Pubkey,
+        platform_config: Pubkey,
+        is_pool_owner: bool,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        use crate::instructions::InitializeUserBurnAllowanceArgs;
+
+        // Get platform config to read burn_tiers_updated_at
+        let platform_config_account = self.svm.get_account(&platform_config).unwrap();
+        let platform_config_data = cpmm_state::PlatformConfig::try_deserialize(
+            &mut platform_config_account.data.as_slice(),
+        )
+        .unwrap();
+
+        let burn_tier_index = if is_pool_owner { 1u8 } else { 0u8 };
+
+        // Derive the UserBurnAllowance PDA with correct seeds
+        let (user_burn_allowance_pda, _bump) = to_sdk(pda::user_burn_allowance_pda(
+            &to_anchor(&owner),
+            &to_anchor(&platform_config),
+            burn_tier_index,
+            platform_config_data.burn_tiers_updated_at,
+        ));
+
+        // Find the pool if needed
+        let pool_pda = if is_pool_owner {
+            to_sdk(pda::pool_pda(
+                &to_anchor(&owner),
+                &to_anchor(&platform_config),
+                &platform_config_data.quote_mint,
+                POOL_INDEX,
+            ))
+            .0
+        } else {
+            self.program_id // Use program_id as dummy when pool is not needed
+        };
+
+        let accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(owner, false),
+            AccountMeta::new(user_burn_allowance_pda, false),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            AccountMeta::new_readonly(pool_pda, false),
+        ];
+
+        let args = InitializeUserBurnAllowanceArgs { burn_tier_index };
+
+        self.send_instruction("initialize_user_burn_allowance", accounts, args, &[payer])?;
+
+        Ok(user_burn_allowance_pda)
+    }
+
+    pub fn burn_virtual_token(
+        &mut self,
+        payer: &Keypair,
+        pool: Pubkey,
+        user_burn_allowance: Pubkey,
+        burn_authority: Option<&Keypair>,
+    ) -> std::result::Result<(), TransactionError> {
+        self.burn_virtual_token_with_cosign(payer, pool, user_burn_allowance, burn_authority, None)
+    }
+
+    pub fn burn_virtual_token_with_cosign(
+        &mut self,
+        payer: &Keypair,
+        pool: Pubkey,
+        user_burn_allowance: Pubkey,
+        burn_authority: Option<&Keypair>,
+        pool_creator: Option<&Keypair>,
+    ) -> std::result::Result<(), TransactionError> {
+        // Get platform_config from pool account
         let pool_account = self.svm.get_account(&pool).unwrap();
         let pool_data =
             cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
@@ -552,10 +1685,178 @@ impl TestRunner {
         } else {
             accounts.push(AccountMeta::new_readonly(self.program_id, false));
         }
+        if let Some(creator) = pool_creator {
+            accounts.push(AccountMeta::new_readonly(creator.pubkey(), true));
+            signers.push(creator);
+        } else {
+            accounts.push(AccountMeta::new_readonly(self.program_id, false));
+        }
+
+        accounts.extend(self.event_cpi_accounts());
 
         self.send_instruction("burn_virtual_token", accounts, (), &signers)
     }
 
+    pub fn get_burn_schedule(&self, pool: Pubkey) -> Option<cpmm_state::BurnSchedule> {
+        let (burn_schedule, _) = to_sdk(pda::burn_schedule_pda(&to_anchor(&pool)));
+        let account = self.svm.get_account(&burn_schedule)?;
+        cpmm_state::BurnSchedule::try_deserialize(&mut account.data.as_slice()).ok()
+    }
+
+    pub fn create_burn_schedule(
+        &mut self,
+        creator: &Keypair,
+        pool: Pubkey,
+        tier_index: u8,
+        interval_seconds: i64,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let (burn_schedule, _) = to_sdk(pda::burn_schedule_pda(&to_anchor(&pool)));
+        let accounts = vec![
+            AccountMeta::new(creator.pubkey(), true),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new(burn_schedule, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        let args = crate::instructions::CreateBurnScheduleArgs {
+            tier_index,
+            interval_seconds,
+        };
+        self.send_instruction("create_burn_schedule", accounts, args, &[creator])
+    }
+
+    pub fn run_burn_schedule(
+        &mut self,
+        keeper: &Keypair,
+        pool: Pubkey,
+        user_burn_allowance: Pubkey,
+        burn_authority: Option<&Keypair>,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let (burn_schedule, _) = to_sdk(pda::burn_schedule_pda(&to_anchor(&pool)));
+        let mut accounts = vec![
+            AccountMeta::new_readonly(keeper.pubkey(), true),
+            AccountMeta::new(burn_schedule, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(user_burn_allowance, false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+        ];
+        let mut signers: Vec<&Keypair> = vec![keeper];
+        if let Some(auth) = burn_authority {
+            accounts.push(AccountMeta::new_readonly(auth.pubkey(), true));
+            signers.push(auth);
+        } else {
+            accounts.push(AccountMeta::new_readonly(self.program_id, false));
+        }
+        accounts.extend(self.event_cpi_accounts());
+
+        self.send_instruction("run_burn_schedule", accounts, (), &signers)
+    }
+
+    pub fn close_burn_schedule(
+        &mut self,
+        authority: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let (burn_schedule, _) = to_sdk(pda::burn_schedule_pda(&to_anchor(&pool)));
+        let accounts = vec![
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new(burn_schedule, false),
+        ];
+        self.send_instruction("close_burn_schedule", accounts, (), &[authority])
+    }
+
+    /// Burns on every `(pool, user_burn_allowance)` pair in one instruction
+    pub fn burn_virtual_token_batch(
+        &mut self,
+        payer: &Keypair,
+        platform_config: Pubkey,
+        burns: &[(Pubkey, Pubkey)],
+        burn_authority: Option<&Keypair>,
+    ) -> std::result::Result<(), TransactionError> {
+        let mut accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(platform_config, false),
+        ];
+
+        let mut signers: Vec<&Keypair> = vec![payer];
+        if let Some(auth) = burn_authority {
+            accounts.push(AccountMeta::new(auth.pubkey(), true));
+            signers.push(auth);
+        } else {
+            accounts.push(AccountMeta::new_readonly(self.program_id, false));
+        }
+
+        accounts.extend(self.event_cpi_accounts());
+        for (pool, user_burn_allowance) in burns {
+            accounts.push(AccountMeta::new(*pool, false));
+            accounts.push(AccountMeta::new(*user_burn_allowance, false));
+        }
+
+        self.send_instruction("burn_virtual_token_batch", accounts, (), &signers)
+    }
+
+    /// `signer` only pays the transaction fee, the rent goes to `refund_recipient`
+    pub fn close_user_burn_allowance(
+        &mut self,
+        signer: &Keypair,
+        owner: Pubkey,
+        platform_config: Pubkey,
+        user_burn_allowance: Pubkey,
+        refund_recipient: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(owner, false),
+            AccountMeta::new(user_burn_allowance, false),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new(refund_recipient, false),
+        ];
+        self.send_instruction("close_user_burn_allowance", accounts, (), &[signer])
+    }
+
+    pub fn migrate_burn_allowance(
+        &mut self,
+        payer: &Keypair,
+        stale_burn_allowance: Pubkey,
+        burn_tier_index: u8,
+        pool: Option<Pubkey>,
+    ) -> std::result::Result<Pubkey, TransactionError> {
+        use crate::instructions::MigrateBurnAllowanceArgs;
+
+        let stale = self.get_user_burn_allowance(&stale_burn_allowance).unwrap();
+        let platform_config = Pubkey::from(stale.platform_config.to_bytes());
+        let platform_config_account = self.svm.get_account(&platform_config).unwrap();
+        let platform_config_data = cpmm_state::PlatformConfig::try_deserialize(
+            &mut platform_config_account.data.as_slice(),
+        )
+        .unwrap();
+        let (user_burn_allowance_pda, _bump) = to_sdk(pda::user_burn_allowance_pda(
+            &stale.user,
+            &stale.platform_config,
+            burn_tier_index,
+            platform_config_data.burn_tiers_updated_at,
+        ));
+
+        let accounts = vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(Pubkey::from(stale.user.to_bytes()), false),
+            AccountMeta::new(stale_burn_allowance, false),
+            AccountMeta::new(Pubkey::from(stale.payer.to_bytes()), false),
+            AccountMeta::new(user_burn_allowance_pda, false),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            AccountMeta::new_readonly(pool.unwrap_or(self.program_id), false),
+        ];
+        let args = MigrateBurnAllowanceArgs { burn_tier_index };
+        self.send_instruction("migrate_burn_allowance", accounts, args, &[payer])?;
+        Ok(user_burn_allowance_pda)
+    }
+
     pub fn get_user_burn_allowance(
         &self,
         address: &Pubkey,
@@ -570,6 +1871,116 @@ impl TestRunner {
         })
     }
 
+    pub fn check_allowance_closable(
+        &self,
+        payer: &Keypair,
+        user_burn_allowance: Pubkey,
+        platform_config: Pubkey,
+    ) -> std::result::Result<bool, TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(user_burn_allowance, false),
+            AccountMeta::new_readonly(platform_config, false),
+        ];
+
+        self.simulate_instruction("check_allowance_closable", accounts, (), &[payer])
+    }
+
+    pub fn get_burn_allowance_status(
+        &self,
+        payer: &Keypair,
+        user_burn_allowance: Pubkey,
+        platform_config: Pubkey,
+    ) -> std::result::Result<crate::instructions::BurnAllowanceStatus, TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(user_burn_allowance, false),
+            AccountMeta::new_readonly(platform_config, false),
+        ];
+
+        self.simulate_instruction("get_burn_allowance_status", accounts, (), &[payer])
+    }
+
+    /// `pools` are `(platform_config, pool)` pairs, each swept from the pool's ATA
+    pub fn sweep_treasuries(
+        &mut self,
+        admin: &Keypair,
+        treasury: Pubkey,
+        quote_mint: Pubkey,
+        pools: &[(Pubkey, Pubkey)],
+    ) -> std::result::Result<(), TransactionError> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+        ];
+        for (platform_config, pool) in pools {
+            let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+                &to_anchor(pool),
+                &to_anchor(&quote_mint),
+            );
+            accounts.push(AccountMeta::new_readonly(*platform_config, false));
+            accounts.push(AccountMeta::new(*pool, false));
+            accounts.push(AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false));
+        }
+        self.send_instruction("sweep_treasuries", accounts, (), &[admin])
+    }
+
+    pub fn get_effective_buy_price(
+        &self,
+        payer: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<u128, TransactionError> {
+        let accounts = vec![AccountMeta::new_readonly(pool, false)];
+        self.simulate_instruction("get_effective_buy_price", accounts, (), &[payer])
+    }
+
+    pub fn get_effective_sell_price(
+        &self,
+        payer: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<u128, TransactionError> {
+        let accounts = vec![AccountMeta::new_readonly(pool, false)];
+        self.simulate_instruction("get_effective_sell_price", accounts, (), &[payer])
+    }
+
+    pub fn quote_buy_batch(
+        &self,
+        payer: &Keypair,
+        pool: Pubkey,
+        quote_amounts: Vec<u64>,
+    ) -> std::result::Result<Vec<crate::instructions::BuyQuote>, TransactionError> {
+        let accounts = vec![AccountMeta::new_readonly(pool, false)];
+        let args = crate::instructions::QuoteBuyBatchArgs { quote_amounts };
+        self.simulate_instruction("quote_buy_batch", accounts, args, &[payer])
+    }
+
+    pub fn get_market_cap(
+        &self,
+        payer: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<u128, TransactionError> {
+        let accounts = vec![AccountMeta::new_readonly(pool, false)];
+        self.simulate_instruction("get_market_cap", accounts, (), &[payer])
+    }
+
+    pub fn get_pool_state(
+        &self,
+        payer: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<crate::instructions::PoolStateView, TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let accounts = vec![
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+        ];
+        self.simulate_instruction("get_pool_state", accounts, (), &[payer])
+    }
+
     pub fn set_system_clock(&mut self, timestamp: i64) {
         let mut initial_clock = self.svm.get_sysvar::<Clock>();
         initial_clock.unix_timestamp = timestamp;
@@ -589,7 +2000,7 @@ impl TestRunner {
         );
         let recipient_ata_sdk = solana_sdk::pubkey::Pubkey::from(recipient_ata.to_bytes());
 
-        MintTo::new(&mut self.svm, &authority, &mint, &recipient_ata_sdk, amount)
+        MintTo::new(&mut self.svm, authority, &mint, &recipient_ata_sdk, amount)
             .owner(authority)
             .send()
             .unwrap();
@@ -623,36 +2034,405 @@ impl TestRunner {
         self.send_instruction("claim_creator_fees", accounts, (), &[owner])
     }
 
-    // pub fn claim_platform_fees(
-    //     &mut self,
-    //     admin: &Keypair,
-    //     creator: Pubkey,
-    //     admin_ata: Pubkey,
-    //     mint: Pubkey,
-    // ) -> std::result::Result<(), TransactionError> {
-    //     let platform_config_pda = Pubkey::find_program_address(
-    //         &[cpmm_state::PLATFORM_CONFIG_SEED, creator.as_ref()],
-    //         &self.program_id,
-    //     )
-    //     .0;
-
-    //     let accounts = vec![
-    //         AccountMeta::new(admin.pubkey(), true),
-    //         AccountMeta::new_readonly(creator, false),
-    //         AccountMeta::new(admin_ata, false),
-    //         AccountMeta::new(platform_config_pda, false),
-    //         AccountMeta::new(mint, false),
-    //         AccountMeta::new_readonly(
-    //             Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
-    //             false,
-    //         ),
-    //         AccountMeta::new_readonly(
-    //             Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
-    //             false,
-    //         ),
-    //         AccountMeta::new(solana_sdk_ids::system_program::ID, false),
-    //     ];
-
-    //     self.send_instruction("claim_platform_fees", accounts, (), &[admin])
-    // }
+    pub fn crank_topup(
+        &mut self,
+        keeper: &Keypair,
+        pool: Pubkey,
+        args: crate::instructions::CrankTopupArgs,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let accounts = vec![
+            AccountMeta::new_readonly(keeper.pubkey(), true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+        ];
+
+        self.send_instruction("crank_topup", accounts, args, &[keeper])
+    }
+
+    pub fn flush_burn_queue(
+        &mut self,
+        keeper: &Keypair,
+        keeper_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
+        );
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+
+        let accounts = vec![
+            AccountMeta::new(keeper.pubkey(), true),
+            AccountMeta::new(keeper_ata, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+        ];
+
+        self.send_instruction("flush_burn_queue", accounts, (), &[keeper])
+    }
+
+    pub fn propose_pool_creator(
+        &mut self,
+        creator: &Keypair,
+        pool: Pubkey,
+        new_creator: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(creator.pubkey(), true),
+            AccountMeta::new(pool, false),
+        ];
+        let args = crate::instructions::ProposePoolCreatorArgs {
+            new_creator: anchor_lang::prelude::Pubkey::from(new_creator.to_bytes()),
+        };
+
+        self.send_instruction("propose_pool_creator", accounts, args, &[creator])
+    }
+
+    pub fn accept_pool_creator(
+        &mut self,
+        new_creator: &Keypair,
+        pool: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(new_creator.pubkey(), true),
+            AccountMeta::new(pool, false),
+        ];
+
+        self.send_instruction("accept_pool_creator", accounts, (), &[new_creator])
+    }
+
+    pub fn set_allow_user_burns(
+        &mut self,
+        creator: &Keypair,
+        pool: Pubkey,
+        allow_user_burns: bool,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(creator.pubkey(), true),
+            AccountMeta::new(pool, false),
+        ];
+        let args = crate::instructions::SetAllowUserBurnsArgs { allow_user_burns };
+
+        self.send_instruction("set_allow_user_burns", accounts, args, &[creator])
+    }
+
+    pub fn set_topup_order(
+        &mut self,
+        creator: &Keypair,
+        pool: Pubkey,
+        topup_order: cpmm_state::TopupOrder,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(creator.pubkey(), true),
+            AccountMeta::new(pool, false),
+        ];
+        let args = crate::instructions::SetTopupOrderArgs { topup_order };
+
+        self.send_instruction("set_topup_order", accounts, args, &[creator])
+    }
+
+    pub fn set_fee_holiday(
+        &mut self,
+        authority: &Keypair,
+        pool: Pubkey,
+        platform_config: Pubkey,
+        fee_holiday_until: i64,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(platform_config, false),
+        ];
+        let args = crate::instructions::SetFeeHolidayArgs { fee_holiday_until };
+
+        self.send_instruction("set_fee_holiday", accounts, args, &[authority])
+    }
+
+    pub fn topup_with_deposit(
+        &mut self,
+        depositor: &Keypair,
+        pool: Pubkey,
+        depositor_ata: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+        let token_program = self.token_program_of(&quote_mint);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address_with_program_id(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &pool_data.quote_mint,
+            &anchor_lang::prelude::Pubkey::from(token_program.to_bytes()),
+        );
+        let accounts = vec![
+            AccountMeta::new_readonly(depositor.pubkey(), true),
+            AccountMeta::new(depositor_ata, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        self.send_instruction("topup_with_deposit", accounts, (), &[depositor])
+    }
+
+    pub fn tip_creator(
+        &mut self,
+        tipper: &Keypair,
+        pool: Pubkey,
+        tipper_ata: Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+        let token_program = self.token_program_of(&quote_mint);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address_with_program_id(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &pool_data.quote_mint,
+            &anchor_lang::prelude::Pubkey::from(token_program.to_bytes()),
+        );
+        let accounts = vec![
+            AccountMeta::new_readonly(tipper.pubkey(), true),
+            AccountMeta::new(tipper_ata, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let args = crate::instructions::TipCreatorArgs { amount };
+        self.send_instruction("tip_creator", accounts, args, &[tipper])
+    }
+
+    pub fn lock_initial_liquidity(
+        &mut self,
+        creator: &Keypair,
+        pool: Pubkey,
+        creator_ata: Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let quote_mint = Pubkey::from(pool_data.quote_mint.to_bytes());
+        let token_program = self.token_program_of(&quote_mint);
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address_with_program_id(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &pool_data.quote_mint,
+            &anchor_lang::prelude::Pubkey::from(token_program.to_bytes()),
+        );
+        let accounts = vec![
+            AccountMeta::new_readonly(creator.pubkey(), true),
+            AccountMeta::new(creator_ata, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new_readonly(token_program, false),
+        ];
+        let args = crate::instructions::LockInitialLiquidityArgs { amount };
+        self.send_instruction("lock_initial_liquidity", accounts, args, &[creator])
+    }
+
+    pub fn update_platform_config(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        args: crate::state::UpdatePlatformConfigArgs,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(platform_config, false),
+        ];
+        self.send_instruction("update_platform_config", accounts, args, &[admin])
+    }
+
+    pub fn resync_burn_limiter(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        pool: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new(pool, false),
+        ];
+        self.send_instruction("resync_burn_limiter", accounts, (), &[admin])
+    }
+
+    pub fn sync_reserves(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        pool: Pubkey,
+        mint: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &anchor_lang::prelude::Pubkey::from(pool.to_bytes()),
+            &anchor_lang::prelude::Pubkey::from(mint.to_bytes()),
+        );
+        let accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+        ];
+        self.send_instruction("sync_reserves", accounts, (), &[admin])
+    }
+
+    pub fn update_burn_rate_config(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        burn_limit_bp_x100: u64,
+        burn_min_bp_x100: u64,
+        decay_rate_per_sec_bp_x100: u64,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new(platform_config, false),
+        ];
+        let args = crate::instructions::UpdateBurnRateConfigArgs {
+            burn_limit_bp_x100,
+            burn_min_bp_x100,
+            decay_rate_per_sec_bp_x100,
+        };
+        self.send_instruction("update_burn_rate_config", accounts, args, &[admin])
+    }
+
+    pub fn propose_config_change(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        args: crate::state::UpdatePlatformConfigArgs,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new(self.get_pending_config_change_pda(platform_config), false),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        self.send_instruction("propose_config_change", accounts, args, &[admin])
+    }
+
+    pub fn apply_config_change(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(platform_config, false),
+            AccountMeta::new(self.get_pending_config_change_pda(platform_config), false),
+        ];
+        self.send_instruction("apply_config_change", accounts, (), &[admin])
+    }
+
+    pub fn set_platform_paused(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        paused: bool,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new(platform_config, false),
+        ];
+        let args = crate::instructions::SetPlatformPausedArgs { paused };
+        self.send_instruction("set_platform_paused", accounts, args, &[admin])
+    }
+
+    pub fn set_vta_frozen(
+        &mut self,
+        admin: &Keypair,
+        pool: Pubkey,
+        virtual_token_account: Pubkey,
+        frozen: bool,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+        let accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(virtual_token_account, false),
+        ];
+        let args = crate::instructions::SetVtaFrozenArgs { frozen };
+        self.send_instruction("set_vta_frozen", accounts, args, &[admin])
+    }
+
+    pub fn rescale_virtual_reserve(
+        &mut self,
+        admin: &Keypair,
+        platform_config: Pubkey,
+        pool: Pubkey,
+    ) -> std::result::Result<(), TransactionError> {
+        let accounts = vec![
+            AccountMeta::new_readonly(admin.pubkey(), true),
+            AccountMeta::new_readonly(platform_config, false),
+            AccountMeta::new(pool, false),
+        ];
+
+        self.send_instruction("rescale_virtual_reserve", accounts, (), &[admin])
+    }
+
+    pub fn claim_platform_fees(
+        &mut self,
+        admin: &Keypair,
+        admin_ata: Pubkey,
+        mint: Pubkey,
+        pool: Pubkey,
+        amount: Option<u64>,
+    ) -> std::result::Result<(), TransactionError> {
+        let pool_ata = anchor_spl::associated_token::get_associated_token_address(
+            &to_anchor(&pool),
+            &to_anchor(&mint),
+        );
+        let pool_account = self.svm.get_account(&pool).unwrap();
+        let pool_data =
+            cpmm_state::CbmmPool::try_deserialize(&mut pool_account.data.as_slice()).unwrap();
+
+        let accounts = vec![
+            AccountMeta::new(admin.pubkey(), true),
+            AccountMeta::new(admin_ata, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(Pubkey::from(pool_ata.to_bytes()), false),
+            AccountMeta::new_readonly(Pubkey::from(pool_data.platform_config.to_bytes()), false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::token::spl_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(
+                Pubkey::from(anchor_spl::associated_token::ID.to_bytes()),
+                false,
+            ),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+
+        self.send_instruction(
+            "claim_platform_fees",
+            accounts,
+            ClaimPlatformFeesArgs { amount },
+            &[admin],
+        )
+    }
 }
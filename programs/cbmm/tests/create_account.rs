@@ -8,7 +8,7 @@ use solana_sdk::signature::{Keypair, Signer};
 fn create_account() {
     let mut svm = LiteSVM::new();
     let user = Keypair::new();
-    let user_addr: Address = Address::from(user.pubkey());
+    let user_addr: Address = user.pubkey();
     svm.airdrop(&user_addr, 1_000_000_000).unwrap();
     let balance = svm.get_balance(&user_addr).unwrap();
     assert_eq!(balance, 1_000_000_000);